@@ -0,0 +1,97 @@
+use crate::app::App;
+use crate::dates::DateFormat;
+use chrono::{Days, NaiveDate};
+
+// one day of the agenda with its TODO items as (list, index) pairs
+pub struct Day {
+    pub label: String,
+    pub items: Vec<(usize, usize)>,
+}
+
+// overdue items followed by each of the `days` days starting today, across all lists; a
+// recurring item is on every day it comes back within them as well
+pub fn agenda(app: &App, today: NaiveDate, days: u64, dates: &DateFormat) -> Vec<Day> {
+    let end = today + Days::new(days);
+    let mut due = Vec::new();
+    for (list, todos) in app.lists.iter().map(|list| &list.todos).enumerate() {
+        for (index, item) in todos.iter().enumerate() {
+            let Some(first) = item.due else {
+                continue;
+            };
+            let mut on = vec![first];
+            if let Some(recur) = &item.recur {
+                // as if done on each day it is due
+                let mut last = first;
+                while let Some(next) = recur.next(Some(last), last).filter(|&next| next < end) {
+                    if next >= today {
+                        on.push(next);
+                    }
+                    last = next;
+                }
+            }
+            due.push(((list, index), on));
+        }
+    }
+    let due_on = |matches: &dyn Fn(NaiveDate) -> bool| -> Vec<(usize, usize)> {
+        due.iter()
+            .filter(|(_, on)| on.iter().any(|&date| matches(date)))
+            .map(|&(item, _)| item)
+            .collect()
+    };
+
+    let mut agenda = Vec::new();
+    let overdue = due_on(&|due| due < today);
+    if !overdue.is_empty() {
+        agenda.push(Day {
+            label: "Overdue".to_string(),
+            items: overdue,
+        });
+    }
+    for offset in 0..days {
+        let date = today + Days::new(offset);
+        let label = match offset {
+            0 => "Today".to_string(),
+            1 => "Tomorrow".to_string(),
+            _ => dates.day(date),
+        };
+        agenda.push(Day {
+            label,
+            items: due_on(&|due| due == date),
+        });
+    }
+    agenda
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::List;
+    use crate::item::Item;
+
+    #[test]
+    fn recurring_items_are_on_each_day_they_come_back() {
+        let mut list = List::new("main");
+        list.todos = vec![
+            Item::parse("water the plants due:2025-07-01 rec:2d"),
+            Item::parse("stand-up due:2025-07-02 rec:daily"),
+            Item::parse("dentist due:2025-07-04"),
+        ];
+        let mut app = App::default();
+        app.lists = vec![list];
+        let today = NaiveDate::from_ymd_opt(2025, 7, 2).unwrap();
+        let days = agenda(&app, today, 5, &DateFormat::default());
+        let items: Vec<(&str, Vec<usize>)> = days
+            .iter()
+            .map(|day| {
+                let items = day.items.iter().map(|&(_, index)| index).collect();
+                (day.label.as_str(), items)
+            })
+            .collect();
+        assert_eq!(items[0], ("Overdue", vec![0]));
+        assert_eq!(items[1], ("Today", vec![1]));
+        assert_eq!(items[2], ("Tomorrow", vec![0, 1]));
+        assert_eq!(items[3].1, [1, 2]);
+        assert_eq!(items[4].1, [0, 1]);
+        assert_eq!(items[5].1, [1]);
+    }
+}
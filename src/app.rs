@@ -0,0 +1,1910 @@
+use crate::board::Column;
+use crate::filter::Filter;
+use crate::fuzzy;
+use crate::group::Grouping;
+use crate::habit;
+use crate::item::{self, Item, State, Urgency};
+use crate::random::Random;
+use crate::session::Session;
+use chrono::{Local, NaiveDateTime, NaiveTime};
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+// letters and digits of a new item id
+const ID_LENGTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Status {
+    #[default]
+    Todo,
+    Done,
+    Archived,
+    // deleted items, purged for good from there
+    Trashed,
+}
+
+impl Status {
+    // in tab order
+    pub const ALL: [Status; 4] = [
+        Status::Todo,
+        Status::Done,
+        Status::Archived,
+        Status::Trashed,
+    ];
+
+    // where toggling an item sends it, archived items go back to DONE; trashed items go
+    // back to TODO or DONE depending on whether they were completed, see `restore_range`
+    pub fn toggle(&self) -> Self {
+        match self {
+            Status::Todo | Status::Archived => Status::Done,
+            Status::Done | Status::Trashed => Status::Todo,
+        }
+    }
+
+    // the tab `delta` steps away, wrapping around
+    pub fn cycle(&self, delta: isize) -> Self {
+        let index = Status::ALL
+            .iter()
+            .position(|status| status == self)
+            .unwrap();
+        Status::ALL[(index as isize + delta).rem_euclid(Status::ALL.len() as isize) as usize]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Todo => "TODO",
+            Status::Done => "DONE",
+            Status::Archived => "ARCHIVE",
+            Status::Trashed => "TRASH",
+        }
+    }
+}
+
+// every list mutation goes through an Action so that its inverse can be recorded
+#[derive(Debug, Clone)]
+pub enum Action {
+    Insert(Status, usize, Item),
+    Remove(Status, usize),
+    Replace(Status, usize, Item),
+    Move {
+        from: (Status, usize),
+        to: (Status, usize),
+    },
+    Batch(Vec<Action>),
+    // runs the action against another list than the active one
+    In(usize, Box<Action>),
+}
+
+impl Action {
+    // where the affected item lives after the action was applied to `list`
+    fn target(&self, list: usize) -> Option<(usize, Status, usize)> {
+        match self {
+            Action::Insert(status, index, _) | Action::Replace(status, index, _) => {
+                Some((list, *status, *index))
+            }
+            Action::Remove(..) => None,
+            Action::Move { to, .. } => Some((list, to.0, to.1)),
+            Action::Batch(actions) => actions.iter().find_map(|action| action.target(list)),
+            Action::In(list, action) => action.target(*list),
+        }
+    }
+}
+
+// a named set of TODO, DONE, archived and trashed items, e.g. "work" or "groceries"
+#[derive(Clone, Default)]
+pub struct List {
+    pub name: String,
+    pub todos: Vec<Item>,
+    pub dones: Vec<Item>,
+    pub archived: Vec<Item>,
+    pub trashed: Vec<Item>,
+    todo_current: usize,
+    done_current: usize,
+    archive_current: usize,
+    trash_current: usize,
+}
+
+impl List {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn items(&self, status: Status) -> &Vec<Item> {
+        match status {
+            Status::Todo => &self.todos,
+            Status::Done => &self.dones,
+            Status::Archived => &self.archived,
+            Status::Trashed => &self.trashed,
+        }
+    }
+
+    pub fn items_mut(&mut self, status: Status) -> &mut Vec<Item> {
+        match status {
+            Status::Todo => &mut self.todos,
+            Status::Done => &mut self.dones,
+            Status::Archived => &mut self.archived,
+            Status::Trashed => &mut self.trashed,
+        }
+    }
+
+    fn current(&self, status: Status) -> usize {
+        match status {
+            Status::Todo => self.todo_current,
+            Status::Done => self.done_current,
+            Status::Archived => self.archive_current,
+            Status::Trashed => self.trash_current,
+        }
+    }
+
+    fn current_mut(&mut self, status: Status) -> &mut usize {
+        match status {
+            Status::Todo => &mut self.todo_current,
+            Status::Done => &mut self.done_current,
+            Status::Archived => &mut self.archive_current,
+            Status::Trashed => &mut self.trash_current,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct App {
+    pub lists: Vec<List>,
+    pub active: usize,
+    pub tab: Status,
+    pub filter: Filter,
+    pub grouping: Grouping,
+    // shows the DONE items under the TODO items in one list
+    pub unified: bool,
+    // leaves the DONE items out of the unified list
+    pub hide_done: bool,
+    // when items due on a day without a time are due, see `Item::deadline`
+    pub due_time: Option<NaiveTime>,
+    // the name the items of whoever runs the program are assigned to, see `Filter::assignee`
+    pub user: Option<String>,
+    // names of the folded sections, a folded section only shows its header
+    collapsed: Vec<String>,
+    // order of a list before it was first sorted, to go back to with `restore_manual_order`
+    manual_order: Option<(usize, Status, Vec<Item>)>,
+    undo_stack: Vec<Action>,
+    redo_stack: Vec<Action>,
+    // where new item ids come from
+    ids: Random,
+    // the columns of the board in order, which `cycle_state` goes through
+    pub columns: Vec<Column>,
+    // counts the changes to the lists, see `generation`
+    generation: u64,
+}
+
+impl App {
+    pub fn list(&self, status: Status) -> &Vec<Item> {
+        self.lists[self.active].items(status)
+    }
+
+    fn list_mut(&mut self, status: Status) -> &mut Vec<Item> {
+        self.lists[self.active].items_mut(status)
+    }
+
+    pub fn current(&self, status: Status) -> usize {
+        self.lists[self.active].current(status)
+    }
+
+    fn current_mut(&mut self, status: Status) -> &mut usize {
+        self.lists[self.active].current_mut(status)
+    }
+
+    pub fn switch_list(&mut self, index: usize) {
+        if index < self.lists.len() {
+            self.active = index;
+        }
+    }
+
+    // the list `delta` steps away from the active one, wrapping around at either end
+    pub fn neighbour_list(&self, delta: isize) -> usize {
+        let len = self.lists.len() as isize;
+        (self.active as isize + delta).rem_euclid(len) as usize
+    }
+
+    pub fn cycle_list(&mut self, delta: isize) {
+        self.active = self.neighbour_list(delta);
+    }
+
+    // the item under the cursor, `None` on an empty list or a folded section
+    pub fn selected(&self) -> Option<&Item> {
+        if self.folded_section().is_some() {
+            return None;
+        }
+        self.list(self.tab).get(self.current(self.tab))
+    }
+
+    // the sections of the list in display order with the items passing the filter;
+    // one unnamed section when the list isn't grouped
+    pub fn sections(&self, status: Status) -> Vec<(String, Vec<usize>)> {
+        let now = Local::now().naive_local();
+        let items = self.list(status);
+        let hidden = folded_away(items);
+        let mut sections: Vec<((u8, String), Vec<usize>)> = Vec::new();
+        let mut roots = Vec::new();
+        let mut root = 0;
+        for (index, item) in items.iter().enumerate() {
+            if item.depth == 0 {
+                root = index;
+            }
+            roots.push(root);
+            if hidden[index] || !self.filter.matches(item, now) {
+                continue;
+            }
+            // subtasks stay in the section of their top level item
+            let key = self
+                .grouping
+                .key(&items[root], items[root].urgency(now, self.due_time));
+            match sections.iter_mut().find(|(other, _)| *other == key) {
+                Some((_, indices)) => indices.push(index),
+                None => sections.push((key, vec![index])),
+            }
+        }
+        sections.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if self.filter.fit.is_some() {
+            for (_, indices) in &mut sections {
+                indices
+                    .sort_by_key(|&index| (items[index].priority.is_none(), items[index].priority));
+            }
+        }
+        // pinned items go first with their subtasks, keeping their order
+        for (_, indices) in &mut sections {
+            indices.sort_by_key(|&index| !items[roots[index]].pinned);
+        }
+        sections
+            .into_iter()
+            .map(|((_, name), indices)| (name, indices))
+            .collect()
+    }
+
+    pub fn is_collapsed(&self, section: &str) -> bool {
+        self.grouping != Grouping::None && self.collapsed.iter().any(|name| name == section)
+    }
+
+    // indices of the items the cursor can go to, in display order; a folded section
+    // is stood in for by its first item
+    pub fn visible(&self, status: Status) -> Vec<usize> {
+        self.sections(status)
+            .into_iter()
+            .flat_map(|(name, mut indices)| {
+                if self.is_collapsed(&name) {
+                    indices.truncate(1);
+                }
+                indices
+            })
+            .collect()
+    }
+
+    // the name of the folded section the cursor is on
+    pub fn folded_section(&self) -> Option<String> {
+        let current = self.current(self.tab);
+        self.sections(self.tab)
+            .into_iter()
+            .find(|(name, indices)| indices.first() == Some(&current) && self.is_collapsed(name))
+            .map(|(name, _)| name)
+    }
+
+    // the items shown from `anchor` to `cursor`, in list order, skipping folded sections
+    pub fn visible_between(&self, anchor: usize, cursor: usize) -> Vec<usize> {
+        let folded: Vec<usize> = self
+            .sections(self.tab)
+            .into_iter()
+            .filter(|(name, _)| self.is_collapsed(name))
+            .flat_map(|(_, indices)| indices)
+            .collect();
+        let visible = self.visible(self.tab);
+        let position = |index| visible.iter().position(|&other| other == index);
+        let (Some(a), Some(b)) = (position(anchor), position(cursor)) else {
+            return Vec::new();
+        };
+        let mut indices: Vec<usize> = visible[a.min(b)..=a.max(b)]
+            .iter()
+            .copied()
+            .filter(|index| !folded.contains(index))
+            .collect();
+        indices.sort();
+        indices
+    }
+
+    // the tabs shown in the list of the current tab, TODO and DONE together in the unified view
+    pub fn shown(&self) -> Vec<Status> {
+        match self.tab {
+            Status::Todo | Status::Done if self.unified && self.hide_done => vec![Status::Todo],
+            Status::Todo | Status::Done if self.unified => vec![Status::Todo, Status::Done],
+            tab => vec![tab],
+        }
+    }
+
+    // the items the cursor can go to across the shown tabs, in display order
+    pub fn positions(&self) -> Vec<(Status, usize)> {
+        self.shown()
+            .into_iter()
+            .flat_map(|status| {
+                self.visible(status)
+                    .into_iter()
+                    .map(move |index| (status, index))
+            })
+            .collect()
+    }
+
+    // steps the cursor `delta` visible items up or down, into the next shown tab at either end
+    fn step(&mut self, delta: isize) {
+        let positions = self.positions();
+        let next = positions
+            .iter()
+            .position(|&position| position == self.position())
+            .and_then(|position| position.checked_add_signed(delta))
+            .and_then(|position| positions.get(position));
+        if let Some(&(status, index)) = next {
+            self.focus(status, index);
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.step(-1);
+    }
+
+    pub fn down(&mut self) {
+        self.step(1);
+    }
+
+    // steps the cursor within the current tab, for selections which can't span tabs
+    pub fn extend(&mut self, delta: isize) {
+        let current = self.current(self.tab);
+        let visible = self.visible(self.tab);
+        let next = visible
+            .iter()
+            .position(|&index| index == current)
+            .and_then(|position| position.checked_add_signed(delta))
+            .and_then(|position| visible.get(position));
+        if let Some(&index) = next {
+            *self.current_mut(self.tab) = index;
+        }
+    }
+
+    // goes to the `n`th item across the shown tabs, or the last one when there are fewer
+    pub fn go_to(&mut self, n: usize) {
+        let positions = self.positions();
+        if let Some(&(status, index)) = positions.get(n).or(positions.last()) {
+            self.focus(status, index);
+        }
+    }
+
+    pub fn toggle_unified(&mut self) {
+        self.unified = !self.unified;
+        self.leave_hidden();
+    }
+
+    pub fn toggle_hide_done(&mut self) {
+        self.hide_done = !self.hide_done;
+        self.leave_hidden();
+    }
+
+    // switches to the next tab, skipping the DONE tab while the unified view hides it
+    pub fn cycle_tab(&mut self, delta: isize) {
+        self.tab = self.tab.cycle(delta);
+        if !self.shown().contains(&self.tab) {
+            self.tab = self.tab.cycle(delta);
+        }
+    }
+
+    // moves the cursor off the DONE items once the unified view hides them
+    fn leave_hidden(&mut self) {
+        if !self.shown().contains(&self.tab) {
+            self.tab = Status::Todo;
+        }
+    }
+
+    pub fn set_grouping(&mut self, grouping: Grouping) {
+        self.grouping = grouping;
+        self.clamp();
+    }
+
+    // folds or unfolds the section the cursor is in, the cursor stays on its header
+    pub fn toggle_section(&mut self) {
+        if self.grouping == Grouping::None {
+            return;
+        }
+        let current = self.current(self.tab);
+        let Some((name, indices)) = self
+            .sections(self.tab)
+            .into_iter()
+            .find(|(_, indices)| indices.contains(&current))
+        else {
+            return;
+        };
+        if let Some(position) = self.collapsed.iter().position(|other| *other == name) {
+            self.collapsed.remove(position);
+        } else {
+            self.collapsed.push(name);
+            *self.current_mut(self.tab) = indices[0];
+        }
+    }
+
+    // the `n`th visible item, or the last one when there are fewer
+    pub fn nth(&self, n: usize) -> usize {
+        let visible = self.visible(self.tab);
+        visible.get(n).or(visible.last()).copied().unwrap_or(0)
+    }
+
+    // the TODO items passing the filter in the order to do them in, most urgent and important
+    // first and in list order otherwise; items with subtasks wait until those are done and
+    // waiting ones until they aren't anymore
+    pub fn next_tasks(&self) -> Vec<usize> {
+        let now = Local::now().naive_local();
+        let items = self.list(Status::Todo);
+        let mut indices: Vec<usize> = (0..items.len())
+            .filter(|&index| {
+                self.filter.matches(&items[index], now)
+                    && items[index].state != Some(State::Waiting)
+                    && !self.has_subtasks(Status::Todo, index)
+            })
+            .collect();
+        indices.sort_by_key(|&index| {
+            let item = &items[index];
+            (
+                item.urgency(now, self.due_time),
+                item.priority.is_none(),
+                item.priority,
+            )
+        });
+        indices
+    }
+
+    // one of `next_tasks` picked at random, more important and older items coming up more
+    // often and overdue ones most of all
+    pub fn pick(&self, random: &mut Random) -> Option<usize> {
+        let now = Local::now().naive_local();
+        let tasks = self.next_tasks();
+        let weights: Vec<u64> = tasks
+            .iter()
+            .map(|&index| {
+                let item = &self.list(Status::Todo)[index];
+                let priority = match item.priority {
+                    Some('A') => 4,
+                    Some('B') => 3,
+                    Some('C') => 2,
+                    _ => 1,
+                };
+                let weeks = item
+                    .created
+                    .map_or(0, |created| (now - created).num_weeks().clamp(0, 4) as u64);
+                let urgency = match item.urgency(now, self.due_time) {
+                    Urgency::Overdue => 3,
+                    Urgency::Today => 2,
+                    Urgency::Later => 1,
+                };
+                priority * (1 + weeks) * urgency
+            })
+            .collect();
+        random.weighted(&weights).map(|position| tasks[position])
+    }
+
+    // the item at `index` and its subtasks
+    pub fn subtree(&self, status: Status, index: usize) -> Range<usize> {
+        index..subtree_end(self.list(status), index)
+    }
+
+    pub fn has_subtasks(&self, status: Status, index: usize) -> bool {
+        self.subtree(status, index).len() > 1
+    }
+
+    // the ascending `indices` of the current tab together with all their subtasks
+    pub fn with_subtasks(&self, indices: Vec<usize>) -> Vec<usize> {
+        self.subtrees(self.tab, indices)
+    }
+
+    fn subtrees(&self, status: Status, indices: Vec<usize>) -> Vec<usize> {
+        let mut all: Vec<usize> = indices
+            .into_iter()
+            .flat_map(|index| self.subtree(status, index))
+            .collect();
+        // a subtask given along with its parent is in the parent's subtree already
+        all.sort();
+        all.dedup();
+        all
+    }
+
+    // the TODO items passing the filter, folded away or not, with their subtasks
+    pub fn open_items(&self) -> Vec<usize> {
+        let indices = self
+            .sections(Status::Todo)
+            .into_iter()
+            .flat_map(|(_, indices)| indices)
+            .collect();
+        self.subtrees(Status::Todo, indices)
+    }
+
+    // hides or shows the subtasks of the selected item, or folds its section when it has none
+    pub fn toggle_fold(&mut self) {
+        let index = self.current(self.tab);
+        if self.selected().is_none() || !self.has_subtasks(self.tab, index) {
+            return self.toggle_section();
+        }
+        let item = &mut self.list_mut(self.tab)[index];
+        item.folded = !item.folded;
+    }
+
+    // nests the selected item and its subtasks one level deeper (positive `delta`) under
+    // the item above, or lifts them one level up
+    pub fn nest(&mut self, delta: isize) -> bool {
+        let index = self.current(self.tab);
+        let items = self.list(self.tab);
+        let Some(item) = self.selected() else {
+            return false;
+        };
+        let allowed = if delta > 0 {
+            index > 0 && items[index - 1].depth >= item.depth
+        } else {
+            item.depth > 0
+        };
+        if !allowed {
+            return false;
+        }
+        let actions = self
+            .subtree(self.tab, index)
+            .map(|index| {
+                let item = &items[index];
+                let item = Item {
+                    depth: item.depth.saturating_add_signed(delta.signum()),
+                    ..item.clone()
+                };
+                Action::Replace(self.tab, index, item)
+            })
+            .collect();
+        self.apply(Action::Batch(actions));
+        true
+    }
+
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+        self.clamp();
+    }
+
+    // the view to come back to on the next launch
+    pub fn session(&self) -> Session {
+        Session {
+            list: self.lists[self.active].name.clone(),
+            tab: self.tab,
+            cursors: self
+                .lists
+                .iter()
+                .flat_map(|list| {
+                    Status::ALL.map(|status| (list.name.clone(), status, list.current(status)))
+                })
+                .collect(),
+            filter: self.filter.clone(),
+            grouping: self.grouping,
+            collapsed: self.collapsed.clone(),
+            unified: self.unified,
+            hide_done: self.hide_done,
+        }
+    }
+
+    // goes back to a saved view, as far as the lists still have what it points to
+    pub fn resume(&mut self, session: Session) {
+        self.filter = session.filter;
+        self.grouping = session.grouping;
+        self.collapsed = session.collapsed;
+        self.unified = session.unified;
+        self.hide_done = session.hide_done;
+        for (name, status, index) in session.cursors {
+            if let Some(list) = self.lists.iter_mut().find(|list| list.name == name) {
+                *list.current_mut(status) = index;
+            }
+        }
+        for index in 0..self.lists.len() {
+            self.active = index;
+            self.clamp();
+        }
+        self.active = self
+            .lists
+            .iter()
+            .position(|list| list.name == session.list)
+            .unwrap_or(0);
+        self.tab = session.tab;
+        self.leave_hidden();
+    }
+
+    // the lists read again from the files or merged with what they hold, the cursors
+    // staying where they were on the lists still there; the history is dropped with the
+    // lists it was of
+    pub fn replace_lists(&mut self, lists: Vec<List>) {
+        let active = self.lists[self.active].name.clone();
+        let cursors: Vec<(String, Status, usize)> = self
+            .lists
+            .iter()
+            .flat_map(|list| {
+                Status::ALL.map(|status| (list.name.clone(), status, list.current(status)))
+            })
+            .collect();
+        self.lists = lists;
+        for (name, status, index) in cursors {
+            if let Some(list) = self.lists.iter_mut().find(|list| list.name == name) {
+                *list.current_mut(status) = index;
+            }
+        }
+        for index in 0..self.lists.len() {
+            self.active = index;
+            self.clamp();
+        }
+        self.active = self
+            .lists
+            .iter()
+            .position(|list| list.name == active)
+            .unwrap_or(0);
+        self.manual_order = None;
+        self.generation += 1;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.leave_hidden();
+    }
+
+    // every `#tag`, `+project` and `@context` word of the active list, sorted
+    pub fn filter_words(&self) -> Vec<String> {
+        let mut words: Vec<String> = Status::ALL
+            .into_iter()
+            .flat_map(|status| self.list(status))
+            .flat_map(|item| item.text.split_whitespace())
+            .filter(|word| word.len() > 1 && word.starts_with(['#', '+', '@']))
+            .map(str::to_lowercase)
+            .collect();
+        words.sort();
+        words.dedup();
+        words
+    }
+
+    // every `@context` of the active list, sorted
+    pub fn contexts(&self) -> Vec<String> {
+        self.filter_words()
+            .into_iter()
+            .filter(|word| word.starts_with('@'))
+            .collect()
+    }
+
+    // every `#tag` of the active list with how many of its items have it, sorted
+    pub fn tag_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for item in Status::ALL.into_iter().flat_map(|status| self.list(status)) {
+            let mut item_tags: Vec<String> = tags(&item.text)
+                .into_iter()
+                .map(str::to_lowercase)
+                .collect();
+            item_tags.sort();
+            item_tags.dedup();
+            for tag in item_tags {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        counts.into_iter().collect()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.selected().is_some_and(|item| {
+            tags(&item.text)
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(tag))
+        })
+    }
+
+    // adds the `#tag` to the selected item, or takes it off when the item has it already;
+    // whether it was added, `None` when there is no item or the tag is all its text
+    pub fn toggle_tag(&mut self, tag: &str) -> Option<bool> {
+        let tag = format!("#{}", tag.trim_start_matches('#'));
+        let added = !self.has_tag(&tag);
+        let mut item = self.selected()?.clone();
+        item.text = if added {
+            format!("{} {}", item.text, tag)
+        } else {
+            item.text
+                .split_whitespace()
+                .filter(|word| !word.eq_ignore_ascii_case(&tag))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        if item.text.is_empty() {
+            return None;
+        }
+        self.edit(item);
+        Some(added)
+    }
+
+    // checks or unchecks the `step`th checklist step of the selected item
+    pub fn toggle_step(&mut self, step: usize) {
+        let Some(mut item) = self.selected().cloned() else {
+            return;
+        };
+        let Some(&(index, checked, text)) = item.steps().get(step) else {
+            return;
+        };
+        item.notes[index] = Item::step(!checked, text);
+        self.edit(item);
+    }
+
+    // adds an unchecked step after the last one of the selected item's checklist, or at
+    // the end of its notes
+    pub fn add_step(&mut self, text: &str) {
+        let Some(mut item) = self.selected().cloned() else {
+            return;
+        };
+        let index = item
+            .steps()
+            .last()
+            .map_or(item.notes.len(), |(index, ..)| index + 1);
+        item.notes.insert(index, Item::step(false, text));
+        self.edit(item);
+    }
+
+    pub fn remove_step(&mut self, step: usize) {
+        let Some(mut item) = self.selected().cloned() else {
+            return;
+        };
+        let Some(&(index, ..)) = item.steps().get(step) else {
+            return;
+        };
+        item.notes.remove(index);
+        self.edit(item);
+    }
+
+    pub fn matches(&self, query: &str) -> Vec<(Status, usize)> {
+        let query = query.to_ascii_lowercase();
+        let mut matches = Vec::new();
+        if query.is_empty() {
+            return matches;
+        }
+        for status in Status::ALL {
+            for index in self.visible(status) {
+                if self.list(status)[index]
+                    .text
+                    .to_ascii_lowercase()
+                    .contains(&query)
+                {
+                    matches.push((status, index));
+                }
+            }
+        }
+        matches
+    }
+
+    // moves to the next match after `from` (TODO items come before DONE and archived items),
+    // wrapping around the end of the lists
+    pub fn search(
+        &mut self,
+        query: &str,
+        from: (Status, usize),
+        forward: bool,
+        skip_from: bool,
+    ) -> bool {
+        let matches = self.matches(query);
+        let found = if forward {
+            matches
+                .iter()
+                .find(|pos| {
+                    if skip_from {
+                        **pos > from
+                    } else {
+                        **pos >= from
+                    }
+                })
+                .or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|pos| {
+                    if skip_from {
+                        **pos < from
+                    } else {
+                        **pos <= from
+                    }
+                })
+                .or(matches.last())
+        };
+        match found {
+            Some(&(status, index)) => {
+                self.focus(status, index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // items across all tabs ordered by fuzzy score, best first
+    pub fn fuzzy_matches(&self, query: &str) -> Vec<(Status, usize)> {
+        let mut scored = Vec::new();
+        for status in Status::ALL {
+            for index in self.visible(status) {
+                if let Some(score) = fuzzy::score(query, &self.list(status)[index].text) {
+                    scored.push((score, status, index));
+                }
+            }
+        }
+        scored.sort_by_key(|&(score, ..)| std::cmp::Reverse(score));
+        scored
+            .into_iter()
+            .map(|(_, status, index)| (status, index))
+            .collect()
+    }
+
+    pub fn position(&self) -> (Status, usize) {
+        (self.tab, self.current(self.tab))
+    }
+
+    pub fn last(&self) -> usize {
+        self.visible(self.tab).last().copied().unwrap_or(0)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.focus(self.tab, index);
+    }
+
+    pub fn focus(&mut self, status: Status, index: usize) {
+        self.tab = status;
+        *self.current_mut(status) = index;
+        self.clamp();
+    }
+
+    // keeps both cursors on an existing item, and on a visible one while filtering
+    fn clamp(&mut self) {
+        for status in Status::ALL {
+            let len = self.list(status).len();
+            let current = self.current_mut(status);
+            if *current >= len {
+                *current = len.saturating_sub(1);
+            }
+            let current = *current;
+            let visible = self.visible(status);
+            if !visible.is_empty() && !visible.contains(&current) {
+                let index = visible
+                    .iter()
+                    .find(|&&index| index > current)
+                    .or(visible.last());
+                *self.current_mut(status) = *index.unwrap();
+            }
+        }
+    }
+
+    // goes up whenever the items change in a way that is saved, so that the lists are only
+    // written out again for the files after they did
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // applies the action and returns the action that reverts it
+    fn execute(&mut self, action: Action) -> Action {
+        self.generation += 1;
+        let inverse = match action {
+            Action::Insert(status, index, mut item) => {
+                // copies of an item, like pasted ones, are items of their own
+                let taken = self.ids();
+                if item.id.as_ref().is_none_or(|id| taken.contains(id)) {
+                    item.id = Some(self.new_id(&taken));
+                }
+                self.list_mut(status).insert(index, item);
+                Action::Remove(status, index)
+            }
+            Action::Remove(status, index) => {
+                let item = self.list_mut(status).remove(index);
+                Action::Insert(status, index, item)
+            }
+            Action::Replace(status, index, mut item) => {
+                if item.id.is_none() {
+                    item.id = self.list(status)[index].id.clone();
+                }
+                let old = std::mem::replace(&mut self.list_mut(status)[index], item);
+                Action::Replace(status, index, old)
+            }
+            Action::Move { from, to } => {
+                let item = self.list_mut(from.0).remove(from.1);
+                self.list_mut(to.0).insert(to.1, item);
+                Action::Move { from: to, to: from }
+            }
+            Action::Batch(actions) => {
+                let mut inverses: Vec<Action> = actions
+                    .into_iter()
+                    .map(|action| self.execute(action))
+                    .collect();
+                inverses.reverse();
+                Action::Batch(inverses)
+            }
+            Action::In(list, action) => {
+                let active = std::mem::replace(&mut self.active, list);
+                let inverse = self.execute(*action);
+                self.active = active;
+                Action::In(list, Box::new(inverse))
+            }
+        };
+        self.clamp();
+        inverse
+    }
+
+    // the ids of the items of every list
+    fn ids(&self) -> HashSet<String> {
+        self.lists
+            .iter()
+            .flat_map(|list| Status::ALL.map(|status| list.items(status)))
+            .flatten()
+            .filter_map(|item| item.id.clone())
+            .collect()
+    }
+
+    fn new_id(&mut self, taken: &HashSet<String>) -> String {
+        loop {
+            let id = self.ids.word(ID_LENGTH);
+            if !taken.contains(&id) {
+                return id;
+            }
+        }
+    }
+
+    // gives the loaded items without an id, or with the id of an item before them, one
+    pub fn assign_ids(&mut self) {
+        let mut taken = self.ids();
+        let mut seen = HashSet::new();
+        for list in 0..self.lists.len() {
+            for status in Status::ALL {
+                for index in 0..self.lists[list].items(status).len() {
+                    let id = self.lists[list].items(status)[index].id.clone();
+                    if id.is_some_and(|id| seen.insert(id)) {
+                        continue;
+                    }
+                    let id = self.new_id(&taken);
+                    taken.insert(id.clone());
+                    seen.insert(id.clone());
+                    self.lists[list].items_mut(status)[index].id = Some(id);
+                    self.generation += 1;
+                }
+            }
+        }
+    }
+
+    // actions are recorded against the list they were applied to, so undo
+    // works no matter which list is active
+    pub fn apply(&mut self, action: Action) {
+        let inverse = self.execute(Action::In(self.active, Box::new(action)));
+        self.undo_stack.push(inverse);
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(action) => {
+                let target = action.target(self.active);
+                let inverse = self.execute(action);
+                if let Some((list, status, index)) = target {
+                    self.active = list;
+                    self.focus(status, index);
+                }
+                self.redo_stack.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(action) => {
+                let target = action.target(self.active);
+                let inverse = self.execute(action);
+                if let Some((list, status, index)) = target {
+                    self.active = list;
+                    self.focus(status, index);
+                }
+                self.undo_stack.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn add(&mut self, item: Item) {
+        self.add_all(vec![item]);
+    }
+
+    // appends the items to TODO as one undoable step and selects the last one
+    pub fn add_all(&mut self, items: Vec<Item>) {
+        if items.is_empty() {
+            return;
+        }
+        let now = Local::now().naive_local();
+        let len = self.list(Status::Todo).len();
+        let actions: Vec<Action> = items
+            .into_iter()
+            .enumerate()
+            .map(|(offset, mut item)| {
+                item.created.get_or_insert(now);
+                Action::Insert(Status::Todo, len + offset, item)
+            })
+            .collect();
+        let last = len + actions.len() - 1;
+        self.apply(Action::Batch(actions));
+        self.focus(Status::Todo, last);
+    }
+
+    // inserts the item as the last subtask of the selected todo and selects it
+    pub fn add_subtask(&mut self, mut item: Item) {
+        let parent = self.current(self.tab);
+        if self.tab != Status::Todo || self.selected().is_none() {
+            return self.add(item);
+        }
+        let index = self.subtree(Status::Todo, parent).end;
+        item.depth = self.list(Status::Todo)[parent].depth + 1;
+        item.created.get_or_insert(Local::now().naive_local());
+        let mut unfolded = self.list(Status::Todo)[parent].clone();
+        unfolded.folded = false;
+        self.apply(Action::Batch(vec![
+            Action::Replace(Status::Todo, parent, unfolded),
+            Action::Insert(Status::Todo, index, item),
+        ]));
+        self.focus(Status::Todo, index);
+    }
+
+    // stamps the item as touched, which holds off `bump_priorities`
+    pub fn edit(&mut self, item: Item) {
+        let index = self.current(self.tab);
+        if index < self.list(self.tab).len() {
+            let touched = Some(Local::now().naive_local());
+            self.apply(Action::Replace(self.tab, index, Item { touched, ..item }));
+        }
+    }
+
+    // range operations work on the items shown from `anchor` to `cursor`,
+    // see `visible_between`
+    // moves the items to the trash, or purges them for good when they're in it already
+    pub fn delete_range(&mut self, anchor: usize, cursor: usize) {
+        let indices = self.with_subtasks(self.visible_between(anchor, cursor));
+        if self.tab != Status::Trashed {
+            self.move_items(self.tab, indices, Status::Trashed);
+        } else if !indices.is_empty() {
+            let actions = indices
+                .into_iter()
+                .rev()
+                .map(|index| Action::Remove(self.tab, index))
+                .collect();
+            self.apply(Action::Batch(actions));
+        }
+    }
+
+    pub fn transfer(&mut self) {
+        if self.log_habit() {
+            return;
+        }
+        let index = self.current(self.tab);
+        self.transfer_range(index, index);
+    }
+
+    // toggles the item like `transfer`, the cursor following it when its new tab is shown
+    // in the same list as in the unified view
+    pub fn toggle_in_place(&mut self) {
+        let dst = self.tab.toggle();
+        let index = self.list(dst).len();
+        self.transfer();
+        if self.shown().contains(&dst) {
+            self.focus(dst, index);
+        }
+    }
+
+    // subtasks go along with their parent; trashed items are restored instead
+    pub fn transfer_range(&mut self, anchor: usize, cursor: usize) {
+        let indices = self.with_subtasks(self.visible_between(anchor, cursor));
+        match self.tab {
+            Status::Trashed => self.move_items_to(
+                Status::Trashed,
+                indices,
+                |item| match item.completed {
+                    Some(_) => Status::Done,
+                    None => Status::Todo,
+                },
+                None,
+            ),
+            Status::Todo => {
+                // habits are logged one at a time instead, see `log_habit`
+                let indices = indices
+                    .into_iter()
+                    .filter(|&index| self.list(Status::Todo)[index].habit.is_none())
+                    .collect();
+                self.move_items(Status::Todo, indices, Status::Done)
+            }
+            tab => self.move_items(tab, indices, tab.toggle()),
+        }
+    }
+
+    // logs the selected TODO habit as done today, or takes today back out, returning
+    // whether it was a habit
+    pub fn log_habit(&mut self) -> bool {
+        let Some(item) = self.selected().filter(|_| self.tab == Status::Todo) else {
+            return false;
+        };
+        let Some(days) = &item.habit else {
+            return false;
+        };
+        let today = Local::now().date_naive();
+        self.edit(Item {
+            habit: Some(habit::toggle(days, today)),
+            ..item.clone()
+        });
+        true
+    }
+
+    // completes TODO items as cancelled, without bringing recurring ones back
+    pub fn cancel_range(&mut self, anchor: usize, cursor: usize) {
+        if self.tab != Status::Todo {
+            return;
+        }
+        let indices = self.with_subtasks(self.visible_between(anchor, cursor));
+        self.move_items_to(
+            Status::Todo,
+            indices,
+            |_| Status::Done,
+            Some(&State::Cancelled),
+        );
+    }
+
+    // moves the selected TODO item on to the next column of the board holding TODO items,
+    // from the last one back to the first
+    pub fn cycle_state(&mut self) {
+        let pending: Vec<&Column> = self
+            .columns
+            .iter()
+            .filter(|column| column.status() == Status::Todo)
+            .collect();
+        if self.tab != Status::Todo || pending.is_empty() {
+            return;
+        }
+        if let Some(item) = self.selected() {
+            let next = pending
+                .iter()
+                .position(|column| column.state() == item.state.as_ref())
+                .map_or(0, |position| (position + 1) % pending.len());
+            self.edit(Item {
+                state: pending[next].state().cloned(),
+                ..item.clone()
+            });
+        }
+    }
+
+    // pins the selected item to the top of its section, or unpins it
+    pub fn toggle_pin(&mut self) {
+        if let Some(item) = self.selected() {
+            self.edit(Item {
+                pinned: !item.pinned,
+                ..item.clone()
+            });
+        }
+    }
+
+    // moves the selected TODO or DONE item into the column of the board, with its subtasks
+    // when that takes it to the other tab
+    pub fn move_to_column(&mut self, column: &Column) {
+        let (src, dst) = (self.tab, column.status());
+        let Some(item) = self.selected() else {
+            return;
+        };
+        if src == dst {
+            let item = Item {
+                state: column.state().cloned(),
+                ..item.clone()
+            };
+            self.edit(item);
+        } else if matches!(src, Status::Todo | Status::Done) {
+            let indices = self.with_subtasks(vec![self.current(src)]);
+            self.move_items_to(src, indices, |_| dst, column.state());
+        }
+    }
+
+    // moves DONE items to the archive, or archived items back to DONE
+    pub fn archive_range(&mut self, anchor: usize, cursor: usize) {
+        let dst = match self.tab {
+            Status::Todo | Status::Trashed => return,
+            Status::Done => Status::Archived,
+            Status::Archived => Status::Done,
+        };
+        let indices = self.with_subtasks(self.visible_between(anchor, cursor));
+        self.move_items(self.tab, indices, dst);
+    }
+
+    // archives every DONE item completed more than `days` days ago and returns how many,
+    // a top level item going with all its subtasks only when they are all that old
+    pub fn archive_old(&mut self, days: i64) -> usize {
+        let cutoff = Local::now().naive_local() - chrono::Duration::days(days);
+        let items = self.list(Status::Done);
+        let mut indices = Vec::new();
+        let mut index = 0;
+        while index < items.len() {
+            let subtree = index..subtree_end(items, index);
+            index = subtree.end;
+            if items[subtree.clone()]
+                .iter()
+                .all(|item| item.completed.is_some_and(|completed| completed < cutoff))
+            {
+                indices.extend(subtree);
+            }
+        }
+        let count = indices.len();
+        self.move_items(Status::Done, indices, Status::Archived);
+        count
+    }
+
+    // purges the items trashed more than `days` days ago from every list and returns how many,
+    // done once on startup so it can't be undone
+    pub fn purge_trash(&mut self, days: i64) -> usize {
+        let cutoff = Local::now().naive_local() - chrono::Duration::days(days);
+        let mut count = 0;
+        for list in self.lists.iter_mut() {
+            let len = list.trashed.len();
+            list.trashed
+                .retain(|item| item.trashed.is_none_or(|trashed| trashed >= cutoff));
+            count += len - list.trashed.len();
+        }
+        if count > 0 {
+            self.generation += 1;
+            self.clamp();
+        }
+        count
+    }
+
+    // raises the priority of the pending items of every list a level for every `days` days
+    // since they were last touched or created, and returns how many went up; done once on
+    // startup like `purge_trash`, habits and items without a creation time left alone
+    pub fn bump_priorities(&mut self, days: i64) -> usize {
+        if days <= 0 {
+            return 0;
+        }
+        let now = Local::now().naive_local();
+        let mut count = 0;
+        for item in self.lists.iter_mut().flat_map(|list| list.todos.iter_mut()) {
+            let Some(since) = item.touched.or(item.created) else {
+                continue;
+            };
+            // a clock set back leaves `since` in the future
+            let levels = ((now - since).num_days() / days).max(0);
+            if item.habit.is_some() || levels == 0 {
+                continue;
+            }
+            let priority = item::shift_priority(item.priority, levels as isize);
+            if priority == item.priority {
+                continue;
+            }
+            item.priority = priority;
+            item.touched = Some(since + chrono::Duration::days(levels * days));
+            count += 1;
+        }
+        if count > 0 {
+            self.generation += 1;
+        }
+        count
+    }
+
+    // `archive_old` for every list, returning how many items went; done once on startup so
+    // it can't be undone
+    pub fn auto_archive(&mut self, days: i64) -> usize {
+        if days <= 0 {
+            return 0;
+        }
+        let active = self.active;
+        let mut count = 0;
+        for list in 0..self.lists.len() {
+            self.active = list;
+            count += self.archive_old(days);
+        }
+        self.active = active;
+        if count > 0 {
+            self.undo_stack.clear();
+        }
+        count
+    }
+
+    // completes every TODO item of `open_items` and returns how many
+    pub fn complete_all(&mut self) -> usize {
+        let indices = self.open_items();
+        let count = indices.len();
+        self.move_items(Status::Todo, indices, Status::Done);
+        count
+    }
+
+    // archives every DONE item and returns how many
+    pub fn archive_done(&mut self) -> usize {
+        let count = self.list(Status::Done).len();
+        self.move_items(Status::Done, (0..count).collect(), Status::Archived);
+        count
+    }
+
+    // appends the items at the ascending `indices` of `src` to `dst` as one undoable step,
+    // completing them when they leave TODO and reopening them when they go back
+    fn move_items(&mut self, src: Status, indices: Vec<usize>, dst: Status) {
+        self.move_items_to(src, indices, |_| dst, None);
+    }
+
+    // like `move_items` with `dst` picking the tab of each top level item, its subtasks
+    // going along; items going to the trash are stamped with the time. completed recurring
+    // items are put back in their place with their subtasks, due on the next day of their
+    // rule unless cancelled. they are put `into` the state when given, and otherwise keep
+    // theirs as far as it fits their new tab
+    fn move_items_to(
+        &mut self,
+        src: Status,
+        indices: Vec<usize>,
+        dst: impl Fn(&Item) -> Status,
+        into: Option<&State>,
+    ) {
+        if indices.is_empty() {
+            return;
+        }
+        let now = Local::now().naive_local();
+        let mut lens = Status::ALL.map(|status| self.list(status).len());
+        let depths = detached_depths(self.list(src), &indices);
+        let mut target = src;
+        let mut recurs = false;
+        // where the recurring items go back to once the others are gone, and their copies
+        let mut renewed = Vec::new();
+        let mut actions = Vec::new();
+        for (offset, (index, depth)) in indices.into_iter().zip(depths).enumerate() {
+            let item = &self.list(src)[index];
+            if depth == 0 {
+                target = dst(item);
+                recurs = src == Status::Todo
+                    && into != Some(&State::Cancelled)
+                    && matches!(target, Status::Done | Status::Archived)
+                    && item.recur.is_some();
+            }
+            if recurs {
+                let due = match &item.recur {
+                    Some(recur) if depth == 0 => recur.next(item.due, now.date()),
+                    _ => item.due,
+                };
+                let copy = Item {
+                    id: None,
+                    state: None,
+                    due,
+                    created: Some(now),
+                    pomodoros: 0,
+                    tracked: 0,
+                    snoozed: None,
+                    mark: None,
+                    flash: None,
+                    ..item.clone()
+                };
+                renewed.push((index - offset, copy));
+            }
+            let completed = match (src, target) {
+                (_, Status::Todo) => None,
+                (Status::Todo, Status::Done | Status::Archived) => Some(now),
+                _ => item.completed,
+            };
+            let state = match into {
+                Some(state) => Some(state.clone()),
+                None => item.state.clone().filter(|state| state.fits(target)),
+            };
+            let item = Item {
+                completed,
+                state,
+                trashed: (target == Status::Trashed).then_some(now),
+                depth,
+                ..item.clone()
+            };
+            let to = lens[target as usize];
+            lens[target as usize] += 1;
+            actions.push(Action::Move {
+                from: (src, index - offset),
+                to: (target, to),
+            });
+            actions.push(Action::Replace(target, to, item));
+        }
+        for (inserted, (index, item)) in renewed.into_iter().enumerate() {
+            actions.push(Action::Insert(src, index + inserted, item));
+        }
+        self.apply(Action::Batch(actions));
+    }
+
+    // copies of the items from `anchor` to `cursor` with their subtasks, nested as they
+    // would be on their own
+    pub fn items_between(&self, anchor: usize, cursor: usize) -> Vec<Item> {
+        let indices = self.with_subtasks(self.visible_between(anchor, cursor));
+        let depths = detached_depths(self.list(self.tab), &indices);
+        indices
+            .into_iter()
+            .zip(depths)
+            .map(|(index, depth)| Item {
+                depth,
+                ..self.list(self.tab)[index].clone()
+            })
+            .collect()
+    }
+
+    // inserts a copy of the selected item and its subtasks under them as new items, the copy
+    // of the item being `edited` when given
+    pub fn duplicate(&mut self, edited: Option<Item>) {
+        let Some(item) = self.selected() else {
+            return;
+        };
+        let first = edited.unwrap_or_else(|| item.clone());
+        let current = self.current(self.tab);
+        let now = Local::now().naive_local();
+        let mut items = self.items_between(current, current);
+        items[0] = Item {
+            depth: items[0].depth,
+            ..first
+        };
+        for item in items.iter_mut() {
+            item.created = Some(now);
+            item.pomodoros = 0;
+        }
+        self.paste(items, true);
+    }
+
+    // inserts the items after the selected item and its subtasks or before it, on its level,
+    // as one undoable step; they are completed or reopened to fit the tab
+    pub fn paste(&mut self, items: Vec<Item>, after: bool) {
+        if items.is_empty() {
+            return;
+        }
+        let now = Local::now().naive_local();
+        let current = self.current(self.tab);
+        let (index, depth) = match self.list(self.tab).get(current) {
+            Some(item) if after => (self.subtree(self.tab, current).end, item.depth),
+            Some(item) => (current, item.depth),
+            None => (0, 0),
+        };
+        let actions = items
+            .into_iter()
+            .enumerate()
+            .map(|(offset, mut item)| {
+                item.depth += depth;
+                item.mark = None;
+                item.created.get_or_insert(now);
+                item.trashed = (self.tab == Status::Trashed).then_some(now);
+                item.state = item.state.filter(|state| state.fits(self.tab));
+                match self.tab {
+                    Status::Todo => item.completed = None,
+                    Status::Done | Status::Archived => {
+                        item.completed.get_or_insert(now);
+                    }
+                    Status::Trashed => {}
+                }
+                Action::Insert(self.tab, index + offset, item)
+            })
+            .collect();
+        self.apply(Action::Batch(actions));
+        self.select(index);
+    }
+
+    // moves the block start..=end one position up or down, `delta` being -1 or 1,
+    // the cursor follows the block
+    pub fn reorder_range(&mut self, start: usize, end: usize, delta: isize) {
+        let len = self.list(self.tab).len();
+        if start > end || end >= len {
+            return;
+        }
+        let action = if delta < 0 && start > 0 {
+            Action::Move {
+                from: (self.tab, start - 1),
+                to: (self.tab, end),
+            }
+        } else if delta > 0 && end + 1 < len {
+            Action::Move {
+                from: (self.tab, end + 1),
+                to: (self.tab, start),
+            }
+        } else {
+            return;
+        };
+        self.apply(action);
+        let current = self.current_mut(self.tab);
+        *current = current.saturating_add_signed(delta);
+    }
+
+    pub fn tag_range(&mut self, anchor: usize, cursor: usize, tag: &str) {
+        let indices = self.visible_between(anchor, cursor);
+        if !indices.is_empty() {
+            let tag = format!("#{}", tag.trim_start_matches('#'));
+            let actions = indices
+                .into_iter()
+                .map(|index| {
+                    let mut item = self.list(self.tab)[index].clone();
+                    item.text = format!("{} {}", item.text, tag);
+                    Action::Replace(self.tab, index, item)
+                })
+                .collect();
+            self.apply(Action::Batch(actions));
+        }
+    }
+
+    // stable sort of the active list, recorded as one undoable step; the cursor stays on its item.
+    // subtasks are sorted among their siblings and stay under their parent
+    pub fn sort_by_key<K: Ord>(&mut self, key: impl Fn(&Item) -> K) {
+        let items = self.list(self.tab);
+        let mut order = Vec::with_capacity(items.len());
+        sort_siblings(items, 0..items.len(), &key, &mut order);
+
+        let unsorted = self
+            .manual_order
+            .as_ref()
+            .is_none_or(|(list, status, _)| (*list, *status) != (self.active, self.tab));
+        if unsorted {
+            self.manual_order = Some((self.active, self.tab, items.clone()));
+        }
+        self.rearrange(order);
+    }
+
+    // puts the items of the active list back in the order they had before sorting,
+    // items added since then go last
+    pub fn restore_manual_order(&mut self) -> bool {
+        let Some((list, status, manual)) = self.manual_order.take() else {
+            return false;
+        };
+        if (list, status) != (self.active, self.tab) {
+            self.manual_order = Some((list, status, manual));
+            return false;
+        }
+        let items = self.list(self.tab);
+        let mut left: Vec<usize> = (0..items.len()).collect();
+        let mut order = Vec::with_capacity(items.len());
+        for item in manual.iter() {
+            if let Some(position) = left.iter().position(|&index| items[index] == *item) {
+                order.push(left.remove(position));
+            }
+        }
+        order.extend(left);
+        self.rearrange(order);
+        true
+    }
+
+    // moves the item at `order[i]` to position `i`
+    fn rearrange(&mut self, order: Vec<usize>) {
+        let items = self.list(self.tab);
+        let actions: Vec<Action> = order
+            .iter()
+            .enumerate()
+            .filter(|(index, from)| index != *from)
+            .map(|(index, &from)| Action::Replace(self.tab, index, items[from].clone()))
+            .collect();
+        if actions.is_empty() {
+            return;
+        }
+        let current = self.current(self.tab);
+        self.apply(Action::Batch(actions));
+        if let Some(index) = order.iter().position(|&from| from == current) {
+            self.select(index);
+        }
+    }
+
+    // swaps the selected item with its visible neighbour on the same level, `delta` being
+    // -1 or 1; subtasks move along with their parent
+    pub fn reorder(&mut self, delta: isize) {
+        let index = self.current(self.tab);
+        let items = self.list(self.tab);
+        let visible = self.visible(self.tab);
+        let Some(position) = visible.iter().position(|&other| other == index) else {
+            return;
+        };
+        let depth = items[index].depth;
+        let subtree = self.subtree(self.tab, index);
+        let neighbour = if delta < 0 {
+            visible[..position]
+                .iter()
+                .rev()
+                .find(|&&other| items[other].depth <= depth)
+        } else {
+            visible[position + 1..]
+                .iter()
+                .find(|&&other| other >= subtree.end && items[other].depth <= depth)
+        };
+        let Some(&neighbour) = neighbour.filter(|&&other| items[other].depth == depth) else {
+            return;
+        };
+        let neighbour = self.subtree(self.tab, neighbour);
+        let (first, second) = if delta < 0 {
+            (subtree.clone(), neighbour.start..subtree.start)
+        } else {
+            (subtree.end..neighbour.end, subtree.clone())
+        };
+        let mut order: Vec<usize> = (0..second.start).collect();
+        order.extend(first.clone());
+        order.extend(second.end..first.start);
+        order.extend(second.clone());
+        order.extend(first.end.max(second.end)..items.len());
+        self.rearrange(order);
+    }
+
+    // moves the selected item to the end of the same column in another list
+    pub fn move_to_list(&mut self, list: usize) {
+        if list == self.active || list >= self.lists.len() {
+            return;
+        }
+        if self.selected().is_some() {
+            let subtree = self.subtree(self.tab, self.current(self.tab));
+            let items = &self.list(self.tab)[subtree.clone()];
+            let dst_len = self.lists[list].items(self.tab).len();
+            let inserts = items.iter().enumerate().map(|(offset, item)| {
+                let item = Item {
+                    depth: item.depth - items[0].depth,
+                    ..item.clone()
+                };
+                Action::In(
+                    list,
+                    Box::new(Action::Insert(self.tab, dst_len + offset, item)),
+                )
+            });
+            let actions = subtree
+                .clone()
+                .rev()
+                .map(|index| Action::Remove(self.tab, index))
+                .chain(inserts)
+                .collect();
+            self.apply(Action::Batch(actions));
+        }
+    }
+
+    // takes the selected item and its subtasks out for good, e.g. once they're written elsewhere
+    pub fn remove(&mut self) {
+        if self.selected().is_some() {
+            let actions = self
+                .subtree(self.tab, self.current(self.tab))
+                .rev()
+                .map(|index| Action::Remove(self.tab, index))
+                .collect();
+            self.apply(Action::Batch(actions));
+        }
+    }
+
+    // puts mark `name` on the selected item, taking it off the item that had it
+    pub fn set_mark(&mut self, name: char) -> bool {
+        if self.selected().is_none() {
+            return false;
+        }
+        for list in self.lists.iter_mut() {
+            for status in Status::ALL {
+                for item in list.items_mut(status).iter_mut() {
+                    if item.mark == Some(name) {
+                        item.mark = None;
+                    }
+                }
+            }
+        }
+        let index = self.current(self.tab);
+        self.list_mut(self.tab)[index].mark = Some(name);
+        true
+    }
+
+    // goes to the item with mark `name` wherever it moved to, marking where the jump
+    // started with `'` so that `''` jumps back
+    pub fn jump_to_mark(&mut self, name: char) -> bool {
+        let found = self.lists.iter().enumerate().find_map(|(list, items)| {
+            Status::ALL.into_iter().find_map(|status| {
+                let index = items
+                    .items(status)
+                    .iter()
+                    .position(|item| item.mark == Some(name))?;
+                Some((list, status, index))
+            })
+        });
+        let Some((list, status, index)) = found else {
+            return false;
+        };
+        self.set_mark('\'');
+        self.active = list;
+        self.focus(status, index);
+        true
+    }
+
+    // counts a finished pomodoro on the item wherever it is in `list` now, false when it's gone
+    pub fn log_pomodoro(&mut self, list: usize, item: &Item) -> bool {
+        self.update_found(list, item, |item| item.pomodoros += 1)
+    }
+
+    // adds tracked minutes to the item like `log_pomodoro`
+    pub fn log_time(&mut self, list: usize, item: &Item, minutes: u32) -> bool {
+        self.update_found(list, item, |item| item.tracked += minutes)
+    }
+
+    // the TODO items of every list that became due between `since` and `now`, by their
+    // due day or time coming or by waking up from a snooze while due
+    pub fn newly_due(&self, since: NaiveDateTime, now: NaiveDateTime) -> Vec<(usize, Item)> {
+        let mut due = Vec::new();
+        for (list, todos) in self.lists.iter().map(|list| &list.todos).enumerate() {
+            for item in todos {
+                let urgency = item.urgency(now, self.due_time);
+                let came = urgency < item.urgency(since, self.due_time);
+                let woke = item
+                    .snoozed
+                    .is_some_and(|snoozed| since < snoozed && snoozed <= now);
+                if urgency != Urgency::Later && !item.is_snoozed(now) && (came || woke) {
+                    due.push((list, item.clone()));
+                }
+            }
+        }
+        due
+    }
+
+    // the TODO items of every list with a reminder between `since` and `now`
+    pub fn reminders(&self, since: NaiveDateTime, now: NaiveDateTime) -> Vec<(usize, Item)> {
+        let mut reminded = Vec::new();
+        for (list, todos) in self.lists.iter().map(|list| &list.todos).enumerate() {
+            for item in todos {
+                if item
+                    .reminders
+                    .iter()
+                    .any(|&reminder| since < reminder && reminder <= now)
+                {
+                    reminded.push((list, item.clone()));
+                }
+            }
+        }
+        reminded
+    }
+
+    // moves the TODO item wherever it is in `list` now to the top with the rest of its
+    // top-level task as one undoable step, the cursor staying on its item
+    pub fn bring_to_top(&mut self, list: usize, item: &Item) {
+        let Some(index) = self
+            .lists
+            .get(list)
+            .and_then(|list| list.todos.iter().position(|other| other.is_same(item)))
+        else {
+            return;
+        };
+        let todos = &self.lists[list].todos;
+        let root = (0..=index)
+            .rev()
+            .find(|&root| todos[root].depth == 0)
+            .unwrap_or(index);
+        let len = subtree_end(todos, root) - root;
+        if root > 0 {
+            let moves = (0..len)
+                .map(|offset| Action::Move {
+                    from: (Status::Todo, root + offset),
+                    to: (Status::Todo, offset),
+                })
+                .collect();
+            self.apply(Action::In(list, Box::new(Action::Batch(moves))));
+            let current = &mut self.lists[list].todo_current;
+            if *current < root {
+                *current += len;
+            } else if *current < root + len {
+                *current -= root;
+            }
+        }
+    }
+
+    // draws the TODO item wherever it is in `list` now flashing until `until`
+    pub fn flash(&mut self, list: usize, item: &Item, until: NaiveDateTime) {
+        let found = self
+            .lists
+            .get_mut(list)
+            .and_then(|list| list.todos.iter_mut().find(|other| other.is_same(item)));
+        if let Some(found) = found {
+            found.flash = Some(until);
+        }
+    }
+
+    // changes the item wherever it is in `list` now as one undoable step, false when it's gone
+    fn update_found(&mut self, list: usize, item: &Item, change: impl FnOnce(&mut Item)) -> bool {
+        let found = [Status::Todo, Status::Done, Status::Archived]
+            .into_iter()
+            .find_map(|status| {
+                let index = self
+                    .lists
+                    .get(list)?
+                    .items(status)
+                    .iter()
+                    .position(|other| other.is_same(item))?;
+                Some((status, index))
+            });
+        let Some((status, index)) = found else {
+            return false;
+        };
+        let mut item = self.lists[list].items(status)[index].clone();
+        change(&mut item);
+        self.apply(Action::In(
+            list,
+            Box::new(Action::Replace(status, index, item)),
+        ));
+        true
+    }
+
+    pub fn restore(&mut self) {
+        let done_current = self.current(Status::Done);
+        if let Some(item) = self.list(Status::Done).get(done_current) {
+            let item = Item {
+                created: Some(Local::now().naive_local()),
+                completed: None,
+                state: None,
+                depth: 0,
+                folded: false,
+                mark: None,
+                source: None,
+                ..item.clone()
+            };
+            let index = self.list(Status::Todo).len();
+            self.apply(Action::Insert(Status::Todo, index, item));
+        }
+    }
+}
+
+// the end of the subtree starting at `index`, the first item after it not nested deeper
+fn subtree_end(items: &[Item], index: usize) -> usize {
+    let depth = items[index].depth;
+    items[index + 1..]
+        .iter()
+        .position(|item| item.depth <= depth)
+        .map_or(items.len(), |offset| index + 1 + offset)
+}
+
+// the depths the items at the ascending `indices` get when taken out of `items`, an item
+// whose parent isn't taken along ends up on the top level
+fn detached_depths(items: &[Item], indices: &[usize]) -> Vec<usize> {
+    let mut depths: Vec<Option<usize>> = vec![None; items.len()];
+    for &index in indices {
+        let parent = (0..index)
+            .rev()
+            .find(|&other| items[other].depth < items[index].depth);
+        depths[index] = Some(
+            parent
+                .and_then(|parent| depths[parent])
+                .map_or(0, |depth| depth + 1),
+        );
+    }
+    indices
+        .iter()
+        .map(|&index| depths[index].unwrap())
+        .collect()
+}
+
+// which items are hidden under a folded parent
+fn folded_away(items: &[Item]) -> Vec<bool> {
+    let mut hidden = Vec::with_capacity(items.len());
+    let mut folded: Option<usize> = None;
+    for item in items {
+        if folded.is_some_and(|depth| item.depth > depth) {
+            hidden.push(true);
+            continue;
+        }
+        hidden.push(false);
+        folded = item.folded.then_some(item.depth);
+    }
+    hidden
+}
+
+// the indices of `range` in sorted order, siblings sorted by `key` and followed by their
+// own sorted subtasks
+fn sort_siblings<K: Ord>(
+    items: &[Item],
+    range: Range<usize>,
+    key: &impl Fn(&Item) -> K,
+    order: &mut Vec<usize>,
+) {
+    let mut siblings = Vec::new();
+    let mut index = range.start;
+    while index < range.end {
+        let end = subtree_end(items, index).min(range.end);
+        siblings.push(index..end);
+        index = end;
+    }
+    siblings.sort_by_key(|sibling| key(&items[sibling.start]));
+    for sibling in siblings {
+        order.push(sibling.start);
+        sort_siblings(items, sibling.start + 1..sibling.end, key, order);
+    }
+}
+
+// the `#tag` words of an item
+pub fn tags(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .filter(|word| word.len() > 1 && word.starts_with('#'))
+        .collect()
+}
+
+// the http(s) URLs in an item, without punctuation that ends a sentence around them
+pub fn urls(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .map(|word| word.trim_start_matches(['(', '<', '"', '\'']))
+        .filter(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .map(|word| word.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '>', '"', '\'']))
+        .collect()
+}
@@ -0,0 +1,118 @@
+use crate::{session, sqlite};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// `$XDG_STATE_HOME/clitodo/backups/<file>`, the file named like its session, to which
+// `.bak.1` is added for the newest backup, `.bak.2` for the one before and so on
+fn backup_base(path: &Path) -> Option<PathBuf> {
+    let file = match fs::canonicalize(path) {
+        Ok(file) => file,
+        // a deleted file can still be restored
+        Err(_) => {
+            let parent = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            fs::canonicalize(parent).ok()?.join(path.file_name()?)
+        }
+    };
+    let name = file.to_string_lossy().replace('/', "%");
+    Some(session::state_dir()?.join("backups").join(name))
+}
+
+fn numbered(base: &Path, number: usize) -> PathBuf {
+    let mut name = base.as_os_str().to_owned();
+    name.push(format!(".bak.{}", number));
+    PathBuf::from(name)
+}
+
+// copies the file at `path` to its newest backup before it is written over, moving the
+// older ones back and dropping those past `keep`; a file not written yet has nothing to
+// back up, and one unchanged since its newest backup needs no other
+pub fn rotate(path: &Path, keep: usize) -> io::Result<()> {
+    if keep == 0 || !path.is_file() {
+        return Ok(());
+    }
+    match backup_base(path) {
+        Some(base) => rotate_to(path, &base, keep),
+        None => Ok(()),
+    }
+}
+
+fn rotate_to(path: &Path, base: &Path, keep: usize) -> io::Result<()> {
+    fs::create_dir_all(base.parent().unwrap())?;
+    let newest = numbered(base, 1);
+    let copy = numbered(base, 0);
+    if sqlite::is_sqlite(path) {
+        // written into anew, and only complete together with its write-ahead log
+        if copy.exists() {
+            fs::remove_file(&copy)?;
+        }
+        sqlite::back_up(path, &copy).map_err(io::Error::other)?;
+    } else {
+        fs::copy(path, &copy)?;
+    }
+    if fs::read(&newest).ok() == Some(fs::read(&copy)?) {
+        return fs::remove_file(&copy);
+    }
+    // left over from a larger `backups`
+    let mut number = keep + 1;
+    while numbered(base, number).exists() {
+        fs::remove_file(numbered(base, number))?;
+        number += 1;
+    }
+    for number in (1..keep).rev() {
+        let backup = numbered(base, number);
+        if backup.exists() {
+            fs::rename(&backup, numbered(base, number + 1))?;
+        }
+    }
+    fs::rename(copy, newest)
+}
+
+// the backups of the file at `path` with when they were made, newest first
+pub fn list(path: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let Some(base) = backup_base(path) else {
+        return Vec::new();
+    };
+    (1..)
+        .map(|number| numbered(&base, number))
+        .map_while(|backup| {
+            let modified = fs::metadata(&backup).and_then(|metadata| metadata.modified());
+            Some((backup, modified.ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn rotate_keeps_the_newest_backups() {
+        let dir = TempDir::new("backup");
+        let path = dir.path().join("todo.txt");
+        let base = dir.path().join("backups").join("todo.txt");
+        for version in 1..=4 {
+            fs::write(&path, format!("TODO: version {}\n", version)).unwrap();
+            rotate_to(&path, &base, 3).unwrap();
+            // unchanged since the newest backup
+            rotate_to(&path, &base, 3).unwrap();
+        }
+        let backups: Vec<String> = (1..=4)
+            .map(|number| fs::read_to_string(numbered(&base, number)).unwrap_or_default())
+            .collect();
+        assert_eq!(
+            backups,
+            [
+                "TODO: version 4\n",
+                "TODO: version 3\n",
+                "TODO: version 2\n",
+                ""
+            ]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use crate::app::{App, Status};
+use crate::item::{Item, State};
+
+// the columns without a `columns` line in the config
+pub const DEFAULT_COLUMNS: &str = "TODO, IN-PROGRESS, WAITING, DONE";
+
+// a column of the board, which holds the items of a tab in one state
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    // TODO items in no state
+    Todo,
+    // TODO items in the state, or DONE ones for CANCELLED
+    State(State),
+    // DONE items in no state
+    Done,
+}
+
+impl Column {
+    // `TODO`, `DONE`, a built-in state or a state of its own like `REVIEW`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "TODO" => Some(Column::Todo),
+            "DONE" => Some(Column::Done),
+            name => State::parse(name).map(Column::State),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Column::Todo => "TODO",
+            Column::State(state) => state.name(),
+            Column::Done => "DONE",
+        }
+    }
+
+    // the tab its items are in
+    pub fn status(&self) -> Status {
+        match self {
+            Column::State(State::Cancelled) | Column::Done => Status::Done,
+            Column::Todo | Column::State(_) => Status::Todo,
+        }
+    }
+
+    // the state its items are in
+    pub fn state(&self) -> Option<&State> {
+        match self {
+            Column::State(state) => Some(state),
+            Column::Todo | Column::Done => None,
+        }
+    }
+
+    fn holds(&self, item: &Item) -> bool {
+        item.state.as_ref() == self.state()
+    }
+}
+
+pub fn parse_columns(value: &str) -> Result<Vec<Column>, String> {
+    let mut columns: Vec<Column> = Vec::new();
+    for name in value.split(',').map(|name| name.trim().to_uppercase()) {
+        let column = Column::parse(&name).ok_or_else(|| {
+            format!(
+                "`{}` can't be a column, name it with letters, digits and dashes",
+                name
+            )
+        })?;
+        if columns.contains(&column) {
+            return Err(format!("column `{}` is given twice", name));
+        }
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+// the items of the column in the active list passing the filter, in display order
+pub fn items(app: &App, column: &Column) -> Vec<usize> {
+    let status = column.status();
+    app.visible(status)
+        .into_iter()
+        .filter(|&index| column.holds(&app.list(status)[index]))
+        .collect()
+}
+
+// where the item is on the board, by column and row
+pub fn position(app: &App, columns: &[Column], id: &str) -> Option<(usize, usize)> {
+    columns.iter().enumerate().find_map(|(column, other)| {
+        let row = items(app, other)
+            .into_iter()
+            .position(|index| app.list(other.status())[index].id.as_deref() == Some(id))?;
+        Some((column, row))
+    })
+}
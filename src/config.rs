@@ -0,0 +1,243 @@
+use crate::board::{self, Column};
+use crate::dates::DateFormat;
+use crate::icons::Icons;
+use crate::item::{self, State};
+use crate::theme::{self, Colors, Theme};
+use crate::ui::MAX_COLUMNS;
+use chrono::NaiveTime;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// settings read from `$XDG_CONFIG_HOME/clitodo/config`, one `key = value` per line
+pub struct Config {
+    pub theme: Theme,
+    // off with `--no-color` or NO_COLOR, the theme is then left out
+    pub color: bool,
+    // inline `*bold*`, `_underline_` and `code` markup in item text
+    pub styling: bool,
+    // alternate the background of list rows
+    pub zebra: bool,
+    // the row of key hints above the status bar
+    pub footer: bool,
+    // reminders go to the desktop too, see `external::notify`
+    pub notify: bool,
+    pub icons: Icons,
+    pub dates: DateFormat,
+    // deleted items are purged from the trash after this many days
+    pub trash_days: i64,
+    // TODO items pending for longer than this many days are flagged, never with 0
+    pub stale_days: i64,
+    // pending items go up a priority level every this many days they're left untouched,
+    // never with 0
+    pub bump_days: i64,
+    // DONE items completed more than this many days ago are archived on startup and by `ga`,
+    // which takes a week otherwise; never on startup with 0
+    pub archive_days: i64,
+    // unsaved changes are saved once the lists are left alone for this many seconds, see
+    // `AUTOSAVE_LIMIT_SECONDS`; never with 0
+    pub autosave_seconds: i64,
+    // saving keeps this many previous versions of each file, see `backup::rotate`
+    pub backups: usize,
+    // when items with a due date but no time are due, at the end of the day when unset
+    pub due_time: Option<NaiveTime>,
+    // the name of whoever runs the program in `for:` tokens of a shared file
+    pub user: Option<String>,
+    // the columns of the board in order, set with `columns = TODO, REVIEW, DONE`
+    pub columns: Vec<Column>,
+    // of the custom columns in order, set with `color.review = magenta`
+    pub column_colors: Vec<Colors>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            color: true,
+            styling: true,
+            zebra: false,
+            footer: true,
+            notify: false,
+            icons: Icons::default(),
+            dates: DateFormat::default(),
+            trash_days: 30,
+            stale_days: 14,
+            bump_days: 0,
+            archive_days: 0,
+            autosave_seconds: 0,
+            backups: 5,
+            due_time: None,
+            user: None,
+            columns: board::parse_columns(board::DEFAULT_COLUMNS).unwrap(),
+            column_colors: Vec::new(),
+        }
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("clitodo").join("config"))
+}
+
+impl Config {
+    // a missing config file is fine, a malformed one is reported as `path:line: message`
+    pub fn load() -> Result<Self, String> {
+        let mut config = Config::default();
+        let Some(path) = config_path() else {
+            return Ok(config);
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Ok(config);
+        };
+
+        let mut colors = Vec::new();
+        let mut glyphs = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error =
+                |message: String| format!("{}:{}: ERROR: {}", path.display(), index + 1, message);
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(format!("expected `key = value`, got `{}`", line)));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "theme" => {
+                    config.theme = Theme::builtin(value)
+                        .ok_or_else(|| error(format!("unknown theme `{}`", value)))?;
+                }
+                "date_format" => config.dates = DateFormat::parse(value),
+                "icons" => {
+                    config.icons = Icons::builtin(value)
+                        .ok_or_else(|| error(format!("unknown icons `{}`", value)))?;
+                }
+                "icon.todo" | "icon.done" | "separator" => {
+                    if value.is_empty() {
+                        return Err(error(format!("expected a glyph for `{}`", key)));
+                    }
+                    glyphs.push((key, value.to_string()));
+                }
+                "trash_days" | "stale_days" | "bump_days" | "archive_days" => {
+                    let days = value
+                        .parse()
+                        .ok()
+                        .filter(|&days: &i64| days >= 0)
+                        .ok_or_else(|| {
+                            error(format!("expected a number of days, got `{}`", value))
+                        })?;
+                    match key {
+                        "trash_days" => config.trash_days = days,
+                        "stale_days" => config.stale_days = days,
+                        "bump_days" => config.bump_days = days,
+                        _ => config.archive_days = days,
+                    }
+                }
+                "autosave_seconds" => {
+                    config.autosave_seconds = value
+                        .parse()
+                        .ok()
+                        .filter(|&seconds: &i64| seconds >= 0)
+                        .ok_or_else(|| {
+                            error(format!("expected a number of seconds, got `{}`", value))
+                        })?;
+                }
+                "backups" => {
+                    config.backups = value.parse().map_err(|_| {
+                        error(format!("expected a number of backups, got `{}`", value))
+                    })?;
+                }
+                "columns" => {
+                    config.columns = board::parse_columns(value).map_err(error)?;
+                    let custom = config
+                        .columns
+                        .iter()
+                        .filter(|column| matches!(column.state(), Some(State::Custom(_))))
+                        .count();
+                    if custom > MAX_COLUMNS {
+                        return Err(error(format!(
+                            "at most {} columns besides the built-in ones",
+                            MAX_COLUMNS
+                        )));
+                    }
+                }
+                "due_time" => {
+                    let time = NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| {
+                        error(format!("expected a time like 17:00, got `{}`", value))
+                    })?;
+                    config.due_time = Some(time);
+                }
+                "user" => {
+                    if !item::is_name(value) {
+                        return Err(error(format!(
+                            "expected a name of letters, digits, dashes and dots, got `{}`",
+                            value
+                        )));
+                    }
+                    config.user = Some(value.to_string());
+                }
+                "styling" | "zebra" | "footer" | "notify" => {
+                    let enabled = value
+                        .parse()
+                        .map_err(|_| error(format!("expected true or false, got `{}`", value)))?;
+                    match key {
+                        "styling" => config.styling = enabled,
+                        "zebra" => config.zebra = enabled,
+                        "footer" => config.footer = enabled,
+                        _ => config.notify = enabled,
+                    }
+                }
+                _ => {
+                    let role = key.strip_prefix("color.").unwrap_or(key);
+                    // the columns may be given further down
+                    if config.theme.role_mut(role).is_none() && !key.starts_with("color.") {
+                        return Err(error(format!("unknown setting `{}`", key)));
+                    }
+                    let value = theme::parse_colors(value)
+                        .ok_or_else(|| error(format!("unknown color `{}`", value)))?;
+                    colors.push((index, role.to_string(), value));
+                }
+            }
+        }
+
+        // custom columns are drawn like regular items unless given a color
+        let custom: Vec<String> = config
+            .columns
+            .iter()
+            .filter_map(|column| match column.state() {
+                Some(State::Custom(name)) => Some(name.to_lowercase()),
+                _ => None,
+            })
+            .collect();
+        config.column_colors = vec![config.theme.regular; custom.len()];
+        // individual colors override the theme no matter where it is set
+        for (index, role, value) in colors {
+            if let Some(slot) = config.theme.role_mut(&role) {
+                *slot = value;
+            } else if let Some(column) = custom.iter().position(|name| *name == role) {
+                config.column_colors[column] = value;
+            } else {
+                return Err(format!(
+                    "{}:{}: ERROR: unknown setting `color.{}`",
+                    path.display(),
+                    index + 1,
+                    role
+                ));
+            }
+        }
+        // and so do individual glyphs the icons
+        for (key, value) in glyphs {
+            let slot = match key {
+                "icon.todo" => &mut config.icons.todo,
+                "icon.done" => &mut config.icons.done,
+                _ => &mut config.icons.separator,
+            };
+            *slot = value.into();
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use std::ffi::CString;
+
+// strftime patterns for dates shown on screen; the file format always stays ISO
+#[derive(Clone)]
+pub struct DateFormat {
+    // a full date, e.g. in the header
+    pub long: String,
+    // day and month, e.g. in the agenda
+    pub short: String,
+}
+
+pub const PRESETS: &[(&str, &str, &str)] = &[
+    ("eu", "%d/%m/%Y", "%d/%m"),
+    ("iso", "%Y-%m-%d", "%m-%d"),
+    ("us", "%m/%d/%Y", "%m/%d"),
+];
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        Self::parse(PRESETS[0].0)
+    }
+}
+
+impl DateFormat {
+    // a preset name or a strftime pattern, which is then used for short dates too
+    pub fn parse(value: &str) -> Self {
+        match PRESETS.iter().find(|(name, ..)| *name == value) {
+            Some((_, long, short)) => Self {
+                long: long.to_string(),
+                short: short.to_string(),
+            },
+            None => Self {
+                long: value.to_string(),
+                short: value.to_string(),
+            },
+        }
+    }
+
+    pub fn date(&self, date: NaiveDate) -> String {
+        strftime(&self.long, date.and_hms_opt(0, 0, 0).unwrap())
+    }
+
+    pub fn short_date(&self, date: NaiveDate) -> String {
+        strftime(&self.short, date.and_hms_opt(0, 0, 0).unwrap())
+    }
+
+    pub fn time(&self, time: NaiveDateTime) -> String {
+        strftime(&format!("{} %H:%M", self.long), time)
+    }
+
+    pub fn short_time(&self, time: NaiveDateTime) -> String {
+        strftime(&format!("{} %H:%M", self.short), time)
+    }
+
+    // the weekday and the short date, e.g. `Wed 12/06`
+    pub fn day(&self, date: NaiveDate) -> String {
+        strftime(
+            &format!("%a {}", self.short),
+            date.and_hms_opt(0, 0, 0).unwrap(),
+        )
+    }
+
+    // e.g. `October 2026`
+    pub fn month(&self, date: NaiveDate) -> String {
+        strftime("%B %Y", date.and_hms_opt(0, 0, 0).unwrap())
+    }
+}
+
+// libc's strftime, so weekday and month names follow the locale set with setlocale
+fn strftime(pattern: &str, time: NaiveDateTime) -> String {
+    let Ok(pattern) = CString::new(pattern) else {
+        return String::new();
+    };
+    // the fields strftime doesn't read can stay zero
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = time.year() - 1900;
+    tm.tm_mon = time.month0() as i32;
+    tm.tm_mday = time.day() as i32;
+    tm.tm_hour = time.hour() as i32;
+    tm.tm_min = time.minute() as i32;
+    tm.tm_sec = time.second() as i32;
+    tm.tm_wday = time.weekday().num_days_from_sunday() as i32;
+    tm.tm_yday = time.ordinal0() as i32;
+    tm.tm_isdst = -1;
+    let mut buffer = [0u8; 256];
+    let len = unsafe {
+        libc::strftime(
+            buffer.as_mut_ptr() as *mut libc::c_char,
+            buffer.len(),
+            pattern.as_ptr(),
+            &tm,
+        )
+    };
+    String::from_utf8_lossy(&buffer[..len]).into_owned()
+}
@@ -0,0 +1,67 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+// line diff through the longest common subsequence, removals before additions
+pub fn diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Change<'a>> {
+    // common[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+// the changes with `context` unchanged lines around each run of changes, `None` marking a gap
+pub fn hunks<'a>(changes: &[Change<'a>], context: usize) -> Vec<Option<Change<'a>>> {
+    let changed: Vec<usize> = changes
+        .iter()
+        .enumerate()
+        .filter(|(_, change)| !matches!(change, Change::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    let near = |index: usize| {
+        changed
+            .iter()
+            .any(|&other| index + context >= other && index <= other + context)
+    };
+    let mut lines = Vec::new();
+    let mut skipped = false;
+    for (index, change) in changes.iter().enumerate() {
+        if near(index) {
+            if skipped && !lines.is_empty() {
+                lines.push(None);
+            }
+            skipped = false;
+            lines.push(Some(*change));
+        } else {
+            skipped = true;
+        }
+    }
+    lines
+}
@@ -0,0 +1,224 @@
+use crate::app::App;
+use crate::filter;
+use crate::group::Grouping;
+use crate::item;
+use crate::keymap::{Command, BINDINGS};
+use crate::templates;
+
+// the keys `:sort` takes, in the order of the sort menu
+pub const SORT_KEYS: &[&str] = &[
+    "urgency",
+    "priority",
+    "due",
+    "created",
+    "alphabetical",
+    "manual",
+];
+
+const GROUPINGS: &[(&str, Grouping)] = &[
+    ("none", Grouping::None),
+    ("project", Grouping::Project),
+    ("tag", Grouping::Tag),
+    ("due", Grouping::Due),
+];
+
+// commands of the command line that take an argument, without one they run the keymap
+// command of the same name if there is one
+const NAMES: &[&str] = &[
+    "archive", "context", "filter", "fit", "group", "list", "move", "new", "sort", "template",
+];
+
+// short names like in vim
+const ALIASES: &[(&str, Command)] = &[
+    ("w", Command::Save),
+    ("write", Command::Save),
+    ("q", Command::Quit),
+    ("wq", Command::SaveQuit),
+    ("x", Command::SaveQuit),
+];
+
+// a line typed after `:`
+pub enum Ex {
+    // a keymap command, `:12` being ENTER with a count, which goes to the 12th item
+    Run(Command, Option<usize>),
+    Sort(usize),
+    Filter(String),
+    // shows only the items of the `@context`, or of every context for `all`
+    Context(Option<String>),
+    // only the pending items that fit in the given minutes
+    Fit(u32),
+    Group(Grouping),
+    // archives all DONE items, or the ones done more than the given days ago
+    Archive(Option<i64>),
+    Move(usize),
+    List(usize),
+    New(String),
+    // adds the items of the template of that name
+    Template(String),
+}
+
+impl Ex {
+    // commands that change the lists, refused in read-only mode; keymap commands are
+    // checked like when their key is pressed
+    pub fn mutates(&self) -> bool {
+        matches!(
+            self,
+            Ex::Sort(_) | Ex::Archive(_) | Ex::Move(_) | Ex::New(_) | Ex::Template(_)
+        )
+    }
+}
+
+// the keymap commands by name, e.g. `move-to-next-list`
+fn commands() -> impl Iterator<Item = (String, Command)> {
+    BINDINGS
+        .iter()
+        .map(|binding| (binding.command.name(), binding.command))
+}
+
+// every name the command line knows, sorted
+fn names() -> Vec<String> {
+    let mut names: Vec<String> = NAMES
+        .iter()
+        .chain(ALIASES.iter().map(|(name, _)| name))
+        .map(|name| name.to_string())
+        .chain(commands().map(|(name, _)| name))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+// the value named by `word` or by a prefix only it has
+fn find<T: Copy>(options: &[(&str, T)], word: &str) -> Option<T> {
+    let exact = options.iter().find(|(name, _)| *name == word);
+    let mut prefixed = options.iter().filter(|(name, _)| name.starts_with(word));
+    exact
+        .or_else(|| prefixed.next().filter(|_| prefixed.next().is_none()))
+        .map(|(_, value)| *value)
+}
+
+fn list_index(app: &App, name: &str) -> Result<usize, String> {
+    let lists: Vec<(&str, usize)> = app
+        .lists
+        .iter()
+        .enumerate()
+        .map(|(index, list)| (list.name.as_str(), index))
+        .collect();
+    find(&lists, name).ok_or_else(|| format!("No list named {}", name))
+}
+
+pub fn parse(line: &str, app: &App) -> Result<Ex, String> {
+    let line = line.trim();
+    if let Ok(number) = line.parse::<usize>() {
+        return Ok(Ex::Run(Command::Transfer, Some(number.max(1))));
+    }
+    let (name, argument) = match line.split_once(' ') {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line, ""),
+    };
+
+    if let Some((_, command)) = ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return Ok(Ex::Run(*command, None));
+    }
+    let commands: Vec<(String, Command)> = commands().collect();
+    let commands: Vec<(&str, Command)> = commands
+        .iter()
+        .map(|(name, command)| (name.as_str(), *command))
+        .collect();
+    let named: Vec<(&str, &str)> = NAMES.iter().map(|name| (*name, *name)).collect();
+    if argument.is_empty() {
+        if let Some(command) = find(&commands, name) {
+            return Ok(Ex::Run(command, None));
+        }
+        if let Some(name) = find(&named, name) {
+            return Err(format!("{} needs an argument", name));
+        }
+    }
+
+    match find(&named, name) {
+        Some("sort") => {
+            let keys: Vec<(&str, usize)> = SORT_KEYS
+                .iter()
+                .enumerate()
+                .map(|(index, key)| (*key, index))
+                .collect();
+            find(&keys, argument)
+                .map(Ex::Sort)
+                .ok_or_else(|| format!("Can't sort by {}", argument))
+        }
+        Some("filter") => Ok(Ex::Filter(argument.to_string())),
+        Some("context") => Ok(Ex::Context(
+            (argument != "all").then(|| filter::parse_context(argument)),
+        )),
+        Some("fit") => item::parse_minutes(argument)
+            .map(Ex::Fit)
+            .ok_or_else(|| format!("Not a duration: {}", argument)),
+        Some("group") => find(GROUPINGS, argument)
+            .map(Ex::Group)
+            .ok_or_else(|| format!("Can't group by {}", argument)),
+        Some("archive") => match argument {
+            "done" => Ok(Ex::Archive(None)),
+            days => days
+                .parse()
+                .map(|days| Ex::Archive(Some(days)))
+                .map_err(|_| "Archive takes `done` or a number of days".to_string()),
+        },
+        Some("move") => list_index(app, argument).map(Ex::Move),
+        Some("list") => list_index(app, argument).map(Ex::List),
+        Some("new") => Ok(Ex::New(argument.to_string())),
+        Some("template") => Ok(Ex::Template(argument.to_string())),
+        _ => Err(format!("Not a command: {}", name)),
+    }
+}
+
+// completes the command name or the argument at the end of `line` as far as the
+// candidates agree and returns the candidates
+pub fn complete(line: &mut String, app: &App) -> Vec<String> {
+    let (prefix, word, candidates) = match line.split_once(' ') {
+        None => (String::new(), line.clone(), names()),
+        Some((name, argument)) => {
+            let values: Vec<String> = match name {
+                "sort" => SORT_KEYS.iter().map(|key| key.to_string()).collect(),
+                "group" => GROUPINGS.iter().map(|(name, _)| name.to_string()).collect(),
+                "archive" => vec!["done".to_string()],
+                "move" | "list" => app.lists.iter().map(|list| list.name.clone()).collect(),
+                "filter" => app.filter_words(),
+                "context" => ["all".to_string()]
+                    .into_iter()
+                    .chain(app.contexts())
+                    .collect(),
+                "template" => templates::load()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|template| template.name)
+                    .collect(),
+                _ => Vec::new(),
+            };
+            (
+                format!("{} ", name),
+                argument.trim_start().to_string(),
+                values,
+            )
+        }
+    };
+    let candidates: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(&word))
+        .collect();
+    let Some(first) = candidates.first() else {
+        return candidates;
+    };
+    let common = candidates.iter().fold(first.as_str(), |common, candidate| {
+        let len = common
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(common.len().min(candidate.len()), |((index, _), _)| index);
+        &common[..len]
+    });
+    *line = format!("{}{}", prefix, common);
+    if candidates.len() == 1 && prefix.is_empty() && NAMES.contains(&common) {
+        line.push(' ');
+    }
+    candidates
+}
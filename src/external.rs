@@ -0,0 +1,140 @@
+use ncurses::*;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process::{self, Command, Stdio};
+use std::thread;
+
+// opens a file or URL with the desktop's default application
+#[cfg(target_os = "macos")]
+const OPENER: &str = "open";
+#[cfg(not(target_os = "macos"))]
+const OPENER: &str = "xdg-open";
+
+// shows a desktop notification
+#[cfg(target_os = "macos")]
+fn notifier(text: &str) -> Command {
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {:?} with title \"clitodo\"",
+        text
+    ));
+    command
+}
+#[cfg(not(target_os = "macos"))]
+fn notifier(text: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.arg("clitodo").arg(text);
+    command
+}
+
+// clipboard programs tried in order until one is installed
+const COPY: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+const PASTE: &[&[&str]] = &[
+    &["pbpaste"],
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+// hands the terminal back while the program runs, so one running in the terminal can use it
+fn run(command: &mut Command) -> io::Result<()> {
+    def_prog_mode();
+    endwin();
+    let status = command.status();
+    reset_prog_mode();
+    refresh();
+    let status = status?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "{:?} exited with {}",
+            command.get_program(),
+            status
+        )));
+    }
+    Ok(())
+}
+
+// sends the notification in the background, without touching the terminal
+pub fn notify(text: &str) -> io::Result<()> {
+    let mut child = notifier(text)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+pub fn open_url(url: &str) -> io::Result<()> {
+    run(Command::new(OPENER).arg(url))
+}
+
+// `text` after editing it in $VISUAL or $EDITOR, vi when neither is set
+pub fn edit(text: &str) -> io::Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or("vi".to_string());
+    // the editor may come with arguments, as in `code --wait`
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap());
+    command.args(words);
+
+    let path = env::temp_dir().join(format!("clitodo-{}.txt", process::id()));
+    fs::write(&path, text)?;
+    let result = run(command.arg(&path)).and_then(|_| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+// the first of `candidates` that is installed, spawned with piped stdin and stdout
+fn spawn_any(candidates: &[&[&str]]) -> io::Result<std::process::Child> {
+    for candidate in candidates {
+        let spawned = Command::new(candidate[0])
+            .args(&candidate[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            result => return result,
+        }
+    }
+    Err(io::Error::other(
+        "no clipboard program found (pbcopy, wl-copy, xclip or xsel)",
+    ))
+}
+
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut child = spawn_any(COPY)?;
+    child.stdin.take().unwrap().write_all(text.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "clipboard program exited with {}",
+            status
+        )));
+    }
+    Ok(())
+}
+
+pub fn paste() -> io::Result<String> {
+    let child = spawn_any(PASTE)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "clipboard program exited with {}",
+            output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
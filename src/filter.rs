@@ -0,0 +1,110 @@
+use crate::item::{hours, Item};
+use chrono::{NaiveDate, NaiveDateTime};
+
+// the word that puts an item on today's list whatever its dates
+const TODAY_TAG: &str = "@today";
+
+// restricts which items of a list are shown and can be navigated to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    // a `#tag`, `+project` or `@context` word the item has to contain
+    pub word: Option<String>,
+    // the `@context` picked with the context switcher, kept when the filter is cleared
+    pub context: Option<String>,
+    // only the items assigned to that user, see `App::user`
+    pub assignee: Option<String>,
+    // the day the item has to be due on
+    pub due: Option<NaiveDate>,
+    // only items due or created today, or tagged with TODAY_TAG
+    pub today: bool,
+    // show snoozed items too
+    pub snoozed: bool,
+    // only pending items estimated to take at most that many minutes, most important first
+    pub fit: Option<u32>,
+}
+
+impl Filter {
+    pub fn is_active(&self) -> bool {
+        self.word.is_some()
+            || self.context.is_some()
+            || self.assignee.is_some()
+            || self.due.is_some()
+            || self.today
+            || self.fit.is_some()
+    }
+
+    pub fn matches(&self, item: &Item, now: NaiveDateTime) -> bool {
+        self.word.as_ref().is_none_or(|word| {
+            item.text
+                .split_whitespace()
+                .any(|other| other.eq_ignore_ascii_case(word))
+        }) && self.context.as_ref().is_none_or(|context| {
+            contexts(&item.text)
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(context))
+        }) && self.assignee.as_ref().is_none_or(|assignee| {
+            item.assignee
+                .as_ref()
+                .is_some_and(|other| other.eq_ignore_ascii_case(assignee))
+        }) && self.due.is_none_or(|due| item.due == Some(due))
+            && (!self.today || is_for_today(item, now.date()))
+            && (self.snoozed || !item.is_snoozed(now))
+            && self.fit.is_none_or(|fit| {
+                item.completed.is_none() && item.estimate.is_some_and(|estimate| estimate <= fit)
+            })
+    }
+
+    // shown in the header while the filter is active
+    pub fn describe(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .context
+            .iter()
+            .chain(&self.word)
+            .cloned()
+            .chain(
+                self.assignee
+                    .as_ref()
+                    .map(|assignee| format!("for {}", assignee)),
+            )
+            .chain(self.due.map(|due| format!("due {}", due)))
+            .chain(self.today.then(|| "today".to_string()))
+            .chain(self.snoozed.then(|| "with snoozed".to_string()))
+            .chain(self.fit.map(|fit| format!("fits in {}", hours(fit))))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+fn is_for_today(item: &Item, today: NaiveDate) -> bool {
+    item.due == Some(today)
+        || item.created.is_some_and(|created| created.date() == today)
+        || item
+            .text
+            .split_whitespace()
+            .any(|word| word.eq_ignore_ascii_case(TODAY_TAG))
+}
+
+// the `@context` words of an item
+pub fn contexts(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .filter(|word| word.len() > 1 && word.starts_with('@'))
+        .collect()
+}
+
+// a bare word given as a context gets its `@`
+pub fn parse_context(input: &str) -> String {
+    if input.starts_with('@') {
+        input.to_string()
+    } else {
+        format!("@{}", input)
+    }
+}
+
+// a bare word typed into the filter prompt is taken as a tag
+pub fn parse_word(input: &str) -> String {
+    if input.starts_with(['#', '+', '@']) {
+        input.to_string()
+    } else {
+        format!("#{}", input)
+    }
+}
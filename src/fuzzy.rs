@@ -0,0 +1,39 @@
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 24;
+const WORD_START_BONUS: i64 = 12;
+const GAP_PENALTY: i64 = 1;
+
+// fzf-style subsequence scoring: every query char has to appear in order,
+// consecutive runs and matches at word starts score higher
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut text_chars = text.chars().enumerate();
+    let mut last_match: Option<usize> = None;
+    let mut prev_char: Option<char> = None;
+
+    for query_char in query.chars().filter(|ch| !ch.is_whitespace()) {
+        let query_char = query_char.to_ascii_lowercase();
+        loop {
+            let (index, ch) = text_chars.next()?;
+            let word_start = prev_char.is_none_or(|prev| !prev.is_alphanumeric());
+            prev_char = Some(ch);
+            if ch.to_ascii_lowercase() != query_char {
+                continue;
+            }
+
+            score += MATCH_SCORE;
+            if word_start {
+                score += WORD_START_BONUS;
+            }
+            match last_match {
+                Some(last) if last + 1 == index => score += CONSECUTIVE_BONUS,
+                Some(last) => score -= GAP_PENALTY * (index - last - 1) as i64,
+                None => score -= GAP_PENALTY * index as i64,
+            }
+            last_match = Some(index);
+            break;
+        }
+    }
+
+    Some(score)
+}
@@ -0,0 +1,52 @@
+use crate::item::{Item, Urgency};
+
+// how a list is split into sections
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Grouping {
+    #[default]
+    None,
+    Project,
+    Tag,
+    Due,
+}
+
+impl Grouping {
+    pub fn next(self) -> Self {
+        match self {
+            Grouping::None => Grouping::Project,
+            Grouping::Project => Grouping::Tag,
+            Grouping::Tag => Grouping::Due,
+            Grouping::Due => Grouping::None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Grouping::None => "nothing",
+            Grouping::Project => "project",
+            Grouping::Tag => "tag",
+            Grouping::Due => "due date",
+        }
+    }
+
+    // the section an item belongs to, sections are shown in the order of their keys
+    pub fn key(self, item: &Item, urgency: Urgency) -> (u8, String) {
+        let first_word = |prefix: char, missing: &str| {
+            item.text
+                .split_whitespace()
+                .find(|word| word.len() > 1 && word.starts_with(prefix))
+                .map_or((1, missing.to_string()), |word| (0, word.to_lowercase()))
+        };
+        match self {
+            Grouping::None => (0, String::new()),
+            Grouping::Project => first_word('+', "no project"),
+            Grouping::Tag => first_word('#', "no tag"),
+            Grouping::Due => match (urgency, item.due) {
+                (Urgency::Overdue, _) => (0, "Overdue".to_string()),
+                (Urgency::Today, _) => (1, "Today".to_string()),
+                (Urgency::Later, Some(_)) => (2, "Upcoming".to_string()),
+                (Urgency::Later, None) => (3, "No due date".to_string()),
+            },
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// the days of `habit:2026-10-01..2026-10-05,2026-10-07`, runs of days in a row given by
+// their first and last day; an empty log is a habit never done yet
+pub fn parse_log(value: &str) -> Option<Vec<NaiveDate>> {
+    let mut days = Vec::new();
+    for run in value.split(',').filter(|run| !run.is_empty()) {
+        let (first, last) = run.split_once("..").unwrap_or((run, run));
+        let first = NaiveDate::parse_from_str(first, DATE_FORMAT).ok()?;
+        let last = NaiveDate::parse_from_str(last, DATE_FORMAT).ok()?;
+        if last < first {
+            return None;
+        }
+        days.extend(first.iter_days().take_while(|day| *day <= last));
+    }
+    days.sort();
+    days.dedup();
+    Some(days)
+}
+
+// the inverse of `parse_log`, `days` being sorted
+pub fn format_log(days: &[NaiveDate]) -> String {
+    let mut runs: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for &day in days {
+        match runs.last_mut() {
+            Some((_, last)) if last.succ_opt() == Some(day) => *last = day,
+            _ => runs.push((day, day)),
+        }
+    }
+    runs.iter()
+        .map(|(first, last)| {
+            if first == last {
+                first.format(DATE_FORMAT).to_string()
+            } else {
+                format!(
+                    "{}..{}",
+                    first.format(DATE_FORMAT),
+                    last.format(DATE_FORMAT)
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// `days` with `today` logged, or taken out again when it was
+pub fn toggle(days: &[NaiveDate], today: NaiveDate) -> Vec<NaiveDate> {
+    let mut days = days.to_vec();
+    match days.binary_search(&today) {
+        Ok(index) => {
+            days.remove(index);
+        }
+        Err(index) => days.insert(index, today),
+    }
+    days
+}
+
+// days in a row the habit was done up to today, or up to yesterday while today is still open
+pub fn streak(days: &[NaiveDate], today: NaiveDate) -> usize {
+    let done = |day: &NaiveDate| days.binary_search(day).is_ok();
+    let start = if done(&today) {
+        Some(today)
+    } else {
+        today.pred_opt()
+    };
+    start
+        .into_iter()
+        .flat_map(|day| day.iter_days().rev())
+        .take_while(done)
+        .count()
+}
+
+// the month of `today` a week per row from Monday, `■` on the days done and `·` on the
+// ones missed
+pub fn heat_map(days: &[NaiveDate], today: NaiveDate) -> Vec<String> {
+    let first = today.with_day(1).unwrap();
+    let mut lines = vec!["Mo Tu We Th Fr Sa Su".to_string()];
+    let mut line = "   ".repeat(first.weekday().num_days_from_monday() as usize);
+    for day in first
+        .iter_days()
+        .take_while(|day| day.month() == first.month())
+    {
+        let mark = match days.binary_search(&day) {
+            Ok(_) => "■",
+            Err(_) if day <= today => "·",
+            Err(_) => " ",
+        };
+        line.push_str(&format!("{:<3}", mark));
+        if day.weekday() == Weekday::Sun {
+            lines.push(line.trim_end().to_string());
+            line.clear();
+        }
+    }
+    if !line.trim().is_empty() {
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
@@ -0,0 +1,100 @@
+use crate::ui::text_width;
+use std::borrow::Cow;
+
+// columns of the line drawn under the headers
+const SEPARATOR_WIDTH: usize = 24;
+
+// markers drawn in front of and inside items, plain ASCII unless configured otherwise
+#[derive(Clone)]
+pub struct Icons {
+    // the checkboxes, which `icon.todo` and `icon.done` in the config can replace
+    pub todo: Cow<'static, str>,
+    pub done: Cow<'static, str>,
+    // replaces the `#` of tags
+    pub tag: &'static str,
+    // replaces the parentheses of `(A)`, which stay when there is none
+    pub priority: Option<&'static str>,
+    // in front of pinned items
+    pub pin: &'static str,
+    // repeated into the line under the headers, set with `separator` in the config
+    pub separator: Cow<'static, str>,
+}
+
+pub const ICONS: &[(&str, Icons)] = &[
+    (
+        "ascii",
+        Icons {
+            todo: Cow::Borrowed("[ ]"),
+            done: Cow::Borrowed("[x]"),
+            tag: "#",
+            priority: None,
+            pin: "*",
+            separator: Cow::Borrowed("-"),
+        },
+    ),
+    (
+        "nerd",
+        Icons {
+            todo: Cow::Borrowed("\u{f096}"),
+            done: Cow::Borrowed("\u{f046}"),
+            tag: "\u{f02b} ",
+            priority: Some("\u{f12a}"),
+            pin: "\u{f08d}",
+            separator: Cow::Borrowed("-"),
+        },
+    ),
+    (
+        "emoji",
+        Icons {
+            todo: Cow::Borrowed("⬜"),
+            done: Cow::Borrowed("✅"),
+            tag: "🏷 ",
+            priority: Some("❗"),
+            pin: "📌",
+            separator: Cow::Borrowed("-"),
+        },
+    ),
+];
+
+impl Default for Icons {
+    fn default() -> Self {
+        ICONS[0].1.clone()
+    }
+}
+
+impl Icons {
+    pub fn builtin(name: &str) -> Option<Self> {
+        ICONS
+            .iter()
+            .find(|(icons, _)| *icons == name)
+            .map(|(_, icons)| icons.clone())
+    }
+
+    // the line drawn under the headers
+    pub fn rule(&self) -> String {
+        let width = text_width(&self.separator).max(1);
+        self.separator.repeat(SEPARATOR_WIDTH.div_ceil(width))
+    }
+
+    // the item line with its tag and priority markers swapped for icons
+    pub fn decorate(&self, line: &str) -> String {
+        if self.priority.is_none() && self.tag == "#" {
+            return line.to_string();
+        }
+        line.split(' ')
+            .enumerate()
+            .map(|(index, word)| {
+                let priority = word
+                    .strip_prefix('(')
+                    .and_then(|word| word.strip_suffix(')'))
+                    .filter(|priority| index == 0 && priority.len() == 1);
+                match (priority.zip(self.priority), word.strip_prefix('#')) {
+                    (Some((priority, icon)), _) => format!("{}{}", icon, priority),
+                    (_, Some(tag)) if !tag.is_empty() => format!("{}{}", self.tag, tag),
+                    _ => word.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,577 @@
+use crate::app::Status;
+use crate::habit;
+use crate::markdown::Source;
+use crate::recur::Recurrence;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M";
+// times in the file carry the UTC offset of where they were written, see `parse_time`
+const ZONED_FORMAT: &str = "%Y-%m-%dT%H:%M%:z";
+// spaces of indentation per nesting level in the file
+const INDENT: usize = 2;
+// note lines starting with these are the steps of a checklist
+const OPEN_STEP: &str = "[ ] ";
+const DONE_STEP: &str = "[x] ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Urgency {
+    Overdue,
+    Today,
+    Later,
+}
+
+// where a pending item stands, or why a closed one was closed, besides its tab
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum State {
+    InProgress,
+    // held up by someone or something else, left out of the tasks to do next
+    Waiting,
+    Cancelled,
+    // a pending state named by a column of the board in the config, like `REVIEW`
+    Custom(String),
+}
+
+// names that can't be states as they start the lines of the tabs
+const TAB_NAMES: [&str; 4] = ["TODO", "DONE", "ARCHIVED", "TRASHED"];
+
+impl State {
+    pub const BUILT_IN: [State; 3] = [State::InProgress, State::Waiting, State::Cancelled];
+
+    // a built-in state by name, anything else in capitals, digits and dashes being a
+    // custom one
+    pub fn parse(name: &str) -> Option<Self> {
+        if let Some(state) = State::BUILT_IN
+            .into_iter()
+            .find(|state| state.name() == name)
+        {
+            return Some(state);
+        }
+        let valid = name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        (valid && !TAB_NAMES.contains(&name)).then(|| State::Custom(name.to_string()))
+    }
+
+    // also what its items' lines start with in the file instead of `TODO:` or `DONE:`
+    pub fn name(&self) -> &str {
+        match self {
+            State::InProgress => "IN-PROGRESS",
+            State::Waiting => "WAITING",
+            State::Cancelled => "CANCELLED",
+            State::Custom(name) => name,
+        }
+    }
+
+    // whether an item can be in this state while it is in the tab; archived and trashed
+    // items keep theirs to have it back when they come back
+    pub fn fits(&self, status: Status) -> bool {
+        match status {
+            Status::Todo => *self != State::Cancelled,
+            Status::Done => *self == State::Cancelled,
+            Status::Archived | Status::Trashed => true,
+        }
+    }
+}
+
+// a todo with its metadata, stored on one line as the text followed by `key:value` tokens
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Item {
+    // short name that stays with the item through edits and moves, for other items and
+    // tools to refer to it by, see `App::assign_ids`
+    pub id: Option<String>,
+    pub text: String,
+    // kept in the file as the prefix of the line, see `State::name`
+    pub state: Option<State>,
+    // `A` is the most important, written as a leading `(A)` like todo.txt
+    pub priority: Option<char>,
+    pub due: Option<NaiveDate>,
+    // the time of day it is due at, see `deadline`
+    pub due_time: Option<NaiveTime>,
+    // completing the item brings it back due on the next day of the rule
+    pub recur: Option<Recurrence>,
+    // times to be reminded of the item at while the program runs, apart from when it is
+    // due, each written as `remind:2025-07-01T09:00`
+    pub reminders: Vec<NaiveDateTime>,
+    // minutes the item is expected to take, written as `est:45m` or `est:1h30m`
+    pub estimate: Option<u32>,
+    // who the item is for on a shared list, written as `for:alice`
+    pub assignee: Option<String>,
+    pub created: Option<NaiveDateTime>,
+    pub completed: Option<NaiveDateTime>,
+    // last edited or bumped up a priority level, see `App::bump_priorities`
+    pub touched: Option<NaiveDateTime>,
+    // finished pomodoros spent on the item
+    pub pomodoros: u32,
+    // minutes of time tracked on the item
+    pub tracked: u32,
+    // hidden from the lists until then
+    pub snoozed: Option<NaiveDateTime>,
+    // when the item was deleted into the trash, purged some days after
+    pub trashed: Option<NaiveDateTime>,
+    // done once a day rather than completed, with the sorted days it was done on, written
+    // as `habit:` followed by the log, see `habit::parse_log`
+    pub habit: Option<Vec<NaiveDate>>,
+    // shown above the other items of its section whatever their order, see `App::sections`
+    pub pinned: bool,
+    // free text under the item, one entry per line
+    pub notes: Vec<String>,
+    // nesting level, an item is a subtask of the closest item above it with a lower depth
+    pub depth: usize,
+    // subtasks are hidden, only kept while the program runs
+    pub folded: bool,
+    // set with `m<letter>` to jump back to with `'<letter>`, only kept while the program runs
+    pub mark: Option<char>,
+    // drawn flashing until then after becoming due, only kept while the program runs
+    pub flash: Option<NaiveDateTime>,
+    // where in a markdown file the item was read from, to write it back there and as it was
+    // while it is unchanged; only kept while the program runs
+    pub source: Option<Source>,
+}
+
+impl Item {
+    // pulls the known metadata tokens off the end of the line and a priority off its start,
+    // everything else stays text as it was typed, spacing included
+    pub fn parse(line: &str) -> Self {
+        let mut item = Item {
+            depth: (line.len() - line.trim_start_matches(' ').len()) / INDENT,
+            ..Item::default()
+        };
+        // the words with where they start in the line
+        let words: Vec<(usize, &str)> = line
+            .split_whitespace()
+            .map(|word| (word.as_ptr() as usize - line.as_ptr() as usize, word))
+            .collect();
+        let mut end = words.len();
+        while end > 0 {
+            let word = words[end - 1].1;
+            // the time may be typed as a word of its own, `due:2025-07-01 14:30`
+            let due_time = NaiveTime::parse_from_str(word, TIME_FORMAT).ok();
+            let due_date = (end > 1)
+                .then(|| words[end - 2].1.strip_prefix("due:"))
+                .flatten()
+                .and_then(|value| NaiveDate::parse_from_str(value, DATE_FORMAT).ok());
+            match due_time.zip(due_date) {
+                Some((time, date)) if item.due.is_none() => {
+                    item.due = Some(date);
+                    item.due_time = Some(time);
+                    end -= 2;
+                }
+                _ if item.take_token(word) => end -= 1,
+                _ => break,
+            }
+        }
+        // read from the end
+        item.reminders.reverse();
+        let mut start = 0;
+        if let Some(priority) = words[..end]
+            .first()
+            .and_then(|(_, word)| parse_priority(word))
+        {
+            item.priority = Some(priority);
+            start = 1;
+        }
+        if start < end {
+            let (from, _) = words[start];
+            let (to, last) = words[end - 1];
+            item.text = line[from..to + last.len()].to_string();
+        }
+        item
+    }
+
+    // sets what a metadata token says, false for a word that isn't one or a field already set
+    fn take_token(&mut self, word: &str) -> bool {
+        let Some((key, value)) = word.split_once(':') else {
+            return false;
+        };
+        match key {
+            "due" if self.due.is_none() => {
+                if let Some(due) = parse_time(value) {
+                    self.due = Some(due.date());
+                    self.due_time = Some(due.time());
+                } else {
+                    match NaiveDate::parse_from_str(value, DATE_FORMAT) {
+                        Ok(date) => self.due = Some(date),
+                        Err(_) => return false,
+                    }
+                }
+            }
+            "rec" if self.recur.is_none() => match Recurrence::parse(value) {
+                Some(recur) => self.recur = Some(recur),
+                None => return false,
+            },
+            "id" if self.id.is_none()
+                && !value.is_empty()
+                && value.chars().all(|c| c.is_ascii_alphanumeric()) =>
+            {
+                self.id = Some(value.to_string())
+            }
+            "remind" => match parse_time(value) {
+                Some(time) => self.reminders.push(time),
+                None => return false,
+            },
+            "est" if self.estimate.is_none() => match parse_minutes(value) {
+                Some(minutes) => self.estimate = Some(minutes),
+                None => return false,
+            },
+            "for" if self.assignee.is_none() && is_name(value) => {
+                self.assignee = Some(value.to_string())
+            }
+            "created" | "completed" | "touched" | "snoozed" | "trashed" => {
+                let Some(time) = parse_time(value) else {
+                    return false;
+                };
+                let field = match key {
+                    "created" => &mut self.created,
+                    "completed" => &mut self.completed,
+                    "touched" => &mut self.touched,
+                    "snoozed" => &mut self.snoozed,
+                    _ => &mut self.trashed,
+                };
+                if field.is_some() {
+                    return false;
+                }
+                *field = Some(time);
+            }
+            "habit" if self.habit.is_none() => match habit::parse_log(value) {
+                Some(days) => self.habit = Some(days),
+                None => return false,
+            },
+            "pinned" if value == "true" => self.pinned = true,
+            "pomodoros" | "tracked" => match value.parse() {
+                Ok(count) if key == "pomodoros" => self.pomodoros = count,
+                Ok(minutes) => self.tracked = minutes,
+                Err(_) => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    // the inverse of `parse`
+    pub fn to_line(&self) -> String {
+        let mut line = " ".repeat(self.depth * INDENT) + &self.tokens(zoned);
+        if let Some(id) = &self.id {
+            line.push_str(&format!(" id:{}", id));
+        }
+        if let Some(created) = self.created {
+            line.push_str(&format!(" created:{}", zoned(created)));
+        }
+        if let Some(completed) = self.completed {
+            line.push_str(&format!(" completed:{}", zoned(completed)));
+        }
+        if let Some(touched) = self.touched {
+            line.push_str(&format!(" touched:{}", zoned(touched)));
+        }
+        if let Some(snoozed) = self.snoozed {
+            line.push_str(&format!(" snoozed:{}", zoned(snoozed)));
+        }
+        if let Some(trashed) = self.trashed {
+            line.push_str(&format!(" trashed:{}", zoned(trashed)));
+        }
+        if let Some(days) = &self.habit {
+            line.push_str(&format!(" habit:{}", habit::format_log(days)));
+        }
+        if self.pinned {
+            line.push_str(" pinned:true");
+        }
+        if self.pomodoros > 0 {
+            line.push_str(&format!(" pomodoros:{}", self.pomodoros));
+        }
+        if self.tracked > 0 {
+            line.push_str(&format!(" tracked:{}", self.tracked));
+        }
+        line
+    }
+
+    // the part of the line a user types and edits, without the bookkeeping tokens and with
+    // its times in the local timezone
+    pub fn text_line(&self) -> String {
+        self.tokens(|time| time.format(TIMESTAMP_FORMAT).to_string())
+    }
+
+    // the text with its tokens, the times written with `time`
+    fn tokens(&self, time: fn(NaiveDateTime) -> String) -> String {
+        let mut line = match self.priority {
+            Some(priority) => format!("({}) {}", priority, self.text),
+            None => self.text.clone(),
+        };
+        match (self.due, self.due_time) {
+            (Some(due), Some(due_time)) => {
+                line.push_str(&format!(" due:{}", time(due.and_time(due_time))))
+            }
+            (Some(due), None) => line.push_str(&format!(" due:{}", due.format(DATE_FORMAT))),
+            _ => {}
+        }
+        if let Some(recur) = &self.recur {
+            line.push_str(&format!(" rec:{}", recur.rule()));
+        }
+        for reminder in &self.reminders {
+            line.push_str(&format!(" remind:{}", time(*reminder)));
+        }
+        if let Some(estimate) = self.estimate {
+            line.push_str(&format!(" est:{}", hours(estimate)));
+        }
+        if let Some(assignee) = &self.assignee {
+            line.push_str(&format!(" for:{}", assignee));
+        }
+        line
+    }
+
+    // the item after editing its `text_line`, keeping everything the line doesn't show
+    pub fn with_line(&self, line: &str) -> Self {
+        let item = Item::parse(line);
+        Self {
+            habit: self.habit.clone().or(item.habit),
+            id: self.id.clone(),
+            state: self.state.clone(),
+            created: self.created,
+            completed: self.completed,
+            touched: self.touched,
+            pomodoros: self.pomodoros,
+            tracked: self.tracked,
+            snoozed: self.snoozed,
+            trashed: self.trashed,
+            pinned: self.pinned,
+            notes: self.notes.clone(),
+            depth: self.depth,
+            folded: self.folded,
+            mark: self.mark,
+            source: self.source.clone(),
+            ..item
+        }
+    }
+
+    // whether `other` is this todo, possibly completed or changed otherwise since
+    pub fn is_same(&self, other: &Item) -> bool {
+        match (&self.id, &other.id) {
+            (Some(id), Some(other)) => id == other,
+            _ => self.text == other.text && self.created == other.created,
+        }
+    }
+
+    // the note lines that are checklist steps: their index in the notes, whether they are
+    // checked and their text
+    pub fn steps(&self) -> Vec<(usize, bool, &str)> {
+        self.notes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, note)| match note.strip_prefix(OPEN_STEP) {
+                Some(text) => Some((index, false, text)),
+                None => Some((index, true, note.strip_prefix(DONE_STEP)?)),
+            })
+            .collect()
+    }
+
+    // checked and all checklist steps, when there are any
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        let steps = self.steps();
+        let done = steps.iter().filter(|(_, checked, _)| *checked).count();
+        (!steps.is_empty()).then_some((done, steps.len()))
+    }
+
+    // whether the notes hold more than checklist steps
+    pub fn has_notes(&self) -> bool {
+        self.notes.len() > self.steps().len()
+    }
+
+    // the note line of a checklist step
+    pub fn step(checked: bool, text: &str) -> String {
+        let prefix = if checked { DONE_STEP } else { OPEN_STEP };
+        format!("{}{}", prefix, text)
+    }
+
+    pub fn is_snoozed(&self, now: NaiveDateTime) -> bool {
+        self.snoozed.is_some_and(|snoozed| snoozed > now)
+    }
+
+    // when the item becomes overdue: at its due time, at `default_time` on its due day
+    // when it has none, or else once the day is over
+    pub fn deadline(&self, default_time: Option<NaiveTime>) -> Option<NaiveDateTime> {
+        let due = self.due?;
+        match self.due_time.or(default_time) {
+            Some(time) => Some(due.and_time(time)),
+            None => Some(due.succ_opt()?.and_time(NaiveTime::MIN)),
+        }
+    }
+
+    pub fn urgency(&self, now: NaiveDateTime, default_time: Option<NaiveTime>) -> Urgency {
+        match self.deadline(default_time) {
+            Some(deadline) if deadline <= now => Urgency::Overdue,
+            _ if self.due == Some(now.date()) => Urgency::Today,
+            _ => Urgency::Later,
+        }
+    }
+}
+
+pub const PRIORITIES: [char; 3] = ['A', 'B', 'C'];
+
+fn parse_priority(word: &str) -> Option<char> {
+    let priority = word.strip_prefix('(')?.strip_suffix(')')?;
+    PRIORITIES
+        .into_iter()
+        .find(|&other| priority.len() == 1 && priority.starts_with(other))
+}
+
+// Todoist's `!p1` to `!p3` typed for the priorities `A` to `C`
+pub fn typed_priority(word: &str) -> Option<char> {
+    let level: usize = word.strip_prefix("!p")?.parse().ok()?;
+    PRIORITIES.get(level.checked_sub(1)?).copied()
+}
+
+// a time of the file in the local timezone: `2025-07-01T09:00+02:00` with the offset it was
+// written with, `2025-07-01T07:00Z` in UTC, or `2025-07-01T09:00` local already as written
+// before times had offsets; so a file shared between timezones is due at the same moment
+pub fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, TIMESTAMP_FORMAT).ok()?;
+        return Some(
+            Utc.from_utc_datetime(&time)
+                .with_timezone(&Local)
+                .naive_local(),
+        );
+    }
+    DateTime::parse_from_str(value, ZONED_FORMAT)
+        .map(|time| time.with_timezone(&Local).naive_local())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT))
+        .ok()
+}
+
+// a local time with its UTC offset, without one when the clocks skipped over it
+pub fn zoned(time: NaiveDateTime) -> String {
+    match Local.from_local_datetime(&time).earliest() {
+        Some(time) => time.format(ZONED_FORMAT).to_string(),
+        None => time.format(TIMESTAMP_FORMAT).to_string(),
+    }
+}
+
+// whether a typed word is metadata rather than text: a `#tag`, `+project` or `@context`,
+// a priority, a due date, a recurrence, a reminder, an estimate, an assignee or a habit
+pub fn is_token(word: &str) -> bool {
+    (word.len() > 1 && word.starts_with(['#', '+', '@']))
+        || typed_priority(word).is_some()
+        || parse_priority(word).is_some()
+        || word.strip_prefix("due:").is_some_and(|date| {
+            NaiveDate::parse_from_str(date, DATE_FORMAT).is_ok() || parse_time(date).is_some()
+        })
+        || word
+            .strip_prefix("rec:")
+            .is_some_and(|rule| Recurrence::parse(rule).is_some())
+        || word
+            .strip_prefix("remind:")
+            .is_some_and(|time| parse_time(time).is_some())
+        || word
+            .strip_prefix("est:")
+            .is_some_and(|estimate| parse_minutes(estimate).is_some())
+        || word.strip_prefix("for:").is_some_and(is_name)
+        || word
+            .strip_prefix("habit:")
+            .is_some_and(|log| habit::parse_log(log).is_some())
+}
+
+// a user name, like `alice` or `bob.smith`
+pub fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+// the priority `delta` levels up (positive) or down from `priority`, down from C being none
+pub fn shift_priority(priority: Option<char>, delta: isize) -> Option<char> {
+    let level = match priority {
+        Some(priority) => {
+            PRIORITIES.len() as isize
+                - PRIORITIES.iter().position(|&other| other == priority)? as isize
+        }
+        None => 0,
+    };
+    let level = (level + delta).clamp(0, PRIORITIES.len() as isize);
+    (level > 0).then(|| PRIORITIES[PRIORITIES.len() - level as usize])
+}
+
+// tracked time like `45m` or `3h05m`
+pub fn hours(minutes: u32) -> String {
+    match minutes {
+        0..=59 => format!("{}m", minutes),
+        _ => format!("{}h{:02}m", minutes / 60, minutes % 60),
+    }
+}
+
+// the inverse of `hours`, also taking whole hours like `2h`
+pub fn parse_minutes(text: &str) -> Option<u32> {
+    let (hours, rest): (u32, &str) = match text.split_once('h') {
+        Some((hours, rest)) => (hours.parse().ok()?, rest),
+        None => (0, text),
+    };
+    let minutes = match rest {
+        "" if hours > 0 => 0,
+        rest => rest.strip_suffix('m')?.parse().ok()?,
+    };
+    let total = hours.checked_mul(60)?.checked_add(minutes)?;
+    (total > 0).then_some(total)
+}
+
+// compact duration like `5m`, `3h` or `12d`
+pub fn age(since: NaiveDateTime, now: NaiveDateTime) -> String {
+    let minutes = (now - since).num_minutes().max(0);
+    match minutes {
+        0..=59 => format!("{}m", minutes),
+        60..=1439 => format!("{}h", minutes / 60),
+        _ => format!("{}d", minutes / 1440),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_line_writes_what_parse_reads() {
+        let line = "  (A) call mom #family due:2025-07-01 est:1h30m for:alice id:k3x9 pinned:true pomodoros:2";
+        assert_eq!(Item::parse(line).to_line(), line);
+    }
+
+    #[test]
+    fn parse_reads_what_to_line_writes() {
+        let time = NaiveDate::from_ymd_opt(2025, 7, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let item = Item {
+            id: Some("k3x9".to_string()),
+            text: "water the plants @home".to_string(),
+            priority: Some('B'),
+            due: time.date().succ_opt(),
+            due_time: Some(time.time()),
+            reminders: vec![time],
+            estimate: Some(20),
+            created: Some(time),
+            completed: Some(time),
+            habit: Some(vec![time.date()]),
+            tracked: 45,
+            depth: 1,
+            ..Item::default()
+        };
+        assert_eq!(Item::parse(&item.to_line()), item);
+    }
+
+    #[test]
+    fn parse_keeps_the_spacing_and_tokens_within_the_text() {
+        let item = Item::parse("(A) ask  about due:friday  and id:x1 for:bob est:1h id:k3x9");
+        assert_eq!(item.text, "ask  about due:friday  and id:x1");
+        assert_eq!(item.priority, Some('A'));
+        assert_eq!(item.id.as_deref(), Some("k3x9"));
+        assert_eq!(item.assignee.as_deref(), Some("bob"));
+        assert_eq!((item.due, item.estimate), (None, Some(60)));
+    }
+
+    #[test]
+    fn unknown_tokens_stay_text() {
+        let item = Item::parse("read due:someday rec:never est:long");
+        assert_eq!(item.text, "read due:someday rec:never est:long");
+        assert_eq!((item.due, item.recur, item.estimate), (None, None, None));
+    }
+}
@@ -0,0 +1,378 @@
+use crate::app::{self, List, Status};
+use crate::item::{self, Item, State};
+use crate::recur::Recurrence;
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+// `.json` files hold the text and every field of their items as values of their own, for
+// other tools to read and write
+pub fn is_json(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct File {
+    lists: Vec<JsonList>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct JsonList {
+    name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    todo: Vec<JsonItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    done: Vec<JsonItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    archived: Vec<JsonItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    trashed: Vec<JsonItem>,
+}
+
+// an item with its times in the format of the text files, see `item::parse_time`, and its
+// subtasks nested under it
+#[derive(Serialize, Deserialize, Default)]
+struct JsonItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    text: String,
+    // the `#tags` of the text, written for other tools and not read back
+    #[serde(default, skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<char>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recur: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reminders: Vec<String>,
+    // in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimate: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    touched: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snoozed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trashed: Option<String>,
+    // the days the habit was done on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    habit: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pinned: bool,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pomodoros: u32,
+    // in minutes
+    #[serde(default, skip_serializing_if = "is_zero")]
+    tracked: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subtasks: Vec<JsonItem>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+// the item and its subtasks, `items` starting with it
+fn to_json(items: &[Item]) -> JsonItem {
+    let item = &items[0];
+    let end = items[1..]
+        .iter()
+        .position(|other| other.depth <= item.depth)
+        .map_or(items.len(), |position| position + 1);
+    let mut subtasks = Vec::new();
+    let mut index = 1;
+    while index < end {
+        let subtask = to_json(&items[index..end]);
+        index += 1 + count(&subtask);
+        subtasks.push(subtask);
+    }
+    JsonItem {
+        id: item.id.clone(),
+        text: item.text.clone(),
+        tags: app::tags(&item.text)
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        state: item.state.as_ref().map(|state| state.name().to_string()),
+        priority: item.priority,
+        due: item.due.map(|due| due.format(DATE_FORMAT).to_string()),
+        due_time: item
+            .due_time
+            .map(|time| time.format(TIME_FORMAT).to_string()),
+        recur: item.recur.as_ref().map(Recurrence::rule),
+        reminders: item
+            .reminders
+            .iter()
+            .map(|&time| item::zoned(time))
+            .collect(),
+        estimate: item.estimate,
+        assignee: item.assignee.clone(),
+        created: item.created.map(item::zoned),
+        completed: item.completed.map(item::zoned),
+        touched: item.touched.map(item::zoned),
+        snoozed: item.snoozed.map(item::zoned),
+        trashed: item.trashed.map(item::zoned),
+        habit: item.habit.as_ref().map(|days| {
+            days.iter()
+                .map(|day| day.format(DATE_FORMAT).to_string())
+                .collect()
+        }),
+        pinned: item.pinned,
+        pomodoros: item.pomodoros,
+        tracked: item.tracked,
+        notes: item.notes.clone(),
+        subtasks,
+    }
+}
+
+// how many subtasks the item has at every level
+fn count(item: &JsonItem) -> usize {
+    item.subtasks.iter().map(|subtask| 1 + count(subtask)).sum()
+}
+
+fn to_json_items(items: &[Item]) -> Vec<JsonItem> {
+    let mut json = Vec::new();
+    let mut index = 0;
+    while index < items.len() {
+        let item = to_json(&items[index..]);
+        index += 1 + count(&item);
+        json.push(item);
+    }
+    json
+}
+
+// the item followed by its subtasks in list order
+fn from_json(json: &JsonItem, depth: usize, items: &mut Vec<Item>) -> Result<(), String> {
+    let time = |value: &str| {
+        item::parse_time(value)
+            .ok_or_else(|| format!("`{}` isn't a time like 2025-07-01T09:00+02:00", value))
+    };
+    let date = |value: &str| {
+        NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .map_err(|_| format!("`{}` isn't a date like 2025-07-01", value))
+    };
+    let item = Item {
+        id: json.id.clone(),
+        text: json.text.clone(),
+        state: match &json.state {
+            Some(name) => {
+                Some(State::parse(name).ok_or_else(|| format!("`{}` isn't a state", name))?)
+            }
+            None => None,
+        },
+        priority: match json.priority {
+            Some(priority) if !item::PRIORITIES.contains(&priority) => {
+                return Err(format!("`{}` isn't a priority", priority));
+            }
+            priority => priority,
+        },
+        due: json.due.as_deref().map(date).transpose()?,
+        due_time: match &json.due_time {
+            Some(time) => Some(
+                NaiveTime::parse_from_str(time, TIME_FORMAT)
+                    .map_err(|_| format!("`{}` isn't a time of day like 14:30", time))?,
+            ),
+            None => None,
+        },
+        recur: match &json.recur {
+            Some(rule) => {
+                Some(Recurrence::parse(rule).ok_or_else(|| format!("`{}` isn't a rule", rule))?)
+            }
+            None => None,
+        },
+        reminders: json
+            .reminders
+            .iter()
+            .map(|reminder| time(reminder))
+            .collect::<Result<_, _>>()?,
+        estimate: json.estimate,
+        assignee: json.assignee.clone(),
+        created: json.created.as_deref().map(time).transpose()?,
+        completed: json.completed.as_deref().map(time).transpose()?,
+        touched: json.touched.as_deref().map(time).transpose()?,
+        snoozed: json.snoozed.as_deref().map(time).transpose()?,
+        trashed: json.trashed.as_deref().map(time).transpose()?,
+        habit: match &json.habit {
+            Some(days) => {
+                let mut days = days
+                    .iter()
+                    .map(|day| date(day))
+                    .collect::<Result<Vec<_>, _>>()?;
+                days.sort();
+                days.dedup();
+                Some(days)
+            }
+            None => None,
+        },
+        pinned: json.pinned,
+        pomodoros: json.pomodoros,
+        tracked: json.tracked,
+        notes: json.notes.clone(),
+        depth,
+        ..Item::default()
+    };
+    items.push(item);
+    for subtask in &json.subtasks {
+        from_json(subtask, depth + 1, items)?;
+    }
+    Ok(())
+}
+
+pub fn load(content: &str) -> Result<Vec<List>, String> {
+    let file: File = serde_json::from_str(content).map_err(|err| err.to_string())?;
+    let mut lists = Vec::new();
+    for json in file.lists {
+        let mut list = List::new(json.name);
+        for (status, items) in [
+            (Status::Todo, &json.todo),
+            (Status::Done, &json.done),
+            (Status::Archived, &json.archived),
+            (Status::Trashed, &json.trashed),
+        ] {
+            for item in items {
+                from_json(item, 0, list.items_mut(status))?;
+            }
+        }
+        lists.push(list);
+    }
+    Ok(lists)
+}
+
+pub fn save(lists: &[List]) -> Vec<u8> {
+    let file = File {
+        lists: lists
+            .iter()
+            .map(|list| JsonList {
+                name: list.name.clone(),
+                todo: to_json_items(list.items(Status::Todo)),
+                done: to_json_items(list.items(Status::Done)),
+                archived: to_json_items(list.items(Status::Archived)),
+                trashed: to_json_items(list.items(Status::Trashed)),
+            })
+            .collect(),
+    };
+    let mut content = serde_json::to_vec_pretty(&file).unwrap();
+    content.push(b'\n');
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r##"{
+  "lists": [
+    {
+      "name": "main",
+      "todo": [
+        {
+          "id": "k3x9",
+          "text": "plan the trip #travel",
+          "tags": [
+            "#travel"
+          ],
+          "priority": "A",
+          "due": "2025-07-01",
+          "subtasks": [
+            {
+              "id": "p2m4",
+              "text": "book the train",
+              "state": "WAITING"
+            }
+          ]
+        }
+      ],
+      "done": [
+        {
+          "id": "q8w1",
+          "text": "pack",
+          "completed": "2025-06-30T18:00"
+        }
+      ]
+    },
+    {
+      "name": "work",
+      "trashed": [
+        {
+          "id": "z5c7",
+          "text": "old idea",
+          "trashed": "2025-06-01T09:00",
+          "notes": [
+            "from the meeting"
+          ]
+        }
+      ]
+    }
+  ]
+}
+"##;
+
+    fn items(lists: &[List]) -> Vec<&Item> {
+        lists
+            .iter()
+            .flat_map(|list| Status::ALL.map(|status| list.items(status)))
+            .flatten()
+            .collect()
+    }
+
+    // rather than `FILE` itself, as the times are written with the UTC offset of wherever
+    // the test runs
+    #[test]
+    fn save_writes_what_load_reads() {
+        let lists = load(FILE).unwrap();
+        let content = save(&lists);
+        let again = load(std::str::from_utf8(&content).unwrap()).unwrap();
+        assert_eq!(items(&again), items(&lists));
+        assert_eq!(save(&again), content);
+    }
+
+    #[test]
+    fn load_reads_subtasks_as_deeper_items() {
+        let lists = load(FILE).unwrap();
+        let todos = lists[0].items(Status::Todo);
+        assert_eq!(todos.len(), 2);
+        assert_eq!(
+            (todos[1].text.as_str(), todos[1].depth),
+            ("book the train", 1)
+        );
+        assert_eq!(todos[1].state, Some(State::Waiting));
+    }
+
+    #[test]
+    fn load_rejects_unknown_values() {
+        for (field, value) in [
+            ("priority", "Z"),
+            ("state", "later"),
+            ("recur", "sometimes"),
+        ] {
+            let content = format!(
+                r#"{{"lists": [{{"name": "main", "todo": [{{"text": "a", "{}": "{}"}}]}}]}}"#,
+                field, value
+            );
+            assert!(load(&content).is_err(), "{}", field);
+        }
+    }
+}
@@ -0,0 +1,857 @@
+use crate::ui::{KEY_CTRL_P, KEY_CTRL_R, KEY_ESCAPE};
+use ncurses::{KEY_BTAB, KEY_DOWN, KEY_ENTER, KEY_LEFT, KEY_RIGHT, KEY_UP};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Quit,
+    // quits saving without asking, for `:wq`
+    SaveQuit,
+    Up,
+    Down,
+    Top,
+    Bottom,
+    MoveUp,
+    MoveDown,
+    Transfer,
+    Toggle,
+    CycleState,
+    CancelItem,
+    Pin,
+    Insert,
+    InsertSubtask,
+    Nest,
+    Unnest,
+    Edit,
+    Duplicate,
+    DuplicateEdit,
+    Delete,
+    Undo,
+    Redo,
+    Restore,
+    Archive,
+    ArchiveOld,
+    CompleteAll,
+    ArchiveDone,
+    Save,
+    NextTab,
+    PrevTab,
+    NextList,
+    PrevList,
+    MoveToNextList,
+    MoveToPrevList,
+    MoveTo,
+    Split,
+    Unified,
+    HideDone,
+    Expand,
+    ScrollLeft,
+    ScrollRight,
+    Sort,
+    Details,
+    Filter,
+    CycleFilter,
+    Context,
+    Mine,
+    ClearFilter,
+    Today,
+    Group,
+    Fold,
+    Timestamps,
+    Search,
+    SearchNext,
+    SearchPrev,
+    Finder,
+    Calendar,
+    Agenda,
+    Stats,
+    Focus,
+    Pick,
+    Pomodoro,
+    Track,
+    OpenUrl,
+    Yank,
+    YankList,
+    YankClipboard,
+    Paste,
+    PasteBefore,
+    PasteClipboard,
+    Snooze,
+    ShowSnoozed,
+    RaisePriority,
+    LowerPriority,
+    LineNumbers,
+    Diff,
+    Visual,
+    Tag,
+    Tags,
+    Notes,
+    Checklist,
+    Board,
+    Template,
+    Record,
+    Replay,
+    Mark,
+    JumpToMark,
+    Palette,
+    Cancel,
+    Help,
+}
+
+impl Command {
+    // the name on the command line, e.g. `move-to-next-list` for `MoveToNextList`
+    pub fn name(self) -> String {
+        let mut name = String::new();
+        for ch in format!("{:?}", self).chars() {
+            if ch.is_ascii_uppercase() && !name.is_empty() {
+                name.push('-');
+            }
+            name.push(ch.to_ascii_lowercase());
+        }
+        name
+    }
+
+    // commands that change the lists or the file, refused in read-only mode
+    pub fn mutates(self) -> bool {
+        matches!(
+            self,
+            Command::MoveUp
+                | Command::MoveDown
+                | Command::Transfer
+                | Command::Toggle
+                | Command::CycleState
+                | Command::CancelItem
+                | Command::Pin
+                | Command::Insert
+                | Command::InsertSubtask
+                | Command::Nest
+                | Command::Unnest
+                | Command::Edit
+                | Command::Duplicate
+                | Command::DuplicateEdit
+                | Command::Delete
+                | Command::Undo
+                | Command::Redo
+                | Command::Restore
+                | Command::Archive
+                | Command::ArchiveOld
+                | Command::CompleteAll
+                | Command::ArchiveDone
+                | Command::Save
+                | Command::MoveToNextList
+                | Command::MoveToPrevList
+                | Command::MoveTo
+                | Command::Sort
+                | Command::Tag
+                | Command::Notes
+                | Command::Template
+                | Command::RaisePriority
+                | Command::LowerPriority
+                | Command::Snooze
+                | Command::Paste
+                | Command::PasteBefore
+                | Command::PasteClipboard
+                | Command::Pomodoro
+                | Command::Track
+        )
+    }
+}
+
+pub struct Binding {
+    pub keys: &'static [&'static [i32]],
+    pub label: &'static str,
+    pub command: Command,
+    pub description: &'static str,
+}
+
+const fn key(ch: char) -> i32 {
+    ch as i32
+}
+
+// normal mode keymap, the help overlay is rendered straight from this table.
+// most commands accept a count prefix, e.g. `5j`, and the register commands a `"x`
+// register prefix, `"+` being the system clipboard
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[&[key('k')], &[KEY_UP]],
+        label: "k",
+        command: Command::Up,
+        description: "move cursor up",
+    },
+    Binding {
+        keys: &[&[key('j')], &[KEY_DOWN]],
+        label: "j",
+        command: Command::Down,
+        description: "move cursor down",
+    },
+    Binding {
+        keys: &[&[key('g'), key('g')]],
+        label: "gg",
+        command: Command::Top,
+        description: "go to the first item, or the [count]th",
+    },
+    Binding {
+        keys: &[&[key('G')]],
+        label: "G",
+        command: Command::Bottom,
+        description: "go to the last item, or the [count]th",
+    },
+    Binding {
+        keys: &[&[key('K')]],
+        label: "K",
+        command: Command::MoveUp,
+        description: "move item up",
+    },
+    Binding {
+        keys: &[&[key('J')]],
+        label: "J",
+        command: Command::MoveDown,
+        description: "move item down",
+    },
+    Binding {
+        keys: &[&[key('\n')], &[KEY_ENTER]],
+        label: "ENTER",
+        command: Command::Transfer,
+        description: "toggle item between TODO and DONE or restore it from the trash, [count]ENTER goes to the [count]th",
+    },
+    Binding {
+        keys: &[&[key('x')], &[key(' ')]],
+        label: "x, SPACE",
+        command: Command::Toggle,
+        description: "toggle item, the cursor staying on it in the unified view",
+    },
+    Binding {
+        keys: &[&[key('b')]],
+        label: "b",
+        command: Command::CycleState,
+        description: "move todo to the next column of the board, by default IN-PROGRESS, then WAITING, then back to TODO",
+    },
+    Binding {
+        keys: &[&[key('X')]],
+        label: "X",
+        command: Command::CancelItem,
+        description: "complete todo as CANCELLED, recurring ones not coming back",
+    },
+    Binding {
+        keys: &[&[key('*')]],
+        label: "*",
+        command: Command::Pin,
+        description: "pin todo above the others whatever the sort order, or unpin it",
+    },
+    Binding {
+        keys: &[&[key('l')], &[KEY_RIGHT]],
+        label: "l",
+        command: Command::NextTab,
+        description: "next tab of TODO, DONE, ARCHIVE and TRASH",
+    },
+    Binding {
+        keys: &[&[key('h')], &[KEY_LEFT]],
+        label: "h",
+        command: Command::PrevTab,
+        description: "previous tab of TODO, DONE, ARCHIVE and TRASH",
+    },
+    Binding {
+        keys: &[&[key('\t')]],
+        label: "TAB",
+        command: Command::NextList,
+        description: "next list, or the [count]th (next tab with one list)",
+    },
+    Binding {
+        keys: &[&[KEY_BTAB]],
+        label: "S-TAB",
+        command: Command::PrevList,
+        description: "previous list",
+    },
+    Binding {
+        keys: &[&[key('>')]],
+        label: ">",
+        command: Command::MoveToNextList,
+        description: "move item to the next list",
+    },
+    Binding {
+        keys: &[&[key('<')]],
+        label: "<",
+        command: Command::MoveToPrevList,
+        description: "move item to the previous list",
+    },
+    Binding {
+        keys: &[&[key('M')]],
+        label: "M",
+        command: Command::MoveTo,
+        description: "move item to a list or another file picked from a menu",
+    },
+    Binding {
+        keys: &[&[key('|')]],
+        label: "|",
+        command: Command::Split,
+        description: "toggle side-by-side TODO and DONE panes",
+    },
+    Binding {
+        keys: &[&[key('U')]],
+        label: "U",
+        command: Command::Unified,
+        description: "toggle showing DONE items under the TODO items",
+    },
+    Binding {
+        keys: &[&[key('g'), key('h')]],
+        label: "gh",
+        command: Command::HideDone,
+        description: "hide or show the DONE items in the unified view",
+    },
+    Binding {
+        keys: &[&[key('=')]],
+        label: "=",
+        command: Command::Expand,
+        description: "toggle showing the full text of the selected item",
+    },
+    Binding {
+        keys: &[&[key('(')]],
+        label: "(",
+        command: Command::ScrollLeft,
+        description: "scroll the selected item back to the left, by [count] steps",
+    },
+    Binding {
+        keys: &[&[key(')')]],
+        label: ")",
+        command: Command::ScrollRight,
+        description: "scroll the selected item to the right to read the rest of a long one",
+    },
+    Binding {
+        keys: &[&[key('I')]],
+        label: "I",
+        command: Command::Details,
+        description: "toggle the detail pane for the selected item",
+    },
+    Binding {
+        keys: &[&[key('S')]],
+        label: "S",
+        command: Command::Sort,
+        description: "sort the list",
+    },
+    Binding {
+        keys: &[&[key('#')]],
+        label: "#",
+        command: Command::LineNumbers,
+        description: "cycle line numbers between off, absolute and relative",
+    },
+    Binding {
+        keys: &[&[key('T')]],
+        label: "T",
+        command: Command::Timestamps,
+        description: "toggle showing item ages",
+    },
+    Binding {
+        keys: &[&[key('f')]],
+        label: "f",
+        command: Command::Filter,
+        description: "show only items with a #tag or +project",
+    },
+    Binding {
+        keys: &[&[key('F')]],
+        label: "F",
+        command: Command::CycleFilter,
+        description: "filter by the next tag of the list",
+    },
+    Binding {
+        keys: &[&[key('g'), key('x')]],
+        label: "gx",
+        command: Command::Context,
+        description: "work in one @context, kept until switched back to all of them",
+    },
+    Binding {
+        keys: &[&[key('g'), key('t')]],
+        label: "gt",
+        command: Command::Tags,
+        description: "browse the tags of the list with their counts",
+    },
+    Binding {
+        keys: &[&[key('g'), key('n')]],
+        label: "gn",
+        command: Command::Notes,
+        description: "edit the notes of the selected item in $EDITOR",
+    },
+    Binding {
+        keys: &[&[key('g'), key('c')]],
+        label: "gc",
+        command: Command::Checklist,
+        description: "check off, add and delete the checklist steps of the selected item",
+    },
+    Binding {
+        keys: &[&[key('g'), key('i')]],
+        label: "gi",
+        command: Command::Template,
+        description: "add the items of a template to TODO, from the templates file next to the config",
+    },
+    Binding {
+        keys: &[&[key('g'), key('b')]],
+        label: "gb",
+        command: Command::Board,
+        description: "show the list as a board with a column per state, set with `columns` in the config",
+    },
+    Binding {
+        keys: &[&[key('g'), key('T')]],
+        label: "gT",
+        command: Command::Tag,
+        description: "add or remove a #tag on the selected item",
+    },
+    Binding {
+        keys: &[&[key('\\')]],
+        label: "\\",
+        command: Command::ClearFilter,
+        description: "clear the filter, keeping the context",
+    },
+    Binding {
+        keys: &[&[key('g'), key('m')]],
+        label: "gm",
+        command: Command::Mine,
+        description: "toggle showing only the items assigned to you with for:<user>",
+    },
+    Binding {
+        keys: &[&[key('.')]],
+        label: ".",
+        command: Command::Today,
+        description: "toggle showing only items due or created today, or tagged @today",
+    },
+    Binding {
+        keys: &[&[key('c')]],
+        label: "c",
+        command: Command::Calendar,
+        description: "calendar of due dates, ENTER filters by the day",
+    },
+    Binding {
+        keys: &[&[key('W')]],
+        label: "W",
+        command: Command::Agenda,
+        description: "agenda of what is due this week",
+    },
+    Binding {
+        keys: &[&[key('D')]],
+        label: "D",
+        command: Command::Stats,
+        description: "statistics of completed items and tags",
+    },
+    Binding {
+        keys: &[&[key('g'), key('f')]],
+        label: "gf",
+        command: Command::Focus,
+        description: "focus mode showing only the next task to do",
+    },
+    Binding {
+        keys: &[&[key('g'), key('r')]],
+        label: "gr",
+        command: Command::Pick,
+        description: "pick a random todo, favoring important and old ones",
+    },
+    Binding {
+        keys: &[&[key('g'), key('p')]],
+        label: "gp",
+        command: Command::Pomodoro,
+        description: "start a 25/5 minute pomodoro on the selected todo, or stop it",
+    },
+    Binding {
+        keys: &[&[key('t')]],
+        label: "t",
+        command: Command::Track,
+        description: "start or stop tracking time on the selected item",
+    },
+    Binding {
+        keys: &[&[key('o')]],
+        label: "o",
+        command: Command::OpenUrl,
+        description: "open a URL of the selected item in the browser",
+    },
+    Binding {
+        keys: &[&[key('y'), key('y')]],
+        label: "yy",
+        command: Command::Yank,
+        description: "copy the selected item, or [count] items, into a register",
+    },
+    Binding {
+        keys: &[&[key('Y')]],
+        label: "Y",
+        command: Command::YankList,
+        description: "copy the shown items to the clipboard as a checklist",
+    },
+    Binding {
+        keys: &[&[key('g'), key('y')]],
+        label: "gy",
+        command: Command::YankClipboard,
+        description: "copy the selected item, or [count] items, to the clipboard, like \"+yy",
+    },
+    Binding {
+        keys: &[&[key('p')]],
+        label: "p",
+        command: Command::Paste,
+        description: "paste a register after the selected item",
+    },
+    Binding {
+        keys: &[&[key('P')]],
+        label: "P",
+        command: Command::PasteBefore,
+        description: "paste a register before the selected item",
+    },
+    Binding {
+        keys: &[&[key('g'), key('P')]],
+        label: "gP",
+        command: Command::PasteClipboard,
+        description: "add each line of the clipboard as a new todo, like \"+p",
+    },
+    Binding {
+        keys: &[&[key('z')]],
+        label: "z",
+        command: Command::Snooze,
+        description: "snooze the selected todo, hiding it for a while",
+    },
+    Binding {
+        keys: &[&[key('g'), key('z')]],
+        label: "gz",
+        command: Command::ShowSnoozed,
+        description: "toggle showing snoozed todos",
+    },
+    Binding {
+        keys: &[&[key('+')]],
+        label: "+",
+        command: Command::RaisePriority,
+        description: "raise the priority of the selected item, up to (A)",
+    },
+    Binding {
+        keys: &[&[key('-')]],
+        label: "-",
+        command: Command::LowerPriority,
+        description: "lower the priority of the selected item, below (C) is none",
+    },
+    Binding {
+        keys: &[&[key('g'), key('s')]],
+        label: "gs",
+        command: Command::Group,
+        description: "group into sections by project, tag or due date",
+    },
+    Binding {
+        keys: &[&[key('Z')]],
+        label: "Z",
+        command: Command::Fold,
+        description: "fold or unfold the subtasks of the selected item, or the current section",
+    },
+    Binding {
+        keys: &[&[key('i')], &[key('a')]],
+        label: "i, a",
+        command: Command::Insert,
+        description: "add a new todo",
+    },
+    Binding {
+        keys: &[&[key('O')]],
+        label: "O",
+        command: Command::InsertSubtask,
+        description: "add a subtask to the selected todo",
+    },
+    Binding {
+        keys: &[&[key('L')]],
+        label: "L",
+        command: Command::Nest,
+        description: "make the selected item a subtask of the one above",
+    },
+    Binding {
+        keys: &[&[key('H')]],
+        label: "H",
+        command: Command::Unnest,
+        description: "move the selected subtask one level up",
+    },
+    Binding {
+        keys: &[&[key('e')], &[key('r')]],
+        label: "e, r",
+        command: Command::Edit,
+        description: "edit the selected item",
+    },
+    Binding {
+        keys: &[&[key('C')]],
+        label: "C",
+        command: Command::Duplicate,
+        description: "duplicate the selected item with its subtasks",
+    },
+    Binding {
+        keys: &[&[key('E')]],
+        label: "E",
+        command: Command::DuplicateEdit,
+        description: "duplicate the selected item and edit the copy",
+    },
+    Binding {
+        keys: &[&[key('d'), key('d')]],
+        label: "dd",
+        command: Command::Delete,
+        description: "cut the selected item, or [count] items, into a register and the trash",
+    },
+    Binding {
+        keys: &[&[key('s')]],
+        label: "s",
+        command: Command::Restore,
+        description: "copy the selected done item back to TODO",
+    },
+    Binding {
+        keys: &[&[key('A')]],
+        label: "A",
+        command: Command::Archive,
+        description: "archive the selected done item, or unarchive it",
+    },
+    Binding {
+        keys: &[&[key('g'), key('a')]],
+        label: "ga",
+        command: Command::ArchiveOld,
+        description: "archive items done more than a week ago, or `archive_days` from the config",
+    },
+    Binding {
+        keys: &[&[key('g'), key('D')]],
+        label: "gD",
+        command: Command::CompleteAll,
+        description: "complete every TODO item passing the filter",
+    },
+    Binding {
+        keys: &[&[key('g'), key('A')]],
+        label: "gA",
+        command: Command::ArchiveDone,
+        description: "archive every DONE item",
+    },
+    Binding {
+        keys: &[&[key('/')]],
+        label: "/",
+        command: Command::Search,
+        description: "search",
+    },
+    Binding {
+        keys: &[&[key('n')]],
+        label: "n",
+        command: Command::SearchNext,
+        description: "next search match",
+    },
+    Binding {
+        keys: &[&[key('N')]],
+        label: "N",
+        command: Command::SearchPrev,
+        description: "previous search match",
+    },
+    Binding {
+        keys: &[&[KEY_CTRL_P]],
+        label: "Ctrl-P",
+        command: Command::Finder,
+        description: "fuzzy find an item",
+    },
+    Binding {
+        keys: &[&[key('v')]],
+        label: "v",
+        command: Command::Visual,
+        description: "start selecting a range of items",
+    },
+    Binding {
+        keys: &[&[key('u')]],
+        label: "u",
+        command: Command::Undo,
+        description: "undo",
+    },
+    Binding {
+        keys: &[&[KEY_CTRL_R]],
+        label: "Ctrl-R",
+        command: Command::Redo,
+        description: "redo",
+    },
+    Binding {
+        keys: &[&[key('w')]],
+        label: "w",
+        command: Command::Save,
+        description: "save to file",
+    },
+    Binding {
+        keys: &[&[key('g'), key('d')]],
+        label: "gd",
+        command: Command::Diff,
+        description: "review what saving will change in the file",
+    },
+    Binding {
+        keys: &[&[key(':')]],
+        label: ":",
+        command: Command::Palette,
+        description: "command line, e.g. `:sort due`, `:move groceries`, TAB completes",
+    },
+    Binding {
+        keys: &[&[key('?')]],
+        label: "?",
+        command: Command::Help,
+        description: "show this help",
+    },
+    Binding {
+        keys: &[&[key('m')]],
+        label: "m<a-z>",
+        command: Command::Mark,
+        description: "mark the selected item",
+    },
+    Binding {
+        keys: &[&[key('\'')]],
+        label: "'<a-z>",
+        command: Command::JumpToMark,
+        description: "jump to a marked item wherever it moved, '' jumps back",
+    },
+    Binding {
+        keys: &[&[key('q')]],
+        label: "q<reg>",
+        command: Command::Record,
+        description: "record keys into a macro register, q again stops",
+    },
+    Binding {
+        keys: &[&[key('@')]],
+        label: "@<reg>",
+        command: Command::Replay,
+        description: "replay a macro [count] times, @@ replays the last one",
+    },
+    Binding {
+        keys: &[&[key('Q')]],
+        label: "Q",
+        command: Command::Quit,
+        description: "save and quit",
+    },
+];
+
+// keymap while a visual selection is active
+pub const VISUAL_BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[&[key('k')], &[KEY_UP]],
+        label: "k",
+        command: Command::Up,
+        description: "extend selection up",
+    },
+    Binding {
+        keys: &[&[key('j')], &[KEY_DOWN]],
+        label: "j",
+        command: Command::Down,
+        description: "extend selection down",
+    },
+    Binding {
+        keys: &[&[key('g'), key('g')]],
+        label: "gg",
+        command: Command::Top,
+        description: "extend selection to the first item",
+    },
+    Binding {
+        keys: &[&[key('G')]],
+        label: "G",
+        command: Command::Bottom,
+        description: "extend selection to the last item",
+    },
+    Binding {
+        keys: &[&[key('\n')], &[KEY_ENTER], &[key('x')]],
+        label: "ENTER, x",
+        command: Command::Transfer,
+        description: "toggle selected items between TODO and DONE",
+    },
+    Binding {
+        keys: &[&[key('X')]],
+        label: "X",
+        command: Command::CancelItem,
+        description: "complete selected todos as CANCELLED",
+    },
+    Binding {
+        keys: &[&[key('d')]],
+        label: "d",
+        command: Command::Delete,
+        description: "cut selected items into a register and the trash",
+    },
+    Binding {
+        keys: &[&[key('y')]],
+        label: "y",
+        command: Command::Yank,
+        description: "copy selected items into a register",
+    },
+    Binding {
+        keys: &[&[key('t')]],
+        label: "t",
+        command: Command::Tag,
+        description: "add a #tag to selected items",
+    },
+    Binding {
+        keys: &[&[key('A')]],
+        label: "A",
+        command: Command::Archive,
+        description: "archive or unarchive selected items",
+    },
+    Binding {
+        keys: &[&[key('K')]],
+        label: "K",
+        command: Command::MoveUp,
+        description: "move selected items up",
+    },
+    Binding {
+        keys: &[&[key('J')]],
+        label: "J",
+        command: Command::MoveDown,
+        description: "move selected items down",
+    },
+    Binding {
+        keys: &[&[KEY_ESCAPE], &[key('v')]],
+        label: "ESC, v",
+        command: Command::Cancel,
+        description: "leave visual mode",
+    },
+];
+
+// collects count prefixes and multi-key sequences until they resolve to a command
+#[derive(Default)]
+pub struct KeyState {
+    count: Option<usize>,
+    pending: Vec<i32>,
+    // `"` was typed and the register name comes next
+    naming: bool,
+    typed_register: Option<char>,
+    // the register given to the last command
+    pub register: Option<char>,
+}
+
+impl KeyState {
+    pub fn feed(&mut self, bindings: &[Binding], code: i32) -> Option<(Command, Option<usize>)> {
+        if self.naming {
+            self.naming = false;
+            self.typed_register = u32::try_from(code).ok().and_then(char::from_u32);
+            return None;
+        }
+        if self.pending.is_empty() && code == key('"') {
+            self.naming = true;
+            return None;
+        }
+        let is_digit = (key('0')..=key('9')).contains(&code);
+        if self.pending.is_empty() && is_digit && (code != key('0') || self.count.is_some()) {
+            let digit = (code - key('0')) as usize;
+            self.count = Some(
+                self.count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit),
+            );
+            return None;
+        }
+
+        self.pending.push(code);
+        let sequence = self.pending.as_slice();
+        if let Some(binding) = bindings
+            .iter()
+            .find(|binding| binding.keys.contains(&sequence))
+        {
+            let count = self.count.take();
+            self.register = self.typed_register.take();
+            self.pending.clear();
+            return Some((binding.command, count));
+        }
+
+        let is_prefix = bindings.iter().any(|binding| {
+            binding
+                .keys
+                .iter()
+                .any(|keys| keys.len() > sequence.len() && keys.starts_with(sequence))
+        });
+        if !is_prefix {
+            self.reset();
+        }
+        None
+    }
+
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.pending.clear();
+        self.naming = false;
+        self.typed_register = None;
+    }
+}
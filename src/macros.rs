@@ -0,0 +1,84 @@
+use ncurses::{ERR, KEY_RESIZE};
+use std::collections::{HashMap, VecDeque};
+
+// replays are stopped after this many keys without one typed in between,
+// e.g. a macro that replays itself
+const MAX_REPLAYED: usize = 10_000;
+
+// keystrokes recorded with `q<reg>` ... `q` and replayed with `@<reg>`
+#[derive(Default)]
+pub struct Macros {
+    recorded: HashMap<char, Vec<i32>>,
+    recording: Option<(char, Vec<i32>)>,
+    // keys of running replays that haven't been read yet
+    queue: VecDeque<i32>,
+    // keys replayed since the last typed one
+    replayed: usize,
+    last: Option<char>,
+}
+
+pub fn is_valid(register: char) -> bool {
+    register.is_ascii_alphanumeric()
+}
+
+impl Macros {
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    pub fn start(&mut self, register: char) {
+        self.recording = Some((register, Vec::new()));
+    }
+
+    // the key that stopped the recording isn't part of the macro
+    pub fn stop(&mut self) -> Option<(char, usize)> {
+        let (register, mut keys) = self.recording.take()?;
+        keys.pop();
+        let len = keys.len();
+        self.recorded.insert(register, keys);
+        Some((register, len))
+    }
+
+    // queues the macro `count` times, `@` standing for the last replayed one
+    pub fn replay(&mut self, register: char, count: usize) -> Result<(), String> {
+        let register = match register {
+            '@' => self.last.ok_or("No macro replayed yet")?,
+            register => register,
+        };
+        let keys = self
+            .recorded
+            .get(&register)
+            .filter(|keys| !keys.is_empty())
+            .ok_or_else(|| format!("Nothing recorded in @{}", register))?;
+        if self.replayed + keys.len().saturating_mul(count) > MAX_REPLAYED {
+            self.queue.clear();
+            return Err(format!("Stopped @{}, it replays too many keys", register));
+        }
+        // a replay started from a macro runs before the rest of that macro
+        for _ in 0..count {
+            for &key in keys.iter().rev() {
+                self.queue.push_front(key);
+            }
+        }
+        self.last = Some(register);
+        Ok(())
+    }
+
+    // the next queued key, or else the key from `read`, recording it if needed
+    pub fn next_key(&mut self, read: impl FnOnce() -> i32) -> i32 {
+        if let Some(key) = self.queue.pop_front() {
+            self.replayed += 1;
+            return key;
+        }
+        self.replayed = 0;
+        let key = read();
+        if let Some((_, keys)) = self
+            .recording
+            .as_mut()
+            .filter(|_| key != ERR && key != KEY_RESIZE)
+        {
+            keys.push(key);
+        }
+        key
+    }
+}
@@ -1,272 +1,3484 @@
-use chrono::Local;
+mod agenda;
+mod app;
+mod backup;
+mod board;
+mod config;
+mod dates;
+mod diff;
+mod ex;
+mod external;
+mod filter;
+mod fuzzy;
+mod group;
+mod habit;
+mod icons;
+mod item;
+mod json;
+mod keymap;
+mod macros;
+mod markdown;
+mod markup;
+mod merge;
+mod pomodoro;
+mod random;
+mod recur;
+mod registers;
+mod session;
+mod sqlite;
+mod stats;
+mod tasks;
+mod templates;
+#[cfg(test)]
+mod testing;
+mod theme;
+mod tracking;
+mod ui;
+mod watch;
+mod when;
+
+use app::{App, List, Status};
+use board::Column;
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, NaiveDateTime, Weekday,
+};
+use config::Config;
+use dates::DateFormat;
+use diff::Change;
+use ex::Ex;
+use filter::Filter;
+use group::Grouping;
+use item::{age, hours, Item, State, Urgency};
+use keymap::{Command, KeyState, BINDINGS, VISUAL_BINDINGS};
 use ncurses::*;
+use pomodoro::{Phase, Pomodoro};
+use random::Random;
+use registers::Registers;
+use session::Session;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
+use std::io;
 use std::io::Write;
-use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tasks::Tasks;
+use tracking::Tracker;
+use ui::*;
+use watch::Watch;
 
-const REGULAR_PAIR: i16 = 0;
-const HIGHLIGHT_PAIR: i16 = 1;
+const SCROLL_MARGIN: usize = 2;
+const DETAIL_MIN_WIDTH: usize = 24;
+const PROGRESS_WIDTH: usize = 10;
+// done items older than this are archived by `ga`, unless `archive_days` is set
+const ARCHIVE_AFTER_DAYS: i64 = 7;
+const AGENDA_DAYS: u64 = 7;
+// after the panes of the TODO, DONE, ARCHIVE and TRASH tabs
+const AGENDA_PANE: usize = 4;
+const DIFF_PANE: usize = 5;
+const TAGS_PANE: usize = 6;
+// the first column of the board, the others following it
+const BOARD_PANE: usize = 7;
+// columns of the tag browser, at most half the screen
+const TAGS_WIDTH: usize = 28;
+// unchanged lines shown around the changes in the diff screen
+const DIFF_CONTEXT: usize = 2;
+// columns `(` and `)` scroll the selected item by
+const SHIFT_STEP: usize = 10;
+// how long items that become due while the program runs flash
+const FLASH_SECONDS: i64 = 5;
+// with `autosave_seconds` set, how long unsaved changes are kept at most while the lists
+// keep changing
+const AUTOSAVE_LIMIT_SECONDS: i64 = 60;
+// name of the list holding items that come before any `[name]` header
+const DEFAULT_LIST: &str = "main";
 
-type Id = usize;
+#[derive(Clone, Copy)]
+enum Mode {
+    Normal,
+    Visual(usize),
+    Search,
+    // the `:` command line
+    Command,
+    Finder,
+    // the selected day
+    Calendar(NaiveDate),
+    // the selected item, counting across all days
+    Agenda(usize),
+    // the line at the cursor
+    Diff(usize),
+    // how many tasks were skipped from the top of `next_tasks`
+    Focus(usize),
+    // the tag at the cursor of the tag browser
+    Tags(usize),
+    // the checklist step at the cursor in the detail pane
+    Checklist(usize),
+    // the column and the item in it at the cursor
+    Board(usize, usize),
+    Stats,
+    Help,
+}
 
-#[derive(Default)]
-struct Ui {
-    list_current: Option<Id>,
-    row: usize,
-    col: usize,
+// the most useful keys of each mode, for the footer
+fn footer_hints(mode: &Mode) -> &'static [(&'static str, &'static str)] {
+    match mode {
+        Mode::Normal => &[
+            ("j/k", "move"),
+            ("i", "add"),
+            ("e", "edit"),
+            ("ENTER", "toggle"),
+            ("dd", "delete"),
+            ("u", "undo"),
+            ("h/l", "tab"),
+            ("/", "search"),
+            ("f", "filter"),
+            ("v", "select"),
+            ("w", "save"),
+            ("?", "help"),
+            ("Q", "quit"),
+        ],
+        Mode::Visual(_) => &[
+            ("j/k", "extend"),
+            ("ENTER", "toggle"),
+            ("d", "delete"),
+            ("y", "yank"),
+            ("t", "tag"),
+            ("J/K", "move"),
+            ("ESC", "cancel"),
+        ],
+        Mode::Search => &[("ENTER", "go"), ("ESC", "cancel")],
+        Mode::Command => &[("TAB", "complete"), ("ENTER", "run"), ("ESC", "cancel")],
+        Mode::Tags(_) => &[
+            ("j/k", "move"),
+            ("SPACE", "add/remove"),
+            ("a", "new tag"),
+            ("ENTER", "filter"),
+            ("ESC", "close"),
+        ],
+        Mode::Checklist(_) => &[
+            ("j/k", "move"),
+            ("SPACE", "check"),
+            ("a", "add step"),
+            ("d", "delete step"),
+            ("ESC", "close"),
+        ],
+        Mode::Board(..) => &[
+            ("h/l", "column"),
+            ("j/k", "move"),
+            ("H/L", "move item"),
+            ("ENTER", "go to item"),
+            ("ESC", "close"),
+        ],
+        _ => &[],
+    }
 }
 
-impl Ui {
-    fn begin(&mut self, row: usize, col: usize) {
-        self.row = row;
-        self.col = col;
+fn pane_of(status: Status) -> usize {
+    match status {
+        Status::Todo => 0,
+        Status::Done => 1,
+        Status::Archived => 2,
+        Status::Trashed => 3,
     }
-    fn begin_list(&mut self, id: Id) {
-        assert!(self.list_current.is_none(), "NESTED LISTS -> NOT ALLOWED");
-        self.list_current = Some(id);
+}
+
+// how an item of the tab is drawn in the lists and on the board
+fn item_pair(ui: &Ui, app: &App, status: Status, item: &Item, now: NaiveDateTime) -> i16 {
+    let pair = match status {
+        Status::Todo => REGULAR_PAIR,
+        _ => DONE_PAIR,
+    };
+    match (status, item.urgency(now, app.due_time), &item.state) {
+        (Status::Todo, Urgency::Overdue, _) => OVERDUE_PAIR,
+        (Status::Todo, Urgency::Today, _) => TODAY_PAIR,
+        (Status::Todo, _, _) if item.pinned => PINNED_PAIR,
+        (_, _, Some(state)) => state_pair(app, state),
+        (Status::Todo, Urgency::Later, _) if is_stale(status, item, now, ui.stale_days) => {
+            STALE_PAIR
+        }
+        (Status::Todo, Urgency::Later, _) => match item.priority {
+            Some('A') => PRIORITY_A_PAIR,
+            Some('B') => PRIORITY_B_PAIR,
+            Some('C') => PRIORITY_C_PAIR,
+            _ => pair,
+        },
+        _ => pair,
     }
+}
 
-    fn list_element(&mut self, label: &str, id: Id) -> bool {
-        let id_current = self
-            .list_current
-            .expect("LIST ELEMENTS -> NOT ALLOWED TO CREATE ELEMENT OUTSIDE OF LIST");
+// custom states have the pair of their column
+fn state_pair(app: &App, state: &State) -> i16 {
+    match state {
+        State::InProgress => IN_PROGRESS_PAIR,
+        State::Waiting => WAITING_PAIR,
+        State::Cancelled => CANCELLED_PAIR,
+        State::Custom(_) => app
+            .columns
+            .iter()
+            .filter(|column| matches!(column.state(), Some(State::Custom(_))))
+            .position(|column| column.state() == Some(state))
+            .map_or(REGULAR_PAIR, |position| COLUMN_PAIR + position as i16),
+    }
+}
 
-        self.label(label, {
-            if id_current == id {
-                HIGHLIGHT_PAIR
-            } else {
-                REGULAR_PAIR
-            }
-        });
+// whether the item is shown with a ticked checkbox, trashed items keeping theirs; TODO
+// items have theirs ticked when done for the day as habits, or as subtasks of markdown files
+fn is_ticked(status: Status, item: &Item) -> bool {
+    match status {
+        Status::Todo => {
+            item.completed.is_some()
+                || item
+                    .habit
+                    .as_ref()
+                    .is_some_and(|days| days.contains(&Local::now().date_naive()))
+        }
+        Status::Done | Status::Archived => true,
+        Status::Trashed => item.completed.is_some(),
+    }
+}
 
-        false
+#[derive(Clone, Copy, PartialEq)]
+enum LineNumbers {
+    Off,
+    Absolute,
+    // distance from the cursor, the cursor's line showing its own number
+    Relative,
+}
+
+impl LineNumbers {
+    fn next(self) -> Self {
+        match self {
+            LineNumbers::Off => LineNumbers::Absolute,
+            LineNumbers::Absolute => LineNumbers::Relative,
+            LineNumbers::Relative => LineNumbers::Off,
+        }
     }
+}
+
+// a screen row of a list: a section header or an item
+enum Row {
+    Section(String, usize),
+    Item(usize),
+}
 
-    fn end_list(&mut self) {
-        self.list_current = None;
+// the rows of the lists of `statuses` one after another in display order, a folded
+// section is stood in for by its first item
+fn list_rows(app: &App, statuses: &[Status]) -> Vec<(Status, Row)> {
+    let mut rows = Vec::new();
+    for &status in statuses {
+        for (name, indices) in app.sections(status) {
+            if app.grouping != Grouping::None {
+                rows.push((status, Row::Section(name.clone(), indices[0])));
+            }
+            if !app.is_collapsed(&name) {
+                rows.extend(indices.into_iter().map(|index| (status, Row::Item(index))));
+            }
+        }
     }
+    rows
+}
+
+// the row the item is shown on, which is its section header when the section is folded
+fn row_of(app: &App, rows: &[(Status, Row)], status: Status, index: usize) -> Option<usize> {
+    rows.iter().position(|(other_status, row)| {
+        *other_status == status
+            && match row {
+                Row::Item(other) => *other == index,
+                Row::Section(name, first) => *first == index && app.is_collapsed(name),
+            }
+    })
+}
 
-    fn label(&mut self, text: &str, pair: i16) {
-        mv(self.row as i32, self.col as i32);
-        attron(COLOR_PAIR(pair));
-        addstr(text);
-        attroff(COLOR_PAIR(pair));
-        self.row += 1;
+// the tabs shown in the pane the mouse hit
+fn pane_statuses(app: &App, pane: usize, split: bool) -> Vec<Status> {
+    if split && !app.unified {
+        vec![Status::ALL[pane]]
+    } else {
+        app.shown()
     }
+}
 
-    fn end(&mut self) {}
+// whether the item has been waiting to be done for more than `days` days
+fn is_stale(status: Status, item: &Item, now: NaiveDateTime, days: i64) -> bool {
+    status == Status::Todo
+        && days > 0
+        && item
+            .created
+            .is_some_and(|created| (now - created).num_days() > days)
 }
 
-#[derive(Debug)]
-enum Status {
-    Todo,
-    Done,
+// renders the lists of `statuses` as one, the DONE items of the unified view in the
+// done colors under the TODO items; `selection` is the (anchor, cursor) pair of a
+// visual selection in the current tab
+fn render_list(
+    ui: &mut Ui,
+    app: &App,
+    statuses: &[Status],
+    timestamps: bool,
+    numbers: LineNumbers,
+    selection: Option<(usize, usize)>,
+) {
+    let (todo, done) = (ui.icons.todo.clone(), ui.icons.done.clone());
+    let now = Local::now().naive_local();
+    let rows = list_rows(app, statuses);
+    // leave room for the fold markers once the list has subtasks
+    let tree = statuses
+        .iter()
+        .any(|&status| app.list(status).iter().any(|item| item.depth > 0));
+    // numbers count the items the cursor can go to, the same ones `[count]G` counts
+    let visible: Vec<(Status, usize)> = statuses
+        .iter()
+        .flat_map(|&status| {
+            app.visible(status)
+                .into_iter()
+                .map(move |index| (status, index))
+        })
+        .collect();
+    let focused = if statuses.contains(&app.tab) {
+        app.tab
+    } else {
+        statuses[0]
+    };
+    let current_position = visible
+        .iter()
+        .position(|&position| position == (focused, app.current(focused)))
+        .unwrap_or(0);
+    let gutter = match numbers {
+        LineNumbers::Off => 0,
+        _ => visible.len().to_string().len() + 1,
+    };
+    let labels: Vec<String> = rows
+        .iter()
+        .map(|(status, row)| match row {
+            Row::Section(name, _) => {
+                let count = app
+                    .sections(*status)
+                    .iter()
+                    .find(|(other, _)| other == name)
+                    .map_or(0, |(_, indices)| indices.len());
+                let marker = if app.is_collapsed(name) { '▸' } else { '▾' };
+                format!("{} {} ({})", marker, name, count)
+            }
+            Row::Item(index) => {
+                let status = *status;
+                let item = &app.list(status)[*index];
+                let checkbox = if is_ticked(status, item) {
+                    &done
+                } else {
+                    &todo
+                };
+                let subtasks = app.subtree(status, *index).len() - 1;
+                let marker = match (subtasks, item.folded) {
+                    (0, _) if tree => "  ",
+                    (0, _) => "",
+                    (_, false) => "▾ ",
+                    (_, true) => "▸ ",
+                };
+                let state = item
+                    .state
+                    .as_ref()
+                    .map_or(String::new(), |state| format!("{} ", state.name()));
+                let pin = if item.pinned {
+                    format!("{} ", ui.icons.pin)
+                } else {
+                    String::new()
+                };
+                let mut label = format!(
+                    "{}{}{} {}{}{}",
+                    "  ".repeat(item.depth),
+                    marker,
+                    checkbox,
+                    pin,
+                    state,
+                    ui.icons.decorate(&item.text_line())
+                );
+                if item.folded && subtasks > 0 {
+                    label.push_str(&format!(" [+{}]", subtasks));
+                }
+                if let Some((done, total)) = item.progress() {
+                    label.push_str(&format!(" ({}/{})", done, total));
+                }
+                if let Some(days) = &item.habit {
+                    label.push_str(&format!(" [streak {}]", habit::streak(days, now.date())));
+                }
+                if item.has_notes() {
+                    label.push_str(" [notes]");
+                }
+                let stamp = match status {
+                    Status::Todo => item
+                        .created
+                        .map(|created| format!("{} old", age(created, now))),
+                    Status::Done | Status::Archived => item
+                        .completed
+                        .map(|completed| format!("done {} ago", age(completed, now))),
+                    Status::Trashed => item
+                        .trashed
+                        .map(|trashed| format!("deleted {} ago", age(trashed, now))),
+                };
+                if let Some(stamp) = stamp.filter(|_| timestamps) {
+                    label.push_str(&format!("  ({})", stamp));
+                }
+                if is_stale(status, item, now, ui.stale_days) {
+                    label.push_str("  !");
+                }
+                if let Some(mark) = item.mark.filter(|&mark| mark != '\'') {
+                    label.push_str(&format!("  '{}", mark));
+                }
+                if let Some(snoozed) = item.snoozed.filter(|_| item.is_snoozed(now)) {
+                    label.push_str(&format!(
+                        "  (snoozed until {})",
+                        ui.dates.short_time(snoozed)
+                    ));
+                }
+                label
+            }
+        })
+        .collect();
+
+    ui.selection = selection.and_then(|(anchor, cursor)| {
+        let anchor = row_of(app, &rows, focused, anchor)?;
+        let cursor = row_of(app, &rows, focused, cursor)?;
+        Some((anchor.min(cursor), anchor.max(cursor)))
+    });
+    let labels: Vec<String> = labels
+        .into_iter()
+        .zip(&rows)
+        .map(|(label, (status, row))| {
+            let position = match row {
+                Row::Item(index) => visible.iter().position(|other| *other == (*status, *index)),
+                Row::Section(name, first) if app.is_collapsed(name) => {
+                    visible.iter().position(|other| *other == (*status, *first))
+                }
+                Row::Section(..) => None,
+            };
+            let number = match (numbers, position) {
+                (LineNumbers::Off, _) | (_, None) => String::new(),
+                (LineNumbers::Relative, Some(position)) if position != current_position => {
+                    position.abs_diff(current_position).to_string()
+                }
+                (_, Some(position)) => (position + 1).to_string(),
+            };
+            match numbers {
+                LineNumbers::Off => label,
+                _ => format!("{:>2$} {}", number, label, gutter - 1),
+            }
+        })
+        .collect();
+    let current = row_of(app, &rows, focused, app.current(focused)).unwrap_or(0);
+    let current_rows = labels
+        .get(current)
+        .map_or(1, |label| ui.element_rows(label));
+    ui.begin_list(current, labels.len(), current_rows);
+    ui.set_gutter(gutter);
+    for (id, (label, (status, row))) in labels.iter().zip(&rows).enumerate() {
+        let status = *status;
+        let pair = match row {
+            Row::Section(..) => HEADER_PAIR,
+            // flashing items blink between the selection colors and their own
+            Row::Item(index)
+                if app.list(status)[*index]
+                    .flash
+                    .is_some_and(|until| now < until)
+                    && now.and_utc().timestamp_subsec_millis() < 500 =>
+            {
+                SELECTION_PAIR
+            }
+            Row::Item(index) => item_pair(ui, app, status, &app.list(status)[*index], now),
+        };
+        ui.list_element(label, id, pair);
+    }
+    ui.end_list();
+
+    if rows.is_empty() && app.filter.is_active() {
+        ui.label("Nothing matches the filter", REGULAR_PAIR)
+    } else if statuses[0] == Status::Todo && rows.is_empty() {
+        ui.label("Everything done, enjoy the day", REGULAR_PAIR)
+    }
 }
 
-impl Status {
-    fn toggle(&self) -> Self {
-        match self {
-            Status::Todo => Status::Done,
-            Status::Done => Status::Todo,
+// "October 2026" with the number of TODO items due on each day of the month of `date`
+fn calendar(app: &App, date: NaiveDate, dates: &DateFormat) -> (String, Vec<usize>, usize) {
+    let first = date.with_day(1).unwrap();
+    let next = first + Months::new(1);
+    let mut counts = vec![0; (next - first).num_days() as usize];
+    for item in app.list(Status::Todo) {
+        if let Some(due) = item.due.filter(|due| (first..next).contains(due)) {
+            counts[due.day0() as usize] += 1;
         }
     }
+    let title = dates.month(first);
+    (
+        title,
+        counts,
+        first.weekday().num_days_from_monday() as usize,
+    )
 }
 
-fn parse_todo(line: &str) -> Option<(Status, &str)> {
-    let todo_prefix = "TODO: ";
-    let done_prefix = "DONE: ";
+// edits the notes of the selected item in an external editor, leaving out the blank
+// lines around them
+fn edit_notes(app: &mut App) -> Option<Message> {
+    let item = app.selected()?.clone();
+    match external::edit(&item.notes.join("\n")) {
+        Ok(text) => {
+            let lines: Vec<String> = text
+                .lines()
+                .map(|line| line.trim_end().to_string())
+                .collect();
+            let start = lines
+                .iter()
+                .position(|line| !line.is_empty())
+                .unwrap_or(lines.len());
+            let end = lines
+                .iter()
+                .rposition(|line| !line.is_empty())
+                .map_or(start, |end| end + 1);
+            let notes = lines[start..end].to_vec();
+            if notes != item.notes {
+                app.edit(Item { notes, ..item });
+            }
+            None
+        }
+        Err(err) => Some(Message::error(format!("Could not edit the notes: {}", err))),
+    }
+}
 
-    if line.starts_with(todo_prefix) {
-        return Some((Status::Todo, &line[todo_prefix.len()..]));
+// adds the tag to the selected item or takes it off, saying which
+fn toggle_tag(app: &mut App, tag: &str) -> Option<Message> {
+    let tag = format!("#{}", tag.trim_start_matches('#'));
+    match app.toggle_tag(&tag) {
+        Some(true) => Some(Message::info(format!("Added {}", tag))),
+        Some(false) => Some(Message::info(format!("Removed {}", tag))),
+        None if app.selected().is_none() => Some(Message::error("No item selected.")),
+        None => Some(Message::error(format!("{} is all the item says", tag))),
     }
+}
 
-    if line.starts_with(done_prefix) {
-        return Some((Status::Done, &line[done_prefix.len()..]));
+// the tags of the list with their counts, ticked when the selected item has them
+fn render_tags(ui: &mut Ui, app: &App, tags: &[(String, usize)], selected: usize) {
+    ui.label("Tags", HEADER_PAIR);
+    ui.rule();
+    if tags.is_empty() {
+        ui.label("No #tags yet, a adds one", DONE_PAIR);
+        return;
+    }
+    let selected = selected.min(tags.len() - 1);
+    ui.begin_list(selected, tags.len(), 1);
+    for (id, (tag, count)) in tags.iter().enumerate() {
+        let checkbox = if app.has_tag(tag) {
+            &ui.icons.done
+        } else {
+            &ui.icons.todo
+        };
+        let label = format!("{} {} ({})", checkbox, tag, count);
+        ui.list_element(&label, id, REGULAR_PAIR);
     }
+    ui.end_list();
+}
 
-    None
+// a pane per column side by side, `selected` being the column and row of the cursor
+fn render_board(ui: &mut Ui, app: &App, top: usize, width: usize, selected: (usize, usize)) {
+    let now = Local::now().naive_local();
+    let column_width = width / app.columns.len().max(1);
+    ui.selection = None;
+    for (index, column) in app.columns.iter().enumerate() {
+        let items = board::items(app, column);
+        let focused = index == selected.0;
+        ui.begin_pane(
+            BOARD_PANE + index,
+            top,
+            index * column_width,
+            column_width.saturating_sub(1),
+            focused,
+        );
+        ui.label(&format!("{} ({})", column.name(), items.len()), HEADER_PAIR);
+        ui.rule();
+        let current = selected.1.min(items.len().saturating_sub(1));
+        ui.begin_list(current, items.len(), 1);
+        for (id, &item) in items.iter().enumerate() {
+            let item = &app.list(column.status())[item];
+            let label = ui.icons.decorate(&item.text_line());
+            let pair = item_pair(ui, app, column.status(), item, now);
+            ui.list_element(&label, id, pair);
+        }
+        ui.end_list();
+    }
 }
 
-fn list_up(list_current: &mut usize) {
-    if *list_current > 0 {
-        *list_current -= 1;
+fn render_agenda(ui: &mut Ui, app: &App, days: &[agenda::Day], selected: usize) {
+    let now = Local::now().naive_local();
+    ui.label("Agenda", HEADER_PAIR);
+    ui.rule();
+    let mut rows: Vec<(String, i16)> = Vec::new();
+    let mut item_rows = Vec::new();
+    for day in days {
+        rows.push((day.label.clone(), HEADER_PAIR));
+        if day.items.is_empty() {
+            rows.push(("    nothing due".to_string(), DONE_PAIR));
+        }
+        for &(list, index) in day.items.iter() {
+            let item = &app.lists[list].todos[index];
+            let mut label = format!("  {} ", ui.icons.todo);
+            if let Some(time) = item.due_time {
+                label.push_str(&format!("{} ", time.format("%H:%M")));
+            }
+            label.push_str(&ui.icons.decorate(&item.text));
+            if app.lists.len() > 1 {
+                label.push_str(&format!("  ({})", app.lists[list].name));
+            }
+            let pair = match item.urgency(now, app.due_time) {
+                Urgency::Overdue => OVERDUE_PAIR,
+                Urgency::Today => TODAY_PAIR,
+                Urgency::Later => REGULAR_PAIR,
+            };
+            item_rows.push(rows.len());
+            rows.push((label, pair));
+        }
     }
+    // with nothing to select the list scrolls to the top
+    let current = item_rows.get(selected).copied().unwrap_or(0);
+    ui.begin_list(current, rows.len(), 1);
+    for (id, (label, pair)) in rows.iter().enumerate() {
+        ui.list_element(label, id, *pair);
+    }
+    ui.end_list();
 }
 
-fn list_down(list: &Vec<String>, list_current: &mut usize) {
-    if *list_current + 1 < list.len() {
-        *list_current += 1;
+// "Overdue: ..." and "Due today: ..." for the TODO items of every list
+fn due_reminders(app: &App, now: NaiveDateTime) -> Vec<String> {
+    let mut reminders = Vec::new();
+    for list in app.lists.iter() {
+        for item in list.todos.iter() {
+            match item.urgency(now, app.due_time) {
+                Urgency::Overdue => reminders.push(format!("Overdue: {}", item.text)),
+                Urgency::Today => reminders.push(format!("Due today: {}", item.text)),
+                Urgency::Later => {}
+            }
+        }
     }
+    reminders
 }
 
-fn list_transfer(
-    list_dst: &mut Vec<String>,
-    list_src: &mut Vec<String>,
-    list_src_curr: &mut usize,
-) {
-    if *list_src_curr < list_src.len() {
-        list_dst.push(list_src.remove(*list_src_curr));
-        if *list_src_curr >= list_src.len() && list_src.len() > 0 {
-            *list_src_curr = list_src.len() - 1;
+// opens the URL of the selected item, asking which one when there are several
+fn open_url(ui: &mut Ui, app: &App) -> Option<Message> {
+    let item = app.selected()?;
+    let urls = app::urls(&item.text);
+    let url = match urls.as_slice() {
+        [] => return Some(Message::info("No URL in this item.")),
+        [url] => *url,
+        urls => {
+            let options: Vec<String> = urls
+                .iter()
+                .take(9)
+                .enumerate()
+                .map(|(index, url)| format!("{}  {}", index + 1, url))
+                .collect();
+            let options: Vec<&str> = options.iter().map(String::as_str).collect();
+            urls[ui.menu("Open", &options)?]
+        }
+    };
+    Some(match external::open_url(url) {
+        Ok(()) => Message::info(format!("Opened {}", url)),
+        Err(err) => Message::error(format!("ERROR: could not open {}: {}", url, err)),
+    })
+}
+
+fn copy(text: &str, what: &str) -> Message {
+    match external::copy(text) {
+        Ok(()) => Message::info(format!("Copied {}", what)),
+        Err(err) => Message::error(format!("ERROR: could not copy: {}", err)),
+    }
+}
+
+// the shown items of the current tab as a markdown checklist
+fn checklist(app: &App) -> String {
+    app.visible(app.tab)
+        .into_iter()
+        .map(|index| {
+            let item = &app.list(app.tab)[index];
+            let checkbox = if is_ticked(app.tab, item) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            format!("{}- {} {}\n", "  ".repeat(item.depth), checkbox, item.text)
+        })
+        .collect()
+}
+
+// the todos in pasted text, one per line, without list bullets or checkboxes;
+// indented lines become subtasks
+fn pasted_items(text: &str) -> Vec<Item> {
+    text.lines()
+        .map(|line| {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let line = line.trim();
+            let line = ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|bullet| line.strip_prefix(bullet))
+                .unwrap_or(line);
+            let line = ["[ ] ", "[x] ", "[X] "]
+                .iter()
+                .find_map(|checkbox| line.strip_prefix(checkbox))
+                .unwrap_or(line)
+                .trim();
+            (indent.replace('\t', "  "), line)
+        })
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(indent, line)| Item::parse(&format!("{}{}", indent, line)))
+        .collect()
+}
+
+// what saving would change in the files on disk, as lines with their colors
+fn diff_lines(app: &App, file_path: &str) -> Vec<(String, i16)> {
+    let files = match serialize_state(app, file_path) {
+        Ok(files) => files,
+        Err(err) => return vec![(format!("ERROR: {}", err), OVERDUE_PAIR)],
+    };
+    let mut lines = Vec::new();
+    for (path, content) in files.iter() {
+        let old = on_disk(path).unwrap_or_default();
+        let old = String::from_utf8_lossy(&old);
+        let new = String::from_utf8_lossy(content);
+        let old: Vec<&str> = old.lines().collect();
+        let new: Vec<&str> = new.lines().collect();
+        let hunks = diff::hunks(&diff::diff(&old, &new), DIFF_CONTEXT);
+        if hunks.is_empty() {
+            continue;
+        }
+        lines.push((format!("--- {}", path.display()), HEADER_PAIR));
+        for line in hunks {
+            lines.push(match line {
+                Some(Change::Same(line)) => (format!("  {}", line), REGULAR_PAIR),
+                Some(Change::Added(line)) => (format!("+ {}", line), DONE_PAIR),
+                Some(Change::Removed(line)) => (format!("- {}", line), OVERDUE_PAIR),
+                None => ("  ...".to_string(), HEADER_PAIR),
+            });
         }
     }
+    if lines.is_empty() {
+        lines.push(("No unsaved changes".to_string(), REGULAR_PAIR));
+    }
+    lines
+}
+
+// e.g. `[TODO (12)]` when focused or ` DONE (34) ` otherwise
+fn tab_label(app: &App, status: Status, focused: bool) -> String {
+    let label = format!("{} ({})", status.name(), app.list(status).len());
+    if focused {
+        format!("[{}]", label)
+    } else {
+        format!(" {} ", label)
+    }
 }
 
-fn save_state(todos: &Vec<String>, dones: &Vec<String>, file_path: &str) {
-    let mut file = File::create(file_path).unwrap();
-    for todo in todos.iter() {
-        writeln!(file, "TODO: {}", todo).unwrap();
+// unsaved changes, progress, active filter and read-only mode, shown after the date in
+// the header
+fn header_info(app: &App, read_only: bool, dirty: bool) -> String {
+    let mut info = if dirty { "* " } else { "" }.to_string();
+    info.push_str(&progress(app));
+    if let Some(filter) = app.filter.describe() {
+        info.push_str(&format!("  [filter: {}]", filter));
     }
-    for done in dones.iter() {
-        writeln!(file, "DONE: {}", done).unwrap();
+    if read_only {
+        info.push_str("  [read-only]");
     }
+    info
 }
 
-fn load_state(todos: &mut Vec<String>, dones: &mut Vec<String>, file_path: &str) {
-    let file = File::open(file_path).unwrap();
-    for (index, line) in io::BufReader::new(file).lines().enumerate() {
-        match parse_todo(&line.unwrap()) {
-            Some((Status::Todo, title)) => todos.push(title.to_string()),
-            Some((Status::Done, title)) => dones.push(title.to_string()),
-            None => {
-                eprintln!(
-                    "{}:{}: ERROR: item line format incorrectly",
-                    file_path,
-                    index + 1
-                );
-                process::exit(1);
+// e.g. `[###-------] 3/10 done (30%)` for the active list
+fn progress(app: &App) -> String {
+    let done = app.list(Status::Done).len();
+    let total = done + app.list(Status::Todo).len();
+    if total == 0 {
+        return String::new();
+    }
+    let filled = done * PROGRESS_WIDTH / total;
+    format!(
+        "[{}{}] {}/{} done ({}%)",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_WIDTH - filled),
+        done,
+        total,
+        done * 100 / total
+    )
+}
+
+const SORT_OPTIONS: &[&str] = &[
+    "u  urgency, overdue first",
+    "p  priority",
+    "d  due date",
+    "c  creation date",
+    "a  alphabetical",
+    "m  manual order",
+];
+
+fn sort_menu(ui: &mut Ui, app: &mut App) -> Option<Message> {
+    sort(app, ui.menu("Sort by", SORT_OPTIONS)?)
+}
+
+// sorts by the `key`th of SORT_OPTIONS
+fn sort(app: &mut App, key: usize) -> Option<Message> {
+    let now = Local::now().naive_local();
+    let due_time = app.due_time;
+    match key {
+        0 => app.sort_by_key(|item| item.urgency(now, due_time)),
+        1 => app.sort_by_key(|item| (item.priority.is_none(), item.priority)),
+        2 => app.sort_by_key(|item| {
+            let deadline = item.deadline(due_time);
+            (deadline.is_none(), deadline)
+        }),
+        3 => app.sort_by_key(|item| (item.created.is_none(), item.created)),
+        4 => app.sort_by_key(|item| item.text.to_lowercase()),
+        _ => {
+            if !app.restore_manual_order() {
+                return Some(Message::info("List is in manual order."));
             }
         }
     }
+    None
 }
 
-// TODO: undo system
-// TODO: new elements to list(todo) maybe done
-// TODO: keep track of dates
-// DONE: persist app state (save)
-// TODO: edit todos
-// TODO: add priority to todos and tags?
-// TODO: delete items
-// TODO: only show daily todos
-// TODO: save state
+// adds the time of the tracker to its item
+fn stop_tracking(app: &mut App, tracker: &Tracker) -> Message {
+    let minutes = tracker.minutes();
+    if minutes == 0 {
+        Message::info("Tracked less than a minute, nothing logged")
+    } else if app.log_time(tracker.list, &tracker.item, minutes) {
+        Message::info(format!(
+            "Tracked {} on {}",
+            hours(minutes),
+            tracker.item.text
+        ))
+    } else {
+        Message::error(format!(
+            "Stopped tracking, {} is gone",
+            ellipsize(&tracker.item.text, 20)
+        ))
+    }
+}
 
-fn main() {
-    let mut args = env::args();
-    args.next().unwrap();
+// moves the selected item and its subtasks to another list picked from a menu, or appends
+// them to another file
+fn move_menu(ui: &mut Ui, app: &mut App, file_path: &str) -> Option<Message> {
+    app.selected()?;
+    let others: Vec<usize> = (0..app.lists.len())
+        .filter(|&list| list != app.active)
+        .collect();
+    let mut options: Vec<String> = others
+        .iter()
+        .enumerate()
+        .map(|(number, &list)| format!("{}  {}", number + 1, app.lists[list].name))
+        .collect();
+    options.push("f  another file".to_string());
+    let options: Vec<&str> = options.iter().map(String::as_str).collect();
+    let choice = ui.menu("Move to", &options)?;
+    if let Some(&list) = others.get(choice) {
+        app.move_to_list(list);
+        return Some(Message::info(format!("Moved to {}", app.lists[list].name)));
+    }
 
-    let file_path = {
-        match args.next() {
-            Some(file_path) => file_path,
-            None => {
-                eprintln!("Usage: todo-rs <file-path>");
-                eprintln!("ERROR: no filepath provided");
-                process::exit(1);
-            }
+    let path = ui.prompt("Move to file", "")?;
+    let path = Path::new(path.trim());
+    if path.as_os_str().is_empty() {
+        return None;
+    }
+    if fs::canonicalize(path).ok() == fs::canonicalize(file_path).ok() {
+        return Some(Message::info("Already in that file."));
+    }
+    let current = app.current(app.tab);
+    let items = app.items_between(current, current);
+    match append_items(path, app.tab, &items) {
+        Ok(()) => {
+            app.remove();
+            Some(Message::info(format!("Moved to {}", path.display())))
         }
+        Err(err) => Some(Message::error(format!(
+            "ERROR: could not write {}: {}",
+            path.display(),
+            err
+        ))),
+    }
+}
+
+// asks for the text of an item, highlighting its metadata and previewing the due date
+// of words like `tomorrow` at its end
+fn prompt_item(ui: &mut Ui, title: &str, initial: &str) -> Option<String> {
+    let today = Local::now().date_naive();
+    let dates = ui.dates.clone();
+    let preview = |line: &str| {
+        let (_, due, time) = when::split(line, today);
+        due.map(|due| match time {
+            Some(time) => format!("due {} {}", dates.day(due), time.format("%H:%M")),
+            None => format!("due {}", dates.day(due)),
+        })
     };
+    ui.prompt_with(title, initial, preview, item::is_token)
+}
 
-    let mut quit = false;
-    let mut todos = Vec::<String>::new();
-    let mut todo_current: usize = 0;
-    let mut dones = Vec::<String>::new();
-    let mut done_current: usize = 0;
+// the due date of the item, with its time when it has one
+fn due_label(item: &Item, dates: &DateFormat) -> Option<String> {
+    let due = item.due?;
+    Some(match item.due_time {
+        Some(time) => dates.time(due.and_time(time)),
+        None => dates.date(due),
+    })
+}
 
-    load_state(&mut todos, &mut dones, &file_path);
+// `base` with the typed line, `!p1` to `!p3` setting its priority and the date at its end
+// its due date and time
+fn typed_item(base: &Item, line: &str) -> Item {
+    let mut priority = None;
+    let words: Vec<&str> = line
+        .split_whitespace()
+        .filter(|word| match item::typed_priority(word) {
+            Some(typed) => {
+                priority = Some(typed);
+                false
+            }
+            None => true,
+        })
+        .collect();
+    let (line, due, time) = when::split(&words.join(" "), Local::now().date_naive());
+    let item = base.with_line(&line);
+    let (due, due_time) = match due {
+        Some(due) => (Some(due), time),
+        None => (item.due, item.due_time),
+    };
+    Item {
+        priority: priority.or(item.priority),
+        due,
+        due_time,
+        ..item
+    }
+}
 
-    initscr();
-    let current_day = Local::now();
-    let formatted_date = current_day.format("%d/%m/%Y");
+const QUIT_OPTIONS: &[&str] = &["y  yes", "n  no", "c  cancel"];
 
-    // disable echo and cursor
-    noecho();
-    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+const CHANGED_OPTIONS: &[&str] = &["r  reload theirs", "k  keep mine", "m  merge both"];
 
-    start_color();
-    init_pair(REGULAR_PAIR, COLOR_WHITE, COLOR_BLACK);
-    init_pair(HIGHLIGHT_PAIR, COLOR_BLACK, COLOR_WHITE);
+// asks what to do about another program having changed the files since they were read or
+// saved; what they hold is read again, with the ids of the items written without one
+// taken from the same items as read before
+fn on_disk_change(
+    ui: &mut Ui,
+    app: &mut App,
+    file_path: &str,
+    watch: &mut Watch,
+    saved: &mut Option<Vec<(PathBuf, Vec<u8>)>>,
+) -> Message {
+    let mut disk = App::default();
+    disk.lists = match read_lists(file_path, &app.columns) {
+        Ok(lists) => lists,
+        Err(err) => {
+            watch.skip();
+            return Message::error(err);
+        }
+    };
+    merge::adopt_ids(&mut disk.lists, &watch.base);
+    disk.assign_ids();
+    let title = format!("{} was changed by another program", file_path);
+    let message = match ui.menu(&title, CHANGED_OPTIONS) {
+        Some(0) => {
+            app.replace_lists(disk.lists.clone());
+            *saved = disk_state(app, file_path);
+            Message::info("Reloaded.")
+        }
+        Some(2) => {
+            let (lists, conflicts) = merge::merge(&watch.base, &app.lists, &disk.lists);
+            app.replace_lists(lists);
+            *saved = None;
+            match conflicts {
+                0 => Message::info("Merged."),
+                count => Message::info(format!(
+                    "Merged, kept yours of {} changed on both sides",
+                    plural(count)
+                )),
+            }
+        }
+        _ => {
+            *saved = None;
+            Message::info("Kept yours, saving writes over theirs.")
+        }
+    };
+    watch.synced(&disk.lists);
+    message
+}
 
-    let mut tab = Status::Todo;
+const SNOOZE_OPTIONS: &[&str] = &[
+    "l  later today, in 3 hours",
+    "t  tomorrow",
+    "w  next week",
+    "c  custom date",
+    "n  not snoozed",
+];
 
-    let mut ui = Ui::default();
+// hides the selected todo until a time picked from the snooze presets
+fn snooze_menu(ui: &mut Ui, app: &mut App) -> Option<Message> {
+    let item = app.selected()?.clone();
+    let now = Local::now().naive_local();
+    let midnight = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap();
+    let snoozed = match ui.menu("Snooze until", SNOOZE_OPTIONS)? {
+        0 => Some(now + chrono::Duration::hours(3)),
+        1 => Some(midnight(now.date() + Days::new(1))),
+        2 => {
+            let monday = now.date().week(Weekday::Mon).first_day();
+            Some(midnight(monday + Days::new(7)))
+        }
+        3 => {
+            let input = ui.prompt("Snooze until (YYYY-MM-DD)", "")?;
+            match NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d") {
+                Ok(date) => Some(midnight(date)),
+                Err(_) => return Some(Message::error(format!("ERROR: invalid date `{}`", input))),
+            }
+        }
+        _ => None,
+    };
+    app.edit(Item { snoozed, ..item });
+    Some(Message::info(match snoozed {
+        Some(snoozed) => format!("Snoozed until {}", ui.dates.time(snoozed)),
+        None => "No longer snoozed".to_string(),
+    }))
+}
 
-    while !quit {
-        erase();
-        ui.begin(0, 0);
-        {
-            match tab {
-                Status::Todo => {
-                    ui.label(
-                        format!("[TODO] DONE  {}:", formatted_date.to_string()).as_str(),
-                        REGULAR_PAIR,
-                    );
-                    ui.label("------------------------", REGULAR_PAIR);
-                    ui.begin_list(todo_current);
-                    for (index, todo) in todos.iter().enumerate() {
-                        ui.list_element(&format!("[ ] {}", todo), index);
-                    }
+// asks before completing todos from `anchor` to `cursor` that have open subtasks,
+// which get completed along with them
+fn confirm_subtasks(ui: &mut Ui, app: &App, anchor: usize, cursor: usize) -> bool {
+    if app.tab != Status::Todo {
+        return true;
+    }
+    let selected = app.visible_between(anchor, cursor);
+    let subtasks = app.with_subtasks(selected.clone()).len() - selected.len();
+    match subtasks {
+        0 => true,
+        1 => ui.confirm("Also complete its open subtask?"),
+        count => ui.confirm(&format!("Also complete {} open subtasks?", count)),
+    }
+}
 
-                    ui.end_list();
+// asks before deleting start..=end of the active list along with their subtasks,
+// returns what was deleted
+fn confirm_delete(ui: &mut Ui, app: &mut App, anchor: usize, cursor: usize) -> Option<Vec<Item>> {
+    let count = app.with_subtasks(app.visible_between(anchor, cursor)).len();
+    if count == 0 {
+        return None;
+    }
+    let question = match (count, app.tab) {
+        (1, Status::Trashed) => "Purge this item for good?".to_string(),
+        (count, Status::Trashed) => format!("Purge {} items for good?", count),
+        (1, _) => "Delete this item?".to_string(),
+        (count, _) => format!("Delete {} items?", count),
+    };
+    if !ui.confirm(&question) {
+        return None;
+    }
+    let items = app.items_between(anchor, cursor);
+    app.delete_range(anchor, cursor);
+    Some(items)
+}
 
-                    if todos.len() < 1 {
-                        ui.label("Everything done, enjoy the day", REGULAR_PAIR)
-                    }
-                }
-                Status::Done => {
-                    ui.label(
-                        format!(" TODO [DONE] {}:", formatted_date.to_string()).as_str(),
-                        REGULAR_PAIR,
-                    );
-                    ui.label("------------------------", REGULAR_PAIR);
-                    ui.begin_list(done_current);
-                    for (index, done) in dones.iter().enumerate() {
-                        ui.list_element(&format!("[x] {}", done), index);
-                    }
-                    ui.end_list();
-                }
+// asks for the template unless `name` is given and for its placeholders, then adds its
+// items to the end of TODO
+fn add_template(ui: &mut Ui, app: &mut App, name: Option<&str>) -> Option<Message> {
+    let templates = match templates::load() {
+        Ok(templates) => templates,
+        Err(err) => return Some(Message::error(err)),
+    };
+    if templates.is_empty() {
+        let path = templates::templates_path().unwrap_or_default();
+        return Some(Message::info(format!(
+            "No templates yet, add them to {}",
+            path.display()
+        )));
+    }
+    let template = match name {
+        Some(name) => match templates
+            .iter()
+            .find(|template| template.name.eq_ignore_ascii_case(name))
+        {
+            Some(template) => template,
+            None => return Some(Message::error(format!("No template named {}", name))),
+        },
+        None => {
+            let options: Vec<String> = templates
+                .iter()
+                .enumerate()
+                .map(|(index, template)| format!("{}  {}", index + 1, template.name))
+                .collect();
+            let options: Vec<&str> = options.iter().map(String::as_str).collect();
+            &templates[ui.menu("Add from template", &options)?]
+        }
+    };
+    let mut values = Vec::new();
+    for name in template.placeholders() {
+        let value = ui.prompt(&name, "")?;
+        values.push((name, value));
+    }
+    // a line can't be nested deeper than a subtask of the one before it
+    let mut depth = 0;
+    let items: Vec<Item> = template
+        .expand(&values, Local::now().date_naive())
+        .into_iter()
+        .enumerate()
+        .map(|(index, (nesting, line))| {
+            depth = if index == 0 {
+                0
+            } else {
+                nesting.min(depth + 1)
+            };
+            Item {
+                depth,
+                ..typed_item(&Item::default(), &line)
             }
+        })
+        .collect();
+    let count = items.len();
+    app.add_all(items);
+    Some(Message::info(format!(
+        "{} added from {}",
+        plural(count),
+        template.name
+    )))
+}
+
+// the `@context` to work in, picked from the ones of the list
+fn switch_context(ui: &mut Ui, app: &mut App) -> Option<Message> {
+    let contexts = app.contexts();
+    if contexts.is_empty() && app.filter.context.is_none() {
+        return Some(Message::info("No @contexts in this list yet"));
+    }
+    let options: Vec<String> = ["all".to_string()]
+        .iter()
+        .chain(&contexts)
+        .enumerate()
+        .map(|(index, context)| format!("{}  {}", index, context))
+        .collect();
+    let options: Vec<&str> = options.iter().map(String::as_str).collect();
+    let selected = ui.menu("Switch context", &options)?;
+    Some(set_context(
+        app,
+        selected.checked_sub(1).map(|index| contexts[index].clone()),
+    ))
+}
+
+fn set_context(app: &mut App, context: Option<String>) -> Message {
+    let message = match &context {
+        Some(context) => format!("Working in {}", context),
+        None => "Working in all contexts".to_string(),
+    };
+    app.set_filter(Filter {
+        context,
+        ..app.filter.clone()
+    });
+    Message::info(message)
+}
+
+// runs a command line command that has no key of its own
+fn run_ex(ui: &mut Ui, app: &mut App, ex: Ex) -> Option<Message> {
+    match ex {
+        Ex::Run(..) => None,
+        Ex::Sort(key) => sort(app, key),
+        Ex::Filter(word) => {
+            app.set_filter(Filter {
+                word: Some(filter::parse_word(&word)),
+                ..app.filter.clone()
+            });
+            None
         }
-        ui.end();
+        Ex::Context(context) => Some(set_context(app, context)),
+        Ex::Fit(minutes) => {
+            app.tab = Status::Todo;
+            app.set_filter(Filter {
+                fit: Some(minutes),
+                ..app.filter.clone()
+            });
+            None
+        }
+        Ex::Group(grouping) => {
+            app.set_grouping(grouping);
+            Some(Message::info(format!("Grouped by {}", grouping.name())))
+        }
+        Ex::Archive(days) => {
+            let count = match days {
+                Some(days) => app.archive_old(days),
+                None => app.archive_done(),
+            };
+            Some(Message::info(match count {
+                0 => "Nothing to archive.".to_string(),
+                count => format!("{} archived", plural(count)),
+            }))
+        }
+        Ex::Move(list) if list == app.active => Some(Message::info("Already in that list.")),
+        Ex::Move(list) => {
+            app.selected()?;
+            app.move_to_list(list);
+            Some(Message::info(format!("Moved to {}", app.lists[list].name)))
+        }
+        Ex::List(list) => {
+            app.switch_list(list);
+            None
+        }
+        Ex::New(line) => {
+            app.add(Item::parse(&line));
+            None
+        }
+        Ex::Template(name) => add_template(ui, app, Some(&name)),
+    }
+}
 
-        refresh();
+// the register or mark name typed after `q`, `@`, `m` or `'`, `None` when cancelled with ESC
+fn read_register(ui: &mut Ui) -> Option<char> {
+    loop {
+        match ui.read_key() {
+            ERR | KEY_RESIZE => {}
+            KEY_ESCAPE => return None,
+            key => return key_char(key),
+        }
+    }
+}
+
+// "1 item" or "3 items"
+fn plural(count: usize) -> String {
+    match count {
+        1 => "1 item".to_string(),
+        count => format!("{} items", count),
+    }
+}
 
-        let key = getch();
+// the cursor and the item `count` - 1 visible items below it, the range of `3dd` or `3yy`
+fn counted(app: &App, count: Option<usize>) -> (usize, usize) {
+    let cursor = app.current(app.tab);
+    let visible = app.visible(app.tab);
+    let position = visible.iter().position(|&index| index == cursor);
+    let last = position
+        .and_then(|position| visible.get(position + count.unwrap_or(1) - 1))
+        .or(visible.last())
+        .copied()
+        .unwrap_or(cursor);
+    (cursor, last)
+}
 
-        // movement keys
-        match key as u8 as char {
-            'q' => quit = true,
-            'k' => match tab {
-                Status::Todo => list_up(&mut todo_current),
-                Status::Done => list_up(&mut done_current),
-            },
-            'j' => match tab {
-                Status::Todo => list_down(&todos, &mut todo_current),
-                Status::Done => list_down(&dones, &mut done_current),
-            },
-            '\n' => match tab {
-                Status::Todo => list_transfer(&mut dones, &mut todos, &mut todo_current),
-                Status::Done => list_transfer(&mut todos, &mut dones, &mut done_current),
-            },
+fn is_register(name: char) -> bool {
+    name == '+' || registers::is_valid(name)
+}
 
-            's' => todos.push(dones[done_current].clone()),
-            'e' => {
-                let mut file = File::create("TODO").unwrap();
-                for todo in todos.iter() {
-                    writeln!(file, "TODO: {}", todo);
-                }
-                for done in dones.iter() {
-                    writeln!(file, "DONE: {}", done);
-                }
+// puts cut or copied items into `register`, `+` being the system clipboard
+fn store(
+    registers: &mut Registers,
+    register: Option<char>,
+    items: Vec<Item>,
+    cut: bool,
+) -> Message {
+    let what = plural(items.len());
+    if register == Some('+') {
+        let text: String = items
+            .iter()
+            .map(|item| format!("{}{}\n", "  ".repeat(item.depth), item.text_line()))
+            .collect();
+        return copy(&text, &what);
+    }
+    if cut {
+        registers.cut(register, items);
+        Message::info(format!("Cut {}", what))
+    } else {
+        registers.copy(register, items);
+        Message::info(format!("Copied {}", what))
+    }
+}
+
+// what pasting `register` adds, `+` being the system clipboard
+fn register_items(registers: &Registers, register: Option<char>) -> io::Result<Vec<Item>> {
+    match register {
+        Some('+') => external::paste().map(|text| pasted_items(&text)),
+        _ => Ok(registers.get(register).cloned().unwrap_or_default()),
+    }
+}
+
+fn timestamp(dates: &DateFormat, time: Option<chrono::NaiveDateTime>) -> String {
+    time.map_or("-".to_string(), |time| dates.time(time))
+}
+
+// what the detail pane shows about the selected item
+fn detail_lines(app: &App, dates: &DateFormat) -> Vec<String> {
+    let Some(item) = app.selected() else {
+        return vec!["No item selected".to_string()];
+    };
+    let tags = app::tags(&item.text);
+    let mut lines = vec![
+        item.text.clone(),
+        String::new(),
+        format!("ID:      {}", item.id.as_deref().unwrap_or("-")),
+        match &item.state {
+            Some(state) => format!("Status:  {}, {}", app.tab.name(), state.name()),
+            None => format!("Status:  {}", app.tab.name()),
+        },
+        format!("List:    {}", app.lists[app.active].name),
+        format!(
+            "Item:    {} of {}",
+            app.current(app.tab) + 1,
+            app.list(app.tab).len()
+        ),
+        format!(
+            "Due:     {}",
+            due_label(item, dates).unwrap_or("-".to_string())
+        ),
+        format!("Created: {}", timestamp(dates, item.created)),
+        format!("Done:    {}", timestamp(dates, item.completed)),
+        format!("Snoozed: {}", timestamp(dates, item.snoozed)),
+        format!(
+            "Remind:  {}",
+            if item.reminders.is_empty() {
+                "-".to_string()
+            } else {
+                item.reminders
+                    .iter()
+                    .map(|&reminder| dates.time(reminder))
+                    .collect::<Vec<_>>()
+                    .join(", ")
             }
-            '\t' => {
-                tab = tab.toggle();
+        ),
+        format!("Pomodoros: {}", item.pomodoros),
+        format!("Tracked: {}", hours(item.tracked)),
+        format!("Estimate: {}", item.estimate.map_or("-".to_string(), hours)),
+        format!("Assignee: {}", item.assignee.as_deref().unwrap_or("-")),
+        format!(
+            "Tags:    {}",
+            if tags.is_empty() {
+                "-".to_string()
+            } else {
+                tags.join(" ")
             }
-            _ => {}
+        ),
+    ];
+    if let Some(days) = &item.habit {
+        let today = Local::now().date_naive();
+        lines.push(String::new());
+        lines.push(format!(
+            "Habit:   {} day streak, done {} days",
+            habit::streak(days, today),
+            days.len()
+        ));
+        lines.extend(habit::heat_map(days, today));
+    }
+    if !item.notes.is_empty() {
+        lines.push(String::new());
+        lines.extend(item.notes.iter().cloned());
+    }
+    lines
+}
+
+// what the lines of an item's notes start with in the file, under the item
+const NOTE_INDENT: &str = "    ";
+
+// what an item line starts with in the file
+fn line_prefix(status: Status) -> &'static str {
+    match status {
+        Status::Todo => "TODO: ",
+        Status::Done => "DONE: ",
+        Status::Archived => "ARCHIVED: ",
+        Status::Trashed => "TRASHED: ",
+    }
+}
+
+// TODO and DONE items in a state start with it instead, archived and trashed ones give it
+// after their tab as in `ARCHIVED: CANCELLED: `
+fn item_prefix(status: Status, item: &Item) -> String {
+    match &item.state {
+        Some(state) if matches!(status, Status::Todo | Status::Done) => {
+            format!("{}: ", state.name())
         }
+        Some(state) => format!("{}{}: ", line_prefix(status), state.name()),
+        None => line_prefix(status).to_string(),
     }
-    getch();
+}
 
-    save_state(&todos, &dones, &file_path);
-    endwin();
+fn parse_state(line: &str) -> Option<(State, &str)> {
+    let (name, title) = line.split_once(": ")?;
+    Some((State::parse(name)?, title))
+}
+
+// any state starts a line, so that items stay readable when their column is taken out of
+// the config; after the tab only the known ones do, the text may start with a capital word
+fn parse_todo<'a>(line: &'a str, columns: &[Column]) -> Option<(Status, Option<State>, &'a str)> {
+    if let Some((state, title)) = parse_state(line) {
+        let status = if state.fits(Status::Todo) {
+            Status::Todo
+        } else {
+            Status::Done
+        };
+        return Some((status, Some(state), title));
+    }
+    let (status, title) = Status::ALL
+        .into_iter()
+        .find_map(|status| Some((status, line.strip_prefix(line_prefix(status))?)))?;
+    match parse_state(title) {
+        Some((state, title))
+            if matches!(status, Status::Archived | Status::Trashed)
+                && (!matches!(state, State::Custom(_))
+                    || columns.iter().any(|column| column.state() == Some(&state))) =>
+        {
+            Some((status, Some(state), title))
+        }
+        _ => Some((status, None, title)),
+    }
+}
+
+fn parse_header(line: &str) -> Option<&str> {
+    line.strip_prefix('[')?.strip_suffix(']').map(str::trim)
+}
+
+fn write_list(out: &mut impl Write, list: &List) -> io::Result<()> {
+    for status in Status::ALL {
+        write_items(out, status, list.items(status))?;
+    }
+    Ok(())
+}
+
+fn write_items(out: &mut impl Write, status: Status, items: &[Item]) -> io::Result<()> {
+    for item in items {
+        writeln!(out, "{}{}", item_prefix(status, item), item.to_line())?;
+        for note in item.notes.iter() {
+            writeln!(out, "{}{}", NOTE_INDENT, note)?;
+        }
+    }
+    Ok(())
+}
+
+// adds the items to the end of the file at `path`, creating it if needed; they end up in
+// its last list when it has several
+fn append_items(path: &Path, status: Status, items: &[Item]) -> io::Result<()> {
+    if sqlite::is_sqlite(path) {
+        return sqlite::append(path, status, items, DEFAULT_LIST).map_err(io::Error::other);
+    }
+    let old = match fs::read(path) {
+        Ok(old) => old,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    if json::is_json(path) {
+        // a JSON file is written again as a whole
+        let mut lists = if old.is_empty() {
+            Vec::new()
+        } else {
+            json::load(&String::from_utf8_lossy(&old)).map_err(io::Error::other)?
+        };
+        append_to_last(&mut lists, status, items);
+        return write_atomically(path, &json::save(&lists));
+    }
+    let mut content = Vec::new();
+    if old.last().is_some_and(|&byte| byte != b'\n') {
+        content.push(b'\n');
+    }
+    if markdown::is_markdown(path) {
+        markdown::write_items(&mut content, status, items)?;
+    } else {
+        write_items(&mut content, status, items)?;
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(&content)
+}
+
+// the items at the end of the last list, a new default one when there are none
+fn append_to_last(lists: &mut Vec<List>, status: Status, items: &[Item]) {
+    if lists.is_empty() {
+        lists.push(List::new(DEFAULT_LIST));
+    }
+    lists
+        .last_mut()
+        .unwrap()
+        .items_mut(status)
+        .extend(items.iter().cloned());
+}
+
+// the files to write and their content; a directory holds one list per file,
+// a single file separates its lists with `[name]` headers, a markdown file has them as
+// task lists among its other lines and a JSON file as an array
+fn serialize_state(app: &App, file_path: &str) -> io::Result<Vec<(PathBuf, Vec<u8>)>> {
+    let path = Path::new(file_path);
+    if json::is_json(path) {
+        return Ok(vec![(path.to_path_buf(), json::save(&app.lists))]);
+    }
+    if markdown::is_markdown(path) {
+        // the lines around the task lists are kept as the file has them now
+        let old = fs::read_to_string(path).unwrap_or_default();
+        let content = markdown::save(&old, &app.lists, DEFAULT_LIST)?;
+        return Ok(vec![(path.to_path_buf(), content)]);
+    }
+    if path.is_dir() {
+        let mut files = Vec::new();
+        for list in app.lists.iter() {
+            let mut content = Vec::new();
+            write_list(&mut content, list)?;
+            files.push((path.join(&list.name), content));
+        }
+        return Ok(files);
+    }
+
+    // a database is written from the lists themselves by `write_files`, and stands for them
+    // as a single file would when telling whether they changed and in the diff
+    let mut content = Vec::new();
+    write_lists(&mut content, &app.lists)?;
+    Ok(vec![(path.to_path_buf(), content)])
+}
+
+// what the file at `path` holds as `serialize_state` gives it, `None` when it doesn't exist
+// or can't be read
+fn on_disk(path: &Path) -> Option<Vec<u8>> {
+    if !path.is_file() {
+        return None;
+    }
+    if sqlite::is_sqlite(path) {
+        let mut content = Vec::new();
+        write_lists(&mut content, &sqlite::load(path).ok()?).ok()?;
+        return Some(content);
+    }
+    fs::read(path).ok()
+}
+
+// what the files hold now, as the files `serialize_state` gives for the lists; `None` when
+// one of them can't be read
+fn disk_state(app: &App, file_path: &str) -> Option<Vec<(PathBuf, Vec<u8>)>> {
+    serialize_state(app, file_path)
+        .ok()?
+        .into_iter()
+        .map(|(path, _)| {
+            let content = on_disk(&path)?;
+            Some((path, content))
+        })
+        .collect()
+}
+
+fn write_lists(out: &mut impl Write, lists: &[List]) -> io::Result<()> {
+    for (index, list) in lists.iter().enumerate() {
+        if index > 0 || list.name != DEFAULT_LIST {
+            writeln!(out, "[{}]", list.name)?;
+        }
+        write_list(out, list)?;
+    }
+    Ok(())
+}
+
+// the saves started so far, numbered from 1, and the last of them written
+static SAVES: AtomicU64 = AtomicU64::new(0);
+static WRITTEN: Mutex<u64> = Mutex::new(0);
+
+// the number of a save about to start, see `write_files`
+fn next_save() -> u64 {
+    SAVES.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+// writes the files of the save numbered `save` of `lists`, one save at a time; a save that
+// has to wait for a later one is dropped, the files holding newer lists by then
+fn write_files(
+    save: u64,
+    files: &[(PathBuf, Vec<u8>)],
+    lists: &[List],
+    backups: usize,
+) -> io::Result<()> {
+    let mut written = WRITTEN.lock().unwrap_or_else(|err| err.into_inner());
+    if *written > save {
+        return Ok(());
+    }
+    *written = save;
+    for (path, content) in files {
+        // neither written nor backed up again when unchanged
+        if on_disk(path).as_ref() == Some(content) {
+            continue;
+        }
+        backup::rotate(path, backups)?;
+        if sqlite::is_sqlite(path) {
+            sqlite::save(path, lists).map_err(io::Error::other)?;
+        } else {
+            write_atomically(path, content)?;
+        }
+    }
+    Ok(())
+}
+
+// writes a hidden file next to `path` and renames it over it once it is on disk, so that
+// a crash or a full disk leaves the old content rather than a cut off one; a symlink is
+// followed to the file it points to
+fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    // numbered too, as two threads may write the same file
+    static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+    let temp = dir.join(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(content)?;
+        if let Ok(metadata) = fs::metadata(&path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, &path)?;
+        // the rename itself is only on disk once the directory is
+        File::open(dir)?.sync_all()
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+// writes the files while the user goes on, reporting `done` when they are
+fn save_in_background(
+    tasks: &mut Tasks,
+    files: Vec<(PathBuf, Vec<u8>)>,
+    lists: Vec<List>,
+    backups: usize,
+    done: &'static str,
+) {
+    let save = next_save();
+    tasks.spawn("Saving", move || {
+        write_files(save, &files, &lists, backups)
+            .map(|()| done.to_string())
+            .map_err(|err| format!("ERROR: could not save: {}", err))
+    })
+}
+
+fn save_state(app: &App, file_path: &str, backups: usize) -> io::Result<()> {
+    write_files(
+        next_save(),
+        &serialize_state(app, file_path)?,
+        &app.lists,
+        backups,
+    )
+}
+
+// the error names the file and line as `path:line: ERROR: message`
+fn load_file(
+    lists: &mut Vec<List>,
+    file_path: &Path,
+    headers: bool,
+    columns: &[Column],
+) -> Result<(), String> {
+    let content = fs::read_to_string(file_path)
+        .map_err(|err| format!("{}: ERROR: {}", file_path.display(), err))?;
+    // the tab of the item read last, which notes belong to
+    let mut last = None;
+    for (index, line) in content.lines().enumerate() {
+        if let Some(name) = parse_header(line).filter(|_| headers) {
+            lists.push(List::new(name));
+            last = None;
+            continue;
+        }
+        if let (Some(note), Some(status)) = (line.strip_prefix(NOTE_INDENT), last) {
+            let item = lists
+                .last_mut()
+                .unwrap()
+                .items_mut(status)
+                .last_mut()
+                .unwrap();
+            item.notes.push(note.to_string());
+            continue;
+        }
+        let Some((status, state, title)) = parse_todo(line, columns) else {
+            return Err(format!(
+                "{}:{}: ERROR: item line format incorrectly",
+                file_path.display(),
+                index + 1
+            ));
+        };
+        if lists.is_empty() {
+            lists.push(List::new(DEFAULT_LIST));
+        }
+        let list = lists.last_mut().unwrap();
+        list.items_mut(status).push(Item {
+            state,
+            ..Item::parse(title)
+        });
+        last = Some(status);
+    }
+    Ok(())
+}
+
+// the lists of the file or directory at `file_path`, the default one alone when there is
+// nothing yet
+fn read_lists(file_path: &str, columns: &[Column]) -> Result<Vec<List>, String> {
+    let path = Path::new(file_path);
+    let error = |err: String| format!("{}: ERROR: {}", file_path, err);
+    let mut lists = Vec::new();
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)
+            .map_err(|err| error(err.to_string()))?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|entry| {
+                entry.is_file()
+                    && !entry
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+            })
+            .collect();
+        entries.sort();
+        for entry in entries {
+            let name = entry.file_name().unwrap().to_string_lossy().into_owned();
+            lists.push(List::new(name));
+            load_file(&mut lists, &entry, false, columns)?;
+        }
+    } else if path.exists() && sqlite::is_sqlite(path) {
+        lists = sqlite::load(path).map_err(error)?;
+    } else if path.exists() && json::is_json(path) {
+        let content = fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+        lists = json::load(&content).map_err(error)?;
+    } else if path.exists() && markdown::is_markdown(path) {
+        let content = fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+        lists = markdown::load(&content, columns, DEFAULT_LIST);
+    } else if path.exists() {
+        load_file(&mut lists, path, true, columns)?;
+    }
+
+    if lists.is_empty() {
+        lists.push(List::new(DEFAULT_LIST));
+    }
+    Ok(lists)
+}
+
+fn load_state(app: &mut App, file_path: &str) {
+    app.lists = read_lists(file_path, &app.columns).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+}
+
+fn is_writable(path: &Path) -> bool {
+    // a file that doesn't exist yet can be created in a writable directory
+    if !path.exists() {
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        return fs::metadata(parent)
+            .is_ok_and(|metadata| metadata.is_dir() && !metadata.permissions().readonly());
+    }
+    if path.is_dir() {
+        return fs::metadata(path).is_ok_and(|metadata| !metadata.permissions().readonly());
+    }
+    fs::OpenOptions::new().append(true).open(path).is_ok()
+}
+
+// the item a new file starts with
+const EXAMPLE_ITEM: &str = "Try me: press ENTER to mark me done #welcome";
+
+// the pages of the tour shown when a new file is created
+const TOUR: &[&[&str]] = &[
+    &[
+        "Your todos live in a plain text file, one per line.",
+        "",
+        "j/k move between them, i adds one and e edits it,",
+        "ENTER marks it done, dd deletes it and u undoes.",
+    ],
+    &[
+        "h/l switch between the TODO, DONE, ARCHIVE and TRASH tabs,",
+        "TAB between lists, which are [name] headers in the file.",
+        "",
+        "/ searches and f shows only items with a #tag or +project.",
+    ],
+    &[
+        "w saves, and so does quitting with Q.",
+        "",
+        "? lists every key. Press any key to start.",
+    ],
+];
+
+// offers to create the missing file, starts it with an example item and walks through
+// the main keys; false when the user would rather not
+fn welcome(ui: &mut Ui, app: &mut App, file_path: &str) -> bool {
+    if !ui.confirm(&format!("{} doesn't exist yet, create it?", file_path)) {
+        return false;
+    }
+    app.add(Item::parse(EXAMPLE_ITEM));
+    // a new file has nothing to back up
+    if let Err(err) = save_state(app, file_path, 0) {
+        endwin();
+        eprintln!("{}: ERROR: could not create: {}", file_path, err);
+        process::exit(1);
+    }
+    for (index, page) in TOUR.iter().enumerate() {
+        let title = format!(
+            "Welcome to clitodo ({}/{}), ESC skips",
+            index + 1,
+            TOUR.len()
+        );
+        let lines: Vec<String> = page.iter().map(|line| line.to_string()).collect();
+        loop {
+            erase();
+            ui.overlay(&title, &lines);
+            refresh();
+            match ui.read_key() {
+                ERR => {}
+                KEY_RESIZE => ui.resize(),
+                KEY_ESCAPE => return true,
+                _ => break,
+            }
+        }
+    }
+    true
+}
+
+fn init_screen(config: &Config) {
+    setlocale(LcCategory::all, "");
+    initscr();
+
+    // disable echo and cursor
+    noecho();
+    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+    keypad(stdscr(), true);
+    set_escdelay(25);
+
+    if config.color && has_colors() {
+        start_color();
+        config.theme.apply(&config.column_colors);
+    }
+}
+
+// `clitodo quick <file>`: asks for one todo, adds it to the first list and exits
+fn quick_add(app: &mut App, config: &Config, file_path: &str) {
+    init_screen(config);
+    let mut ui = Ui::default();
+    ui.resize();
+    let line = prompt_item(&mut ui, "Quick add", "");
+    endwin();
+
+    let Some(line) = line.filter(|line| !line.trim().is_empty()) else {
+        return;
+    };
+    app.add(typed_item(&Item::default(), &line));
+    if let Err(err) = save_state(app, file_path, config.backups) {
+        eprintln!("{}: ERROR: could not save: {}", file_path, err);
+        process::exit(1);
+    }
+}
+
+// `clitodo restore <file>`: picks one of the backups of the file and puts it back, the
+// file as it was becoming the newest backup so that the restore can be undone
+fn restore_backup(config: &Config, file_path: &str) {
+    let path = Path::new(file_path);
+    let backups = backup::list(path);
+    if backups.is_empty() {
+        eprintln!("{}: ERROR: no backups to restore", file_path);
+        process::exit(1);
+    }
+    let now = Local::now().naive_local();
+    let options: Vec<String> = backups
+        .iter()
+        .enumerate()
+        .map(|(index, (_, modified))| {
+            let time = DateTime::<Local>::from(*modified).naive_local();
+            format!(
+                "{}  {} ({} ago)",
+                index + 1,
+                config.dates.time(time),
+                age(time, now)
+            )
+        })
+        .collect();
+    let options: Vec<&str> = options.iter().map(String::as_str).collect();
+
+    init_screen(config);
+    let mut ui = Ui::default();
+    ui.resize();
+    let choice = ui.menu("Restore which backup?", &options);
+    endwin();
+
+    let Some(choice) = choice else {
+        return;
+    };
+    let replaced = path.is_file();
+    let backup = &backups[choice].0;
+    let restored = if sqlite::is_sqlite(path) {
+        // written into rather than replaced, which would leave its write-ahead log behind
+        sqlite::load(backup)
+            .map_err(io::Error::other)
+            .and_then(|lists| {
+                backup::rotate(path, config.backups.max(1))?;
+                sqlite::save(path, &lists).map_err(io::Error::other)
+            })
+    } else {
+        fs::read(backup).and_then(|content| {
+            backup::rotate(path, config.backups.max(1))?;
+            write_atomically(path, &content)
+        })
+    };
+    match restored {
+        Ok(()) if replaced => println!(
+            "{}: restored backup {}, the replaced version is backup 1",
+            file_path,
+            choice + 1
+        ),
+        Ok(()) => println!("{}: restored backup {}", file_path, choice + 1),
+        Err(err) => {
+            eprintln!("{}: ERROR: could not restore: {}", file_path, err);
+            process::exit(1);
+        }
+    }
+}
+
+// DONE: undo system
+// DONE: new elements to list(todo) maybe done
+// DONE: persist app state (save)
+// DONE: edit todos
+// DONE: delete items
+// DONE: only show daily todos
+
+fn main() {
+    let mut args = env::args();
+    args.next().unwrap();
+
+    let mut quick = false;
+    let mut restore = false;
+    let mut read_only = false;
+    let mut no_color = false;
+    let mut date_format = None;
+    let mut file_path = None;
+    let mut first = true;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--read-only" => read_only = true,
+            "--no-color" => no_color = true,
+            "--date-format" => match args.next() {
+                Some(value) => date_format = Some(DateFormat::parse(&value)),
+                None => {
+                    eprintln!("ERROR: --date-format needs a preset (eu, iso, us) or a pattern");
+                    process::exit(1);
+                }
+            },
+            "quick" if first => quick = true,
+            "restore" if first => restore = true,
+            _ if arg.starts_with("--") => {
+                eprintln!("ERROR: unknown option {}", arg);
+                process::exit(1);
+            }
+            _ => file_path = Some(arg),
+        }
+        first = false;
+    }
+    let file_path = {
+        match file_path {
+            Some(file_path) => file_path,
+            None => {
+                eprintln!(
+                    "Usage: todo-rs [quick | restore] [--read-only] [--no-color] [--date-format <format>] <file-path>"
+                );
+                eprintln!("ERROR: no filepath provided");
+                process::exit(1);
+            }
+        }
+    };
+    read_only = read_only || !is_writable(Path::new(&file_path));
+    if (quick || restore) && read_only {
+        eprintln!("{}: ERROR: file is read-only", file_path);
+        process::exit(1);
+    }
+
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+
+    if let Some(date_format) = date_format {
+        config.dates = date_format;
+    }
+    // https://no-color.org
+    if no_color || env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        config.color = false;
+    }
+
+    if restore {
+        restore_backup(&config, &file_path);
+        return;
+    }
+
+    let mut quit = false;
+    // quitting without saving
+    let mut discard = false;
+    let mut app = App::default();
+
+    let first_run = !Path::new(&file_path).exists();
+    if first_run && read_only {
+        eprintln!(
+            "{}: ERROR: file doesn't exist and can't be created",
+            file_path
+        );
+        process::exit(1);
+    }
+    app.columns = config.columns.clone();
+    load_state(&mut app, &file_path);
+    app.assign_ids();
+    // the lists as the files hold them, see `Watch`
+    let on_disk = app.lists.clone();
+    app.purge_trash(config.trash_days);
+    app.bump_priorities(config.bump_days);
+    let archived = app.auto_archive(config.archive_days);
+    app.due_time = config.due_time;
+    app.user = config.user.clone();
+
+    if quick {
+        quick_add(&mut app, &config, &file_path);
+        return;
+    }
+    if let Some(session) = Session::load(&file_path) {
+        app.resume(session);
+    }
+
+    init_screen(&config);
+
+    mousemask(
+        (BUTTON1_CLICKED | BUTTON1_DOUBLE_CLICKED | BUTTON4_PRESSED | BUTTON5_PRESSED) as mmask_t,
+        None,
+    );
+
+    // wake up periodically so transient messages can expire
+    timeout(250);
+
+    let mut mode = Mode::Normal;
+    let mut input = LineEdit::default();
+    let mut message = (archived > 0).then(|| {
+        Message::info(format!(
+            "{} done more than {} days ago archived",
+            plural(archived),
+            config.archive_days
+        ))
+    });
+    let mut search_origin = app.position();
+    let mut finder_selected: usize = 0;
+    let mut keys = KeyState::default();
+    let mut split = false;
+    let mut details = false;
+    let mut timestamps = true;
+    let mut numbers = LineNumbers::Off;
+    let mut pomodoro: Option<Pomodoro> = None;
+    let mut tracker: Option<Tracker> = None;
+    let mut toasts: Vec<Toast> = Vec::new();
+    let mut registers = Registers::default();
+    let mut random = Random::seeded();
+    // what the command line offered on TAB
+    let mut completions: Vec<String> = Vec::new();
+    // a keymap command given on the command line, run as if its key was pressed
+    let mut queued: Option<(Command, Option<usize>)> = None;
+    let mut tasks = Tasks::default();
+    // the day due reminders were last given for
+    let mut reminded: Option<NaiveDate> = None;
+    // the item the selected element was scrolled on, scrolling back when it changes
+    let mut shifted = (app.active, app.position());
+    // when items were last checked for becoming due
+    let mut due_checked = Local::now().naive_local();
+
+    let mut ui = Ui::default();
+    ui.scroll_margin = SCROLL_MARGIN;
+    ui.styling = config.styling;
+    ui.zebra = config.zebra;
+    ui.accessible = config.theme.accessible || !config.color || !has_colors();
+    ui.icons = config.icons.clone();
+    ui.dates = config.dates.clone();
+    ui.stale_days = config.stale_days;
+    ui.footer = config.footer;
+    ui.resize();
+
+    if first_run && !welcome(&mut ui, &mut app, &file_path) {
+        endwin();
+        return;
+    }
+    // what the files hold as far as known, unknown after a failed save; what loading changed,
+    // like new ids or archived items, is unsaved
+    let mut saved = disk_state(&app, &file_path);
+    let mut watch = Watch::new(&file_path, if first_run { &app.lists } else { &on_disk });
+    // the lists as of the last loop, the `App::generation` they are of, and when they
+    // changed, and since when they differ from the files, for `autosave_seconds`
+    let mut current = serialize_state(&app, &file_path).ok();
+    let mut serialized = Some(app.generation());
+    // the lines of the diff view while it is open and the `App::generation` they are of
+    let mut shown_diff: Option<(u64, Vec<(String, i16)>)> = None;
+    let mut changed_at = Local::now().naive_local();
+    let mut unsaved_since: Option<NaiveDateTime> = None;
+
+    while !quit {
+        if message.as_ref().is_some_and(Message::expired) {
+            message = None;
+        }
+
+        toasts.retain(|toast| !toast.expired());
+        if shifted != (app.active, app.position()) {
+            shifted = (app.active, app.position());
+            ui.shift = 0;
+        }
+        let now = Local::now().naive_local();
+        let today = now.date();
+        // the reminders of a new day already name the items due on it
+        let newly_due = app.newly_due(due_checked, now);
+        let reminders = app.reminders(due_checked, now);
+        due_checked = now;
+        for (list, item) in newly_due.iter().rev() {
+            if !read_only && matches!(mode, Mode::Normal) {
+                app.bring_to_top(*list, item);
+            }
+            app.flash(*list, item, now + Duration::seconds(FLASH_SECONDS));
+        }
+        if reminded == Some(today) {
+            toasts.extend(
+                newly_due
+                    .iter()
+                    .map(|(_, item)| Toast::new(format!("Now due: {}", item.text))),
+            );
+        }
+        for (_, item) in reminders {
+            let text = format!("Reminder: {}", item.text);
+            if config.notify {
+                if let Err(err) = external::notify(&text) {
+                    message = Some(Message::error(format!("Can't notify: {}", err)));
+                }
+            }
+            toasts.push(Toast::new(text));
+        }
+        if reminded != Some(today) {
+            reminded = Some(today);
+            toasts.extend(due_reminders(&app, now).into_iter().map(Toast::new));
+        }
+
+        match pomodoro.as_mut().and_then(Pomodoro::tick) {
+            Some(Phase::Work) => {
+                let timer = pomodoro.as_ref().unwrap();
+                toasts.push(Toast::new(if app.log_pomodoro(timer.list, &timer.item) {
+                    "Pomodoro done, take a 5 minute break"
+                } else {
+                    "Pomodoro done, but its item was changed or removed"
+                }));
+                beep();
+            }
+            Some(Phase::Break) => {
+                pomodoro = None;
+                toasts.push(Toast::new("Break is over"));
+                beep();
+            }
+            None => {}
+        }
+        let outcomes = tasks.poll();
+        if !outcomes.is_empty() && tasks.is_idle() {
+            watch.saved(outcomes.iter().all(Result::is_ok));
+        }
+        for outcome in outcomes {
+            message = Some(match outcome {
+                Ok(text) => Message::info(text),
+                Err(text) => {
+                    saved = None;
+                    Message::error(text)
+                }
+            });
+        }
+        if matches!(mode, Mode::Normal) && tasks.is_idle() && watch.changed() {
+            message = Some(on_disk_change(
+                &mut ui, &mut app, &file_path, &mut watch, &mut saved,
+            ));
+            // a markdown file is written around what it holds now
+            serialized = None;
+        }
+        if serialized != Some(app.generation()) {
+            serialized = Some(app.generation());
+            let state = serialize_state(&app, &file_path).ok();
+            if state != current {
+                current = state;
+                changed_at = now;
+            }
+        }
+        let dirty = current != saved;
+        unsaved_since = dirty.then(|| unsaved_since.unwrap_or(now));
+        // not while a save is still running, whose outcome decides what is left to save
+        if config.autosave_seconds > 0
+            && !read_only
+            && tasks.is_idle()
+            && unsaved_since.is_some_and(|since| {
+                now - changed_at >= Duration::seconds(config.autosave_seconds)
+                    || now - since >= Duration::seconds(AUTOSAVE_LIMIT_SECONDS)
+            })
+        {
+            if let Some(files) = current.clone() {
+                saved = Some(files.clone());
+                watch.saving(&app.lists);
+                save_in_background(
+                    &mut tasks,
+                    files,
+                    app.lists.clone(),
+                    config.backups,
+                    "Auto-saved.",
+                );
+            }
+            // a failed save is tried again only after as long
+            changed_at = now;
+            unsaved_since = Some(now);
+        }
+        let indicators: Vec<String> =
+            tasks
+                .status()
+                .into_iter()
+                .chain(
+                    ui.macros
+                        .recording()
+                        .map(|register| format!("recording @{}", register)),
+                )
+                .chain(pomodoro.as_ref().map(|timer| {
+                    format!("{} {}", timer.describe(), ellipsize(&timer.item.text, 20))
+                }))
+                .chain(tracker.as_ref().map(|tracker| {
+                    format!(
+                        "{} {}",
+                        tracker.describe(),
+                        ellipsize(&tracker.item.text, 20)
+                    )
+                }))
+                .collect();
+        ui.indicator = (!indicators.is_empty()).then(|| indicators.join("  "));
+
+        let selection = match mode {
+            Mode::Visual(anchor) => Some((anchor, app.current(app.tab))),
+            _ => None,
+        };
+        let formatted_date = ui.dates.date(today);
+
+        erase();
+        ui.begin(0, 0);
+        // nothing but the task is shown in focus mode
+        let focus = matches!(mode, Mode::Focus(_));
+        let top = if app.lists.len() > 1 && !focus {
+            let labels: Vec<String> = app
+                .lists
+                .iter()
+                .enumerate()
+                .map(|(index, list)| format!("{}:{}", index + 1, list.name))
+                .collect();
+            ui.tab_bar(&labels, app.active);
+            1
+        } else {
+            0
+        };
+        let mut width = ui.width.max(0) as usize;
+        // only worked out while shown, like the diff below
+        let tags = match mode {
+            Mode::Tags(_) => app.tag_counts(),
+            _ => Vec::new(),
+        };
+        // the columns left of the list
+        let mut left = 0;
+        if let Mode::Tags(selected) = mode {
+            left = TAGS_WIDTH.min(width / 2);
+            ui.begin_pane(TAGS_PANE, top, 0, left.saturating_sub(1), true);
+            render_tags(&mut ui, &app, &tags, selected);
+            width -= left;
+        }
+        let checked_step = match mode {
+            Mode::Checklist(step) => Some(step),
+            _ => None,
+        };
+        if (details || checked_step.is_some()) && !focus {
+            let detail_width = (width / 3).max(DETAIL_MIN_WIDTH).min(width / 2);
+            width -= detail_width;
+            let lines = detail_lines(&app, &ui.dates);
+            // the notes are the last lines
+            let highlight = app.selected().zip(checked_step).and_then(|(item, step)| {
+                let (index, ..) = item.steps().get(step).copied()?;
+                Some(lines.len() - item.notes.len() + index)
+            });
+            ui.detail_pane(top, left + width, detail_width, &lines, highlight);
+        }
+        let days = match mode {
+            Mode::Agenda(_) => {
+                agenda::agenda(&app, Local::now().date_naive(), AGENDA_DAYS, &ui.dates)
+            }
+            _ => Vec::new(),
+        };
+        let diff = match mode {
+            Mode::Diff(_) => {
+                if shown_diff
+                    .as_ref()
+                    .is_none_or(|(generation, _)| *generation != app.generation())
+                {
+                    shown_diff = Some((app.generation(), diff_lines(&app, &file_path)));
+                }
+                shown_diff.as_ref().unwrap().1.clone()
+            }
+            _ => {
+                shown_diff = None;
+                Vec::new()
+            }
+        };
+        if let Mode::Focus(skipped) = mode {
+            let tasks = app.next_tasks();
+            match tasks.get(skipped) {
+                Some(&index) => {
+                    let item = &app.list(Status::Todo)[index];
+                    let mut detail = vec![format!("{}/{}", skipped + 1, tasks.len())];
+                    detail.extend(
+                        item.priority
+                            .map(|priority| format!("priority {}", priority)),
+                    );
+                    detail.extend(due_label(item, &ui.dates).map(|due| format!("due {}", due)));
+                    if app.lists.len() > 1 {
+                        detail.push(app.lists[app.active].name.clone());
+                    }
+                    ui.zen(&ui.icons.decorate(&item.text), &detail.join("  ·  "));
+                }
+                None => ui.zen("Nothing to do", ""),
+            }
+        } else if let Mode::Diff(current) = mode {
+            ui.begin_pane(DIFF_PANE, top, 0, width, true);
+            ui.label("Unsaved changes", HEADER_PAIR);
+            ui.rule();
+            ui.begin_list(current, diff.len(), 1);
+            for (id, (line, pair)) in diff.iter().enumerate() {
+                ui.list_element(line, id, *pair);
+            }
+            ui.end_list();
+        } else if let Mode::Board(column, row) = mode {
+            render_board(&mut ui, &app, top, width, (column, row));
+        } else if let Mode::Agenda(selected) = mode {
+            let focused = days.iter().any(|day| !day.items.is_empty());
+            ui.begin_pane(AGENDA_PANE, top, 0, width, focused);
+            render_agenda(&mut ui, &app, &days, selected);
+        } else if split && matches!(app.tab, Status::Todo | Status::Done) && !app.unified {
+            // the archive and the trash have no pane of their own in the split view
+            let half = width / 2;
+            for (status, col) in [(Status::Todo, left), (Status::Done, left + half)] {
+                let focused = app.tab == status;
+                ui.begin_pane(pane_of(status), top, col, half.saturating_sub(1), focused);
+                let title = match status {
+                    Status::Todo => format!(
+                        "{} {}: {}",
+                        tab_label(&app, status, focused),
+                        formatted_date,
+                        header_info(&app, read_only, dirty)
+                    ),
+                    _ => tab_label(&app, status, focused),
+                };
+                ui.label(&title, HEADER_PAIR);
+                ui.rule();
+                render_list(
+                    &mut ui,
+                    &app,
+                    &[status],
+                    timestamps,
+                    numbers,
+                    selection.filter(|_| focused),
+                );
+            }
+        } else {
+            let shown = app.shown();
+            ui.begin_pane(pane_of(shown[0]), top, left, width, true);
+            let tabs: String = Status::ALL
+                .iter()
+                .map(|&status| tab_label(&app, status, shown.contains(&status)))
+                .collect();
+            let title = format!(
+                "{} {}: {}",
+                tabs,
+                formatted_date,
+                header_info(&app, read_only, dirty)
+            );
+            ui.label(&title, HEADER_PAIR);
+            ui.rule();
+            render_list(&mut ui, &app, &shown, timestamps, numbers, selection);
+        }
+        ui.footer_bar(footer_hints(&mode));
+        match mode {
+            Mode::Normal => ui.status_bar(
+                "TAB: switch, ENTER: toggle, ?: help, Q: quit",
+                message.as_ref(),
+            ),
+            Mode::Visual(_) => ui.status_bar(
+                "-- VISUAL -- ENTER: toggle, d: delete, t: tag, J/K: move, ESC: cancel",
+                message.as_ref(),
+            ),
+            Mode::Search => ui.command_line("/", &input),
+            Mode::Command => {
+                if !completions.is_empty() {
+                    ui.wildmenu(&completions);
+                }
+                ui.command_line(":", &input);
+            }
+            Mode::Finder => {
+                let items: Vec<String> = app
+                    .fuzzy_matches(input.text())
+                    .into_iter()
+                    .map(|(status, index)| {
+                        let item = &app.list(status)[index];
+                        let checkbox = if is_ticked(status, item) {
+                            &ui.icons.done
+                        } else {
+                            &ui.icons.todo
+                        };
+                        format!("{} {}", checkbox, item.text)
+                    })
+                    .collect();
+                ui.picker("Find", &input, &items, finder_selected);
+            }
+            Mode::Calendar(date) => {
+                let (title, counts, first_weekday) = calendar(&app, date, &ui.dates);
+                let today = Local::now().date_naive();
+                let today = (today.with_day(1) == date.with_day(1)).then(|| today.day() as usize);
+                ui.calendar(&title, &counts, first_weekday, date.day() as usize, today);
+                ui.status_bar(
+                    "-- CALENDAR -- h/j/k/l: move, </>: month, ENTER: filter by day, ESC: close",
+                    message.as_ref(),
+                );
+            }
+            Mode::Diff(_) => ui.status_bar(
+                "-- DIFF -- j/k: move, w: save, ESC: close",
+                message.as_ref(),
+            ),
+            Mode::Agenda(_) => ui.status_bar(
+                "-- AGENDA -- j/k: move, ENTER: go to item, ESC: close",
+                message.as_ref(),
+            ),
+            Mode::Checklist(_) => ui.status_bar(
+                "-- CHECKLIST -- j/k: move, SPACE: check, a: add step, d: delete step, ESC: close",
+                message.as_ref(),
+            ),
+            Mode::Board(..) => ui.status_bar(
+                "-- BOARD -- h/l: column, j/k: move, H/L: move item, ENTER: go to item, ESC: close",
+                message.as_ref(),
+            ),
+            Mode::Tags(_) => ui.status_bar(
+                "-- TAGS -- j/k: move, SPACE: add/remove on item, a: new tag, ENTER: filter, ESC: close",
+                message.as_ref(),
+            ),
+            Mode::Focus(_) => ui.status_bar(
+                "-- FOCUS -- ENTER/x: complete, j/k: next/previous, r: random, u: undo, ESC: leave",
+                message.as_ref(),
+            ),
+            Mode::Stats => {
+                let lines = stats::lines(&app, Local::now().naive_local(), &ui.dates);
+                ui.overlay(
+                    &format!("Statistics: {}", app.lists[app.active].name),
+                    &lines,
+                );
+            }
+            Mode::Help => {
+                let describe = |binding: &keymap::Binding| {
+                    format!("{:<8} {}", binding.label, binding.description)
+                };
+                let mut lines: Vec<String> = BINDINGS.iter().map(describe).collect();
+                lines.push(String::new());
+                lines.push("Visual mode".to_string());
+                lines.extend(VISUAL_BINDINGS.iter().map(describe));
+                ui.overlay("Keybindings", &lines);
+            }
+        }
+        ui.toasts(&mut toasts);
+        ui.end();
+
+        refresh();
+
+        // a command from the command line runs without waiting for a key
+        let key = if queued.is_some() { ERR } else { ui.read_key() };
+        if key == ERR && queued.is_none() {
+            continue;
+        }
+        if key == KEY_RESIZE {
+            ui.resize();
+            clear();
+            continue;
+        }
+        message = None;
+
+        match mode {
+            Mode::Normal if key == KEY_MOUSE => {
+                let mut event = MEVENT {
+                    id: 0,
+                    x: 0,
+                    y: 0,
+                    z: 0,
+                    bstate: 0,
+                };
+                if getmouse(&mut event) == OK {
+                    let bstate = event.bstate as i32;
+                    if bstate & BUTTON4_PRESSED != 0 {
+                        app.up();
+                    } else if bstate & BUTTON5_PRESSED != 0 {
+                        app.down();
+                    } else if let Some((pane, id)) = ui.list_hit(event.y, event.x) {
+                        let statuses = pane_statuses(&app, pane, split);
+                        let (status, index) = match list_rows(&app, &statuses).get(id) {
+                            Some((status, Row::Section(_, first))) => {
+                                app.focus(*status, *first);
+                                app.toggle_section();
+                                continue;
+                            }
+                            Some((status, Row::Item(index))) => (*status, *index),
+                            None => continue,
+                        };
+                        // toggled like with ENTER, asking first about the subtasks
+                        if bstate & BUTTON1_CLICKED != 0 {
+                            app.focus(status, index);
+                            if ui.on_checkbox(pane, event.x) && !read_only {
+                                queued = Some((Command::Transfer, None));
+                            }
+                        } else if bstate & BUTTON1_DOUBLE_CLICKED != 0 {
+                            app.focus(status, index);
+                            if !read_only {
+                                queued = Some((Command::Transfer, None));
+                            }
+                        }
+                    }
+                }
+            }
+            Mode::Normal => match queued.take().or_else(|| keys.feed(BINDINGS, key)) {
+                // a count turns ENTER into a jump, which is fine to do
+                Some((command, count))
+                    if read_only
+                        && command.mutates()
+                        && !(command == Command::Transfer && count.is_some()) =>
+                {
+                    message = Some(Message::error("Read-only: changes are disabled."));
+                }
+                Some((Command::Quit, _)) if dirty && !read_only => {
+                    match ui.menu("Save changes?", QUIT_OPTIONS) {
+                        Some(0) => quit = true,
+                        Some(1) => (quit, discard) = (true, true),
+                        _ => {}
+                    }
+                }
+                Some((Command::Quit | Command::SaveQuit, _)) => quit = true,
+                Some((Command::Record, _)) => {
+                    message = Some(match ui.macros.stop() {
+                        Some((register, 1)) => {
+                            Message::info(format!("Recorded 1 key into @{}", register))
+                        }
+                        Some((register, len)) => {
+                            Message::info(format!("Recorded {} keys into @{}", len, register))
+                        }
+                        None => match read_register(&mut ui) {
+                            Some(register) if macros::is_valid(register) => {
+                                ui.macros.start(register);
+                                Message::info(format!("Recording @{}, q stops", register))
+                            }
+                            Some(register) => {
+                                Message::error(format!("Invalid register: {}", register))
+                            }
+                            None => Message::info("Not recording."),
+                        },
+                    });
+                }
+                Some((Command::Mark, _)) => {
+                    if let Some(name) = read_register(&mut ui) {
+                        message = if !name.is_ascii_lowercase() {
+                            Some(Message::error(format!("Invalid mark: {}", name)))
+                        } else if app.set_mark(name) {
+                            Some(Message::info(format!("Marked '{}", name)))
+                        } else {
+                            None
+                        };
+                    }
+                }
+                Some((Command::JumpToMark, _)) => {
+                    if let Some(name) = read_register(&mut ui) {
+                        if !app.jump_to_mark(name) {
+                            message = Some(Message::error(format!("Mark not set: {}", name)));
+                        }
+                    }
+                }
+                Some((Command::Replay, count)) => {
+                    if let Some(register) = read_register(&mut ui) {
+                        let replayed = if register == '@' || macros::is_valid(register) {
+                            ui.macros.replay(register, count.unwrap_or(1))
+                        } else {
+                            Err(format!("Invalid register: {}", register))
+                        };
+                        message = replayed.err().map(Message::error);
+                    }
+                }
+                Some((Command::Up, count)) => (0..count.unwrap_or(1)).for_each(|_| app.up()),
+                Some((Command::Down, count)) => (0..count.unwrap_or(1)).for_each(|_| app.down()),
+                Some((Command::Top, count)) => app.go_to(count.map_or(0, |n| n - 1)),
+                Some((Command::Bottom, count)) => app.go_to(count.map_or(usize::MAX, |n| n - 1)),
+                Some((Command::MoveUp, count)) => {
+                    (0..count.unwrap_or(1)).for_each(|_| app.reorder(-1))
+                }
+                Some((Command::MoveDown, count)) => {
+                    (0..count.unwrap_or(1)).for_each(|_| app.reorder(1))
+                }
+                Some((Command::Transfer, Some(count))) => app.go_to(count - 1),
+                Some((Command::Transfer, _)) if app.folded_section().is_some() => {
+                    app.toggle_section()
+                }
+                Some((Command::Transfer, _)) => {
+                    let index = app.current(app.tab);
+                    if !app.log_habit() && confirm_subtasks(&mut ui, &app, index, index) {
+                        app.transfer();
+                    }
+                }
+                Some((Command::Toggle, _)) if app.folded_section().is_none() => {
+                    let index = app.current(app.tab);
+                    if !app.log_habit() && confirm_subtasks(&mut ui, &app, index, index) {
+                        app.toggle_in_place();
+                    }
+                }
+                Some((Command::CycleState, _)) => app.cycle_state(),
+                Some((Command::Pin, _)) => app.toggle_pin(),
+                Some((Command::CancelItem, _)) if app.tab == Status::Todo => {
+                    let index = app.current(app.tab);
+                    if app.selected().is_some() && confirm_subtasks(&mut ui, &app, index, index) {
+                        app.cancel_range(index, index);
+                    }
+                }
+                Some((Command::Insert, _)) => {
+                    if let Some(line) = prompt_item(&mut ui, "New todo", "") {
+                        app.add(typed_item(&Item::default(), &line));
+                    }
+                }
+                Some((Command::InsertSubtask, _)) => {
+                    if let Some(line) = prompt_item(&mut ui, "New subtask", "") {
+                        app.add_subtask(typed_item(&Item::default(), &line));
+                    }
+                }
+                Some((command @ (Command::Nest | Command::Unnest), _)) => {
+                    let delta = if command == Command::Nest { 1 } else { -1 };
+                    if !app.nest(delta) && app.selected().is_some() {
+                        message = Some(Message::info(if delta > 0 {
+                            "Nothing above to nest under."
+                        } else {
+                            "Already on the top level."
+                        }));
+                    }
+                }
+                Some((Command::Edit, _)) => {
+                    if let Some(item) = app.selected() {
+                        if let Some(line) = prompt_item(&mut ui, "Edit", &item.text_line()) {
+                            app.edit(typed_item(item, &line));
+                        }
+                    }
+                }
+                Some((Command::MoveTo, _)) => message = move_menu(&mut ui, &mut app, &file_path),
+                Some((Command::Duplicate, _)) => app.duplicate(None),
+                Some((Command::DuplicateEdit, _)) => {
+                    if let Some(item) = app.selected() {
+                        if let Some(line) = prompt_item(&mut ui, "Edit copy", &item.text_line()) {
+                            app.duplicate(Some(typed_item(item, &line)));
+                        }
+                    }
+                }
+                Some((_, _)) if keys.register.is_some_and(|name| !is_register(name)) => {
+                    let name = keys.register.unwrap();
+                    message = Some(Message::error(format!("Invalid register: {}", name)));
+                }
+                Some((Command::Delete, count)) if app.selected().is_some() => {
+                    let (anchor, cursor) = counted(&app, count);
+                    if let Some(items) = confirm_delete(&mut ui, &mut app, anchor, cursor) {
+                        message = Some(store(&mut registers, keys.register, items, true));
+                    }
+                }
+                Some((Command::Visual, _)) if app.selected().is_some() => {
+                    mode = Mode::Visual(app.current(app.tab));
+                }
+                Some((Command::Undo, _)) => {
+                    message = (!app.undo()).then(|| Message::info("Nothing to undo."));
+                }
+                Some((Command::Redo, _)) => {
+                    message = (!app.redo()).then(|| Message::info("Nothing to redo."));
+                }
+                Some((Command::Restore, _)) => app.restore(),
+                Some((Command::Archive, _)) if app.selected().is_some() => {
+                    let index = app.current(app.tab);
+                    app.archive_range(index, index);
+                }
+                Some((Command::ArchiveOld, _)) => {
+                    let days = match config.archive_days {
+                        0 => ARCHIVE_AFTER_DAYS,
+                        days => days,
+                    };
+                    message = Some(Message::info(match app.archive_old(days) {
+                        0 => "Nothing to archive.".to_string(),
+                        count => format!("{} archived", plural(count)),
+                    }));
+                }
+                Some((Command::CompleteAll, _)) => {
+                    let count = app.open_items().len();
+                    if count == 0 {
+                        message = Some(Message::info("Nothing to complete."));
+                    } else if ui.confirm(&format!("Complete {}?", plural(count))) {
+                        app.complete_all();
+                        message = Some(Message::info(format!("{} completed", plural(count))));
+                    }
+                }
+                Some((Command::ArchiveDone, _)) => {
+                    let count = app.list(Status::Done).len();
+                    if count == 0 {
+                        message = Some(Message::info("Nothing to archive."));
+                    } else if ui.confirm(&format!("Archive {}?", plural(count))) {
+                        app.archive_done();
+                        message = Some(Message::info(format!("{} archived", plural(count))));
+                    }
+                }
+                Some((Command::Save, _)) => match serialize_state(&app, &file_path) {
+                    Ok(files) => {
+                        saved = Some(files.clone());
+                        watch.saving(&app.lists);
+                        save_in_background(
+                            &mut tasks,
+                            files,
+                            app.lists.clone(),
+                            config.backups,
+                            "Saved.",
+                        );
+                    }
+                    Err(err) => {
+                        message = Some(Message::error(format!("ERROR: could not save: {}", err)))
+                    }
+                },
+                Some((Command::NextTab, _)) => app.cycle_tab(1),
+                Some((Command::PrevTab, _)) => app.cycle_tab(-1),
+                Some((Command::NextList, _)) if app.lists.len() == 1 => app.tab = app.tab.cycle(1),
+                Some((Command::NextList, Some(count))) => app.switch_list(count - 1),
+                Some((Command::NextList, None)) => app.cycle_list(1),
+                Some((Command::PrevList, _)) => app.cycle_list(-1),
+                Some((command @ (Command::MoveToNextList | Command::MoveToPrevList), _))
+                    if app.lists.len() > 1 && app.selected().is_some() =>
+                {
+                    let delta = if command == Command::MoveToNextList {
+                        1
+                    } else {
+                        -1
+                    };
+                    let list = app.neighbour_list(delta);
+                    app.move_to_list(list);
+                    message = Some(Message::info(format!("Moved to {}", app.lists[list].name)));
+                }
+                Some((Command::Split, _)) => split = !split,
+                Some((Command::Unified, _)) => app.toggle_unified(),
+                Some((Command::HideDone, _)) => {
+                    app.toggle_hide_done();
+                    message = Some(Message::info(if app.hide_done {
+                        "Hiding DONE items in the unified view"
+                    } else {
+                        "Showing DONE items in the unified view"
+                    }));
+                }
+                Some((Command::Expand, _)) => ui.expand = !ui.expand,
+                Some((Command::ScrollLeft, count)) => {
+                    ui.shift = ui.shift.saturating_sub(SHIFT_STEP * count.unwrap_or(1));
+                }
+                Some((Command::ScrollRight, count)) => {
+                    ui.shift += SHIFT_STEP * count.unwrap_or(1);
+                }
+                Some((Command::Sort, _)) => message = sort_menu(&mut ui, &mut app),
+                Some((Command::Details, _)) => details = !details,
+                Some((Command::Filter, _)) => {
+                    let current = app.filter.word.clone().unwrap_or_default();
+                    if let Some(input) = ui.prompt("Filter by #tag, +project or @context", &current)
+                    {
+                        app.set_filter(Filter {
+                            word: Some(filter::parse_word(&input)),
+                            ..app.filter.clone()
+                        });
+                    }
+                }
+                Some((Command::CycleFilter, _)) => {
+                    let words = app.filter_words();
+                    let next = match &app.filter.word {
+                        Some(word) => words.iter().skip_while(|other| *other != word).nth(1),
+                        None => words.first(),
+                    };
+                    app.set_filter(Filter {
+                        word: next.cloned(),
+                        ..app.filter.clone()
+                    });
+                }
+                Some((Command::ClearFilter, _)) => app.set_filter(Filter {
+                    context: app.filter.context.clone(),
+                    ..Filter::default()
+                }),
+                Some((Command::Context, _)) => message = switch_context(&mut ui, &mut app),
+                Some((Command::Mine, _)) => match (&app.filter.assignee, &app.user) {
+                    (None, None) => {
+                        message = Some(Message::error(
+                            "Set `user = <name>` in the config to see your items",
+                        ))
+                    }
+                    (assignee, user) => {
+                        let assignee = if assignee.is_some() {
+                            None
+                        } else {
+                            user.clone()
+                        };
+                        app.set_filter(Filter {
+                            assignee,
+                            ..app.filter.clone()
+                        });
+                    }
+                },
+                Some((Command::Today, _)) => {
+                    app.set_filter(Filter {
+                        today: !app.filter.today,
+                        ..app.filter.clone()
+                    });
+                }
+                Some((Command::Group, _)) => {
+                    app.set_grouping(app.grouping.next());
+                    message = Some(Message::info(format!("Grouped by {}", app.grouping.name())));
+                }
+                Some((Command::Fold, _)) => app.toggle_fold(),
+                Some((Command::Timestamps, _)) => timestamps = !timestamps,
+                Some((Command::LineNumbers, _)) => numbers = numbers.next(),
+                Some((command @ (Command::RaisePriority | Command::LowerPriority), count)) => {
+                    if let Some(item) = app.selected() {
+                        let steps = count.unwrap_or(1) as isize;
+                        let delta = if command == Command::RaisePriority {
+                            steps
+                        } else {
+                            -steps
+                        };
+                        let priority = item::shift_priority(item.priority, delta);
+                        app.edit(Item {
+                            priority,
+                            ..item.clone()
+                        });
+                    }
+                }
+                Some((Command::Snooze, _)) if app.tab == Status::Todo => {
+                    message = snooze_menu(&mut ui, &mut app)
+                }
+                Some((Command::ShowSnoozed, _)) => {
+                    app.set_filter(Filter {
+                        snoozed: !app.filter.snoozed,
+                        ..app.filter.clone()
+                    });
+                }
+                Some((Command::OpenUrl, _)) => message = open_url(&mut ui, &app),
+                Some((command @ (Command::Yank | Command::YankClipboard), count))
+                    if app.selected().is_some() =>
+                {
+                    let (anchor, cursor) = counted(&app, count);
+                    let items = app.items_between(anchor, cursor);
+                    let register = match command {
+                        Command::YankClipboard => Some('+'),
+                        _ => keys.register,
+                    };
+                    message = Some(store(&mut registers, register, items, false));
+                }
+                Some((
+                    command @ (Command::Paste | Command::PasteBefore | Command::PasteClipboard),
+                    count,
+                )) => {
+                    let register = match command {
+                        Command::PasteClipboard => Some('+'),
+                        _ => keys.register,
+                    };
+                    message = Some(match register_items(&registers, register) {
+                        Ok(items) if items.is_empty() => Message::info("Nothing to paste."),
+                        Ok(items) => {
+                            let items: Vec<Item> = (0..count.unwrap_or(1))
+                                .flat_map(|_| items.clone())
+                                .collect();
+                            let what = plural(items.len());
+                            app.paste(items, command != Command::PasteBefore);
+                            Message::info(format!("Pasted {}", what))
+                        }
+                        Err(err) => Message::error(format!("ERROR: could not paste: {}", err)),
+                    });
+                }
+                Some((Command::YankList, _)) => {
+                    let what = plural(app.visible(app.tab).len());
+                    message = Some(copy(&checklist(&app), &what));
+                }
+                Some((Command::Pomodoro, _)) if pomodoro.is_some() => {
+                    pomodoro = None;
+                    message = Some(Message::info("Pomodoro stopped."));
+                }
+                Some((Command::Pomodoro, _)) if app.tab == Status::Todo => {
+                    if let Some(item) = app.selected() {
+                        message = Some(Message::info(format!("Pomodoro started: {}", item.text)));
+                        pomodoro = Some(Pomodoro::start(app.active, item.clone()));
+                    }
+                }
+                Some((Command::Track, _)) => {
+                    let running = tracker.take();
+                    let selected = app.selected().cloned();
+                    if let Some(running) = &running {
+                        message = Some(stop_tracking(&mut app, running));
+                    }
+                    let same = running.as_ref().is_some_and(|running| {
+                        running.list == app.active
+                            && selected
+                                .as_ref()
+                                .is_some_and(|item| item.is_same(&running.item))
+                    });
+                    if let Some(item) = selected.filter(|_| !same) {
+                        message = Some(Message::info(format!("Tracking {}", item.text)));
+                        tracker = Some(Tracker::start(app.active, item));
+                    }
+                }
+                Some((Command::Palette, _)) => {
+                    input.start("");
+                    completions.clear();
+                    mode = Mode::Command;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+                }
+                Some((Command::Search, _)) => {
+                    search_origin = app.position();
+                    input.start("");
+                    ui.search = Some(String::new());
+                    mode = Mode::Search;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+                }
+                Some((command @ (Command::SearchNext | Command::SearchPrev), _)) => {
+                    let forward = command == Command::SearchNext;
+                    if let Some(query) = ui.search.clone() {
+                        if !app.search(&query, app.position(), forward, true) {
+                            message = Some(Message::error(format!("Pattern not found: {}", query)));
+                        }
+                    }
+                }
+                Some((Command::Finder, _)) => {
+                    input.start("");
+                    finder_selected = 0;
+                    mode = Mode::Finder;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+                }
+                Some((Command::Calendar, _)) => {
+                    let date = app.filter.due.unwrap_or_else(|| Local::now().date_naive());
+                    mode = Mode::Calendar(date);
+                }
+                Some((Command::Agenda, _)) => mode = Mode::Agenda(0),
+                Some((Command::Tags, _)) => mode = Mode::Tags(0),
+                Some((Command::Template, _)) => message = add_template(&mut ui, &mut app, None),
+                Some((Command::Board, _)) => {
+                    let id = app.selected().and_then(|item| item.id.clone());
+                    let (column, row) = id
+                        .and_then(|id| board::position(&app, &app.columns, &id))
+                        .unwrap_or((0, 0));
+                    mode = Mode::Board(column, row);
+                }
+                Some((Command::Notes, _)) => message = edit_notes(&mut app),
+                Some((Command::Checklist, _)) if app.selected().is_some() => {
+                    mode = Mode::Checklist(0)
+                }
+                Some((Command::Tag, _)) if app.selected().is_some() => {
+                    if let Some(tag) = ui.prompt("Add or remove #", "") {
+                        message = toggle_tag(&mut app, &tag);
+                    }
+                }
+                Some((Command::Diff, _)) => mode = Mode::Diff(0),
+                Some((Command::Stats, _)) => mode = Mode::Stats,
+                Some((Command::Focus, _)) => mode = Mode::Focus(0),
+                Some((Command::Pick, _)) => match app.pick(&mut random) {
+                    Some(index) => {
+                        app.focus(Status::Todo, index);
+                        message = Some(Message::info("How about this one?"));
+                    }
+                    None => message = Some(Message::info("Nothing to pick from.")),
+                },
+                Some((Command::Help, _)) => mode = Mode::Help,
+                _ => {}
+            },
+            Mode::Visual(anchor) => {
+                let cursor = app.current(app.tab);
+                let (start, end) = (anchor.min(cursor), anchor.max(cursor));
+                match keys.feed(VISUAL_BINDINGS, key) {
+                    Some((command, _)) if read_only && command.mutates() => {
+                        message = Some(Message::error("Read-only: changes are disabled."));
+                    }
+                    Some((_, _)) if keys.register.is_some_and(|name| !is_register(name)) => {
+                        let name = keys.register.unwrap();
+                        message = Some(Message::error(format!("Invalid register: {}", name)));
+                    }
+                    // the selection stays within the tab in the unified view
+                    Some((Command::Up, count)) => {
+                        (0..count.unwrap_or(1)).for_each(|_| app.extend(-1))
+                    }
+                    Some((Command::Down, count)) => {
+                        (0..count.unwrap_or(1)).for_each(|_| app.extend(1))
+                    }
+                    Some((Command::Top, _)) => app.select(app.nth(0)),
+                    Some((Command::Bottom, _)) => app.select(app.last()),
+                    Some((Command::Transfer, _)) => {
+                        if confirm_subtasks(&mut ui, &app, anchor, cursor) {
+                            app.transfer_range(anchor, cursor);
+                            app.select(start);
+                        }
+                        mode = Mode::Normal;
+                    }
+                    Some((Command::CancelItem, _)) => {
+                        if confirm_subtasks(&mut ui, &app, anchor, cursor) {
+                            app.cancel_range(anchor, cursor);
+                            app.select(start);
+                        }
+                        mode = Mode::Normal;
+                    }
+                    Some((Command::Archive, _)) => {
+                        app.archive_range(anchor, cursor);
+                        app.select(start);
+                        mode = Mode::Normal;
+                    }
+                    Some((Command::Delete, _)) => {
+                        if let Some(items) = confirm_delete(&mut ui, &mut app, anchor, cursor) {
+                            message = Some(store(&mut registers, keys.register, items, true));
+                        }
+                        mode = Mode::Normal;
+                    }
+                    Some((Command::Yank, _)) => {
+                        let items = app.items_between(anchor, cursor);
+                        message = Some(store(&mut registers, keys.register, items, false));
+                        app.select(start);
+                        mode = Mode::Normal;
+                    }
+                    Some((Command::Tag, _)) => {
+                        if let Some(tag) = ui.prompt("Tag #", "") {
+                            app.tag_range(anchor, cursor, &tag);
+                            mode = Mode::Normal;
+                        }
+                    }
+                    Some((command @ (Command::MoveUp | Command::MoveDown), _)) => {
+                        let delta = if command == Command::MoveUp { -1 } else { 1 };
+                        app.reorder_range(start, end, delta);
+                        let shift = app.current(app.tab) as isize - cursor as isize;
+                        mode = Mode::Visual(anchor.saturating_add_signed(shift));
+                    }
+                    Some((Command::Cancel, _)) => mode = Mode::Normal,
+                    _ => {}
+                }
+            }
+            Mode::Search => match key {
+                KEY_ESCAPE => {
+                    ui.search = None;
+                    app.focus(search_origin.0, search_origin.1);
+                    mode = Mode::Normal;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                }
+                10 | KEY_ENTER => {
+                    ui.search = input.take();
+                    if let Some(query) = &ui.search {
+                        if !app.search(query, search_origin, true, false) {
+                            message = Some(Message::error(format!("Pattern not found: {}", query)));
+                        }
+                    }
+                    mode = Mode::Normal;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                }
+                _ => {
+                    input.handle_key(key);
+                    if !app.search(input.text(), search_origin, true, false) {
+                        app.focus(search_origin.0, search_origin.1);
+                    }
+                    ui.search = Some(input.text().to_string());
+                }
+            },
+            Mode::Command => match key {
+                KEY_ESCAPE => {
+                    mode = Mode::Normal;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                }
+                10 | KEY_ENTER => {
+                    mode = Mode::Normal;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                    let Some(line) = input.take() else {
+                        continue;
+                    };
+                    match ex::parse(&line, &app) {
+                        Ok(Ex::Run(command, count)) => {
+                            keys.register = None;
+                            queued = Some((command, count));
+                        }
+                        Ok(ex) if read_only && ex.mutates() => {
+                            message = Some(Message::error("Read-only: changes are disabled."));
+                        }
+                        Ok(ex) => message = run_ex(&mut ui, &mut app, ex),
+                        Err(err) => message = Some(Message::error(err)),
+                    }
+                }
+                9 => {
+                    let mut line = input.text().to_string();
+                    completions = ex::complete(&mut line, &app);
+                    input.start(&line);
+                }
+                _ => {
+                    completions.clear();
+                    input.handle_key(key);
+                }
+            },
+            Mode::Finder => match key {
+                KEY_ESCAPE => {
+                    mode = Mode::Normal;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                }
+                10 | KEY_ENTER => {
+                    if let Some(&(status, index)) =
+                        app.fuzzy_matches(input.text()).get(finder_selected)
+                    {
+                        app.focus(status, index);
+                    }
+                    mode = Mode::Normal;
+                    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+                }
+                KEY_UP | KEY_CTRL_P => finder_selected = finder_selected.saturating_sub(1),
+                KEY_DOWN | KEY_CTRL_N => {
+                    let count = app.fuzzy_matches(input.text()).len();
+                    if finder_selected + 1 < count {
+                        finder_selected += 1;
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                    finder_selected = 0;
+                }
+            },
+            Mode::Calendar(date) => {
+                let moved = match (key, key_char(key)) {
+                    (KEY_ESCAPE, _) | (_, Some('c' | 'q')) => {
+                        mode = Mode::Normal;
+                        None
+                    }
+                    (10 | KEY_ENTER, _) => {
+                        app.tab = Status::Todo;
+                        app.set_filter(Filter {
+                            due: Some(date),
+                            ..app.filter.clone()
+                        });
+                        mode = Mode::Normal;
+                        None
+                    }
+                    (KEY_LEFT, _) | (_, Some('h')) => date.pred_opt(),
+                    (KEY_RIGHT, _) | (_, Some('l')) => date.succ_opt(),
+                    (KEY_UP, _) | (_, Some('k')) => date.checked_sub_days(Days::new(7)),
+                    (KEY_DOWN, _) | (_, Some('j')) => date.checked_add_days(Days::new(7)),
+                    (_, Some('<')) => date.checked_sub_months(Months::new(1)),
+                    (_, Some('>')) => date.checked_add_months(Months::new(1)),
+                    _ => None,
+                };
+                if let Some(date) = moved {
+                    mode = Mode::Calendar(date);
+                }
+            }
+            Mode::Diff(current) => match (key, key_char(key)) {
+                (KEY_ESCAPE, _) | (_, Some('q')) => mode = Mode::Normal,
+                (_, Some('w')) if read_only => {
+                    message = Some(Message::error("Read-only: changes are disabled."));
+                }
+                (_, Some('w')) => {
+                    match serialize_state(&app, &file_path) {
+                        Ok(files) => {
+                            saved = Some(files.clone());
+                            watch.saving(&app.lists);
+                            save_in_background(
+                                &mut tasks,
+                                files,
+                                app.lists.clone(),
+                                config.backups,
+                                "Saved.",
+                            );
+                        }
+                        Err(err) => {
+                            message =
+                                Some(Message::error(format!("ERROR: could not save: {}", err)))
+                        }
+                    }
+                    mode = Mode::Normal;
+                }
+                (KEY_UP, _) | (_, Some('k')) => mode = Mode::Diff(current.saturating_sub(1)),
+                (KEY_DOWN, _) | (_, Some('j')) if current + 1 < diff.len() => {
+                    mode = Mode::Diff(current + 1)
+                }
+                (_, Some('G')) => mode = Mode::Diff(diff.len().saturating_sub(1)),
+                (_, Some('g')) => mode = Mode::Diff(0),
+                _ => {}
+            },
+            Mode::Agenda(selected) => {
+                let items: Vec<(usize, usize)> =
+                    days.into_iter().flat_map(|day| day.items).collect();
+                match (key, key_char(key)) {
+                    (KEY_ESCAPE, _) | (_, Some('W' | 'q')) => mode = Mode::Normal,
+                    (10 | KEY_ENTER, _) => {
+                        if let Some(&(list, index)) = items.get(selected) {
+                            app.switch_list(list);
+                            let item = &app.list(Status::Todo)[index];
+                            if !app.filter.matches(item, Local::now().naive_local()) {
+                                app.set_filter(Filter {
+                                    snoozed: item.snoozed.is_some(),
+                                    ..Filter::default()
+                                });
+                            }
+                            app.focus(Status::Todo, index);
+                        }
+                        mode = Mode::Normal;
+                    }
+                    (KEY_UP, _) | (_, Some('k')) => mode = Mode::Agenda(selected.saturating_sub(1)),
+                    (KEY_DOWN, _) | (_, Some('j')) if selected + 1 < items.len() => {
+                        mode = Mode::Agenda(selected + 1)
+                    }
+                    _ => {}
+                }
+            }
+            Mode::Tags(selected) => {
+                let selected = selected.min(tags.len().saturating_sub(1));
+                match (key, key_char(key)) {
+                    (KEY_ESCAPE, _) | (_, Some('q')) => mode = Mode::Normal,
+                    (10 | KEY_ENTER, _) => {
+                        if let Some((tag, _)) = tags.get(selected) {
+                            app.set_filter(Filter {
+                                word: Some(tag.clone()),
+                                ..app.filter.clone()
+                            });
+                        }
+                        mode = Mode::Normal;
+                    }
+                    (KEY_UP, _) | (_, Some('k')) => mode = Mode::Tags(selected.saturating_sub(1)),
+                    (KEY_DOWN, _) | (_, Some('j')) if selected + 1 < tags.len() => {
+                        mode = Mode::Tags(selected + 1)
+                    }
+                    (_, Some(' ' | 'x' | 'a')) if read_only => {
+                        message = Some(Message::error("Read-only: changes are disabled."));
+                    }
+                    (_, Some(' ' | 'x')) => {
+                        if let Some((tag, _)) = tags.get(selected) {
+                            message = toggle_tag(&mut app, tag);
+                        }
+                    }
+                    (_, Some('a')) => {
+                        if let Some(tag) = ui.prompt("New tag #", "") {
+                            message = toggle_tag(&mut app, &tag);
+                            let tag = format!("#{}", tag.trim_start_matches('#').to_lowercase());
+                            if let Some(index) =
+                                app.tag_counts().iter().position(|(other, _)| *other == tag)
+                            {
+                                mode = Mode::Tags(index);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Mode::Board(column, row) => {
+                let last = app.columns.len().saturating_sub(1);
+                let column = column.min(last);
+                let items = app
+                    .columns
+                    .get(column)
+                    .map_or(Vec::new(), |other| board::items(&app, other));
+                let row = row.min(items.len().saturating_sub(1));
+                match (key, key_char(key)) {
+                    (KEY_ESCAPE, _) | (_, Some('q')) => mode = Mode::Normal,
+                    (KEY_LEFT, _) | (_, Some('h')) => {
+                        mode = Mode::Board(column.saturating_sub(1), row)
+                    }
+                    (KEY_RIGHT, _) | (_, Some('l')) => {
+                        mode = Mode::Board((column + 1).min(last), row)
+                    }
+                    (KEY_UP, _) | (_, Some('k')) => {
+                        mode = Mode::Board(column, row.saturating_sub(1))
+                    }
+                    (KEY_DOWN, _) | (_, Some('j')) => mode = Mode::Board(column, row + 1),
+                    (10 | KEY_ENTER, _) => {
+                        if let Some(&index) = items.get(row) {
+                            app.focus(app.columns[column].status(), index);
+                            mode = Mode::Normal;
+                        }
+                    }
+                    (_, Some('H' | 'L')) if read_only => {
+                        message = Some(Message::error("Read-only: changes are disabled."));
+                    }
+                    (_, Some(direction @ ('H' | 'L'))) => {
+                        let target = match direction {
+                            'H' => column.checked_sub(1),
+                            _ => (column < last).then_some(column + 1),
+                        };
+                        if let (Some(&index), Some(target)) = (items.get(row), target) {
+                            let from = app.columns[column].status();
+                            let to = app.columns[target].clone();
+                            app.focus(from, index);
+                            let id = app.selected().and_then(|item| item.id.clone());
+                            if from == to.status() || confirm_subtasks(&mut ui, &app, index, index)
+                            {
+                                app.move_to_column(&to);
+                            }
+                            if let Some((column, row)) =
+                                id.and_then(|id| board::position(&app, &app.columns, &id))
+                            {
+                                mode = Mode::Board(column, row);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Mode::Checklist(step) => {
+                let steps = app.selected().map_or(0, |item| item.steps().len());
+                let step = step.min(steps.saturating_sub(1));
+                match (key, key_char(key)) {
+                    (KEY_ESCAPE, _) | (_, Some('q')) => mode = Mode::Normal,
+                    (KEY_UP, _) | (_, Some('k')) => mode = Mode::Checklist(step.saturating_sub(1)),
+                    (KEY_DOWN, _) | (_, Some('j')) if step + 1 < steps => {
+                        mode = Mode::Checklist(step + 1)
+                    }
+                    (10 | KEY_ENTER, _) | (_, Some(' ' | 'x' | 'a' | 'd')) if read_only => {
+                        message = Some(Message::error("Read-only: changes are disabled."));
+                    }
+                    (10 | KEY_ENTER, _) | (_, Some(' ' | 'x')) => app.toggle_step(step),
+                    (_, Some('a')) => {
+                        if let Some(text) = ui.prompt("New step", "") {
+                            app.add_step(&text);
+                            mode = Mode::Checklist(steps);
+                        }
+                    }
+                    (_, Some('d')) => app.remove_step(step),
+                    _ => {}
+                }
+            }
+            Mode::Focus(skipped) => {
+                let tasks = app.next_tasks();
+                let skipped = skipped.min(tasks.len().saturating_sub(1));
+                match (key, key_char(key)) {
+                    (KEY_ESCAPE, _) | (_, Some('q')) => {
+                        if let Some(&index) = tasks.get(skipped) {
+                            app.focus(Status::Todo, index);
+                        }
+                        mode = Mode::Normal;
+                    }
+                    (10 | KEY_ENTER, _) | (_, Some('x' | ' ')) if read_only => {
+                        message = Some(Message::error("Read-only: changes are disabled."));
+                    }
+                    (10 | KEY_ENTER, _) | (_, Some('x' | ' ')) => {
+                        if let Some(&index) = tasks.get(skipped) {
+                            app.focus(Status::Todo, index);
+                            app.transfer();
+                            let left = app.next_tasks().len();
+                            mode = Mode::Focus(skipped.min(left.saturating_sub(1)));
+                            message = Some(Message::info("Done, on to the next one"));
+                        }
+                    }
+                    (_, Some('u')) => {
+                        message = (!app.undo()).then(|| Message::info("Nothing to undo."));
+                    }
+                    (_, Some('r')) => {
+                        let picked = app.pick(&mut random);
+                        if let Some(position) =
+                            tasks.iter().position(|&index| Some(index) == picked)
+                        {
+                            mode = Mode::Focus(position);
+                        }
+                    }
+                    (KEY_DOWN, _) | (_, Some('j' | 'n')) if skipped + 1 < tasks.len() => {
+                        mode = Mode::Focus(skipped + 1)
+                    }
+                    (KEY_UP, _) | (_, Some('k' | 'p')) => {
+                        mode = Mode::Focus(skipped.saturating_sub(1))
+                    }
+                    _ => {}
+                }
+            }
+            Mode::Stats | Mode::Help => mode = Mode::Normal,
+        }
+    }
+    // a save still running would otherwise race the final one
+    let outcomes = tasks.wait();
+    if !outcomes.is_empty() {
+        watch.saved(outcomes.iter().all(Result::is_ok));
+    }
+    // what another program wrote since the last check isn't written over unasked
+    if !read_only && !discard && watch.changed() {
+        on_disk_change(&mut ui, &mut app, &file_path, &mut watch, &mut saved);
+    }
+    endwin();
+
+    if let Err(err) = app.session().save(&file_path) {
+        eprintln!("{}: ERROR: could not save the session: {}", file_path, err);
+    }
+    if read_only || discard {
+        return;
+    }
+    if let Some(running) = tracker {
+        stop_tracking(&mut app, &running);
+    }
+    for err in outcomes.into_iter().filter_map(Result::err) {
+        eprintln!("{}", err);
+        saved = None;
+    }
+    let state = serialize_state(&app, &file_path);
+    if state.as_ref().ok() == saved.as_ref() {
+        return;
+    }
+    let written =
+        state.and_then(|files| write_files(next_save(), &files, &app.lists, config.backups));
+    if let Err(err) = written {
+        eprintln!("{}: ERROR: could not save: {}", file_path, err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::TempDir;
+
+    #[test]
+    fn assigned_ids_are_saved() {
+        let dir = TempDir::new("ids");
+        let path = dir.path().join("todo.txt");
+        fs::write(&path, "TODO: first\nTODO: second\nDONE: third\n").unwrap();
+        let file_path = path.to_string_lossy();
+        let mut app = App::default();
+        load_state(&mut app, &file_path);
+        app.assign_ids();
+        let ids = |lists: &[List]| -> Vec<Option<String>> {
+            lists
+                .iter()
+                .flat_map(|list| list.todos.iter().chain(&list.dones))
+                .map(|item| item.id.clone())
+                .collect()
+        };
+        assert!(ids(&app.lists).iter().all(Option::is_some));
+        // nothing else changed, the files still differ by the ids
+        assert_ne!(
+            disk_state(&app, &file_path),
+            serialize_state(&app, &file_path).ok()
+        );
+        let files = serialize_state(&app, &file_path).unwrap();
+        write_files(next_save(), &files, &app.lists, 0).unwrap();
+        let reloaded = read_lists(&file_path, &app.columns).unwrap();
+        assert_eq!(ids(&reloaded), ids(&app.lists));
+    }
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn write_atomically_renames_the_written_file_over_the_old_one() {
+        let dir = TempDir::new("atomic");
+        let path = dir.path().join("todo.txt");
+        fs::write(&path, "TODO: old\n").unwrap();
+        write_atomically(&path, b"TODO: new\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "TODO: new\n");
+        // no hidden file left behind
+        assert_eq!(entries(dir.path()), ["todo.txt"]);
+    }
+
+    #[test]
+    fn write_atomically_keeps_the_old_content_when_it_fails() {
+        let dir = TempDir::new("atomic");
+        // a directory can't be renamed over, so the written file never takes its place
+        let path = dir.path().join("todo.txt");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("old.txt"), "TODO: old\n").unwrap();
+        assert!(write_atomically(&path, b"TODO: new\n").is_err());
+        assert_eq!(
+            fs::read_to_string(path.join("old.txt")).unwrap(),
+            "TODO: old\n"
+        );
+        assert_eq!(entries(dir.path()), ["todo.txt"]);
+    }
 }
@@ -0,0 +1,493 @@
+use crate::app::{List, Status};
+use crate::board::Column;
+use crate::item::{Item, State};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use std::io::{self, Write};
+use std::path::Path;
+
+// spaces of indentation per level of subtasks, and in front of notes under their task
+const INDENT: usize = 2;
+
+// where a task was read from: the index of its line in its task list, that line with the
+// notes and blank lines under it, and the task as `item_lines` writes it, which while it
+// stays the same has the lines written back as they were
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    line: usize,
+    lines: Vec<String>,
+    written: String,
+}
+
+// a part of a markdown file, which is kept as it is unless it's a task list
+enum Block<'a> {
+    Text(&'a str),
+    // a line of a ``` or ~~~ code block, fences included, where nothing is a task or heading
+    Code(&'a str),
+    // the lines of a task list, named by `names`
+    Tasks(Vec<&'a str>),
+}
+
+// `.md` and `.markdown` files hold GitHub-style task lists, `- [ ] todo` and `- [x] done`;
+// they have no place for the trash, so that deleted tasks are gone from them once saved
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown")
+        })
+}
+
+// the indentation of a task line, whether it is ticked and its text
+fn task(line: &str) -> Option<(usize, bool, &str)> {
+    let text = line.trim_start_matches(' ');
+    let rest = text.strip_prefix(['-', '*', '+'])?.strip_prefix(" [")?;
+    let ticked = match rest.chars().next()? {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    let title = rest[1..].strip_prefix(']')?;
+    if !title.is_empty() && !title.starts_with(' ') {
+        return None;
+    }
+    Some((line.len() - text.len(), ticked, title.trim_start()))
+}
+
+// the ``` or ~~~ a line opening or closing a code block starts with
+fn fence(line: &str) -> Option<&'static str> {
+    let line = line.trim_start_matches(' ');
+    ["```", "~~~"]
+        .into_iter()
+        .find(|fence| line.starts_with(fence))
+}
+
+// the file cut into task lists and the lines around them; the lines of a list are its
+// tasks with the more indented lines under them, blank lines between its tasks included
+fn blocks(content: &str) -> Vec<Block<'_>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut blocks = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        if let Some(open) = fence(lines[index]) {
+            // up to the closing fence, or the end of the file when there is none
+            let end = lines[index + 1..]
+                .iter()
+                .position(|line| fence(line) == Some(open))
+                .map_or(lines.len(), |offset| index + offset + 2);
+            blocks.extend(lines[index..end].iter().map(|line| Block::Code(line)));
+            index = end;
+            continue;
+        }
+        let Some((indent, _, _)) = task(lines[index]) else {
+            blocks.push(Block::Text(lines[index]));
+            index += 1;
+            continue;
+        };
+        let mut end = index + 1;
+        let mut task_end = end;
+        while end < lines.len() {
+            let line = lines[end];
+            if task(line).is_some_and(|(other, _, _)| other >= indent) {
+                task_end = end + 1;
+            } else if fence(line).is_some() {
+                break;
+            } else if line.trim().is_empty() {
+                // a blank line only belongs to the list when it goes on after it
+            } else if line.len() - line.trim_start().len() <= indent || end > task_end {
+                break;
+            } else {
+                task_end = end + 1;
+            }
+            end += 1;
+        }
+        blocks.push(Block::Tasks(lines[index..task_end].to_vec()));
+        index = task_end;
+    }
+    blocks
+}
+
+// the name of each task list: the heading above it, `default` for one without, numbered
+// when several lists have the same heading; `#tag` lines aren't headings
+fn names(blocks: &[Block], default: &str) -> Vec<String> {
+    let mut heading = default.to_string();
+    // the heading of each list so far
+    let mut headings: Vec<String> = Vec::new();
+    let mut names = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Text(line) if line.starts_with('#') => {
+                let title = line.trim_start_matches('#');
+                if title.starts_with(' ') && !title.trim().is_empty() {
+                    heading = title.trim().to_string();
+                }
+            }
+            Block::Text(_) | Block::Code(_) => {}
+            Block::Tasks(_) => {
+                let count = headings.iter().filter(|other| **other == heading).count();
+                names.push(match count {
+                    0 => heading.clone(),
+                    count => format!("{} ({})", heading, count + 1),
+                });
+                headings.push(heading.clone());
+            }
+        }
+    }
+    names
+}
+
+// written after the text of a completed task with the day it was completed, like the Tasks
+// plugin of Obsidian does
+const DONE_SIGN: &str = "✅";
+
+// the title without the day it was completed, if it has one
+fn completed(title: &str) -> (&str, Option<NaiveDateTime>) {
+    let Some((text, date)) = title.rsplit_once(&format!(" {} ", DONE_SIGN)) else {
+        return (title, None);
+    };
+    match NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+        Ok(date) => (text, date.and_hms_opt(0, 0, 0)),
+        Err(_) => (title, None),
+    }
+}
+
+// a state written in front of the text like `- [ ] WAITING: reply`, the custom ones only
+// when they are columns of the board so that a capital word can start a task
+fn parse_state<'a>(title: &'a str, status: Status, columns: &[Column]) -> Option<(State, &'a str)> {
+    let (name, text) = title.split_once(": ")?;
+    let state = State::parse(name)?;
+    let known = !matches!(state, State::Custom(_))
+        || columns.iter().any(|column| column.state() == Some(&state));
+    (known && state.fits(status)).then_some((state, text))
+}
+
+// the task lists of the file, `[ ]` tasks in TODO and `[x]` ones in DONE with their subtasks,
+// which keep their own tick as whether they are completed; lines indented under a task are
+// its notes. a task ticked without the day it was completed has none, as the file doesn't
+// tell how old it is, except for a subtask, which needs one for its tick and gets that of
+// its task or else today's
+pub fn load(content: &str, columns: &[Column], default: &str) -> Vec<List> {
+    let blocks = blocks(content);
+    let names = names(&blocks, default);
+    let mut lists = Vec::new();
+    for (lines, name) in blocks
+        .iter()
+        .filter_map(|block| match block {
+            Block::Tasks(lines) => Some(lines),
+            Block::Text(_) | Block::Code(_) => None,
+        })
+        .zip(names)
+    {
+        let mut list = List::new(name);
+        let base = task(lines[0]).map_or(0, |(indent, _, _)| indent);
+        let now = Local::now().naive_local();
+        let mut last = None;
+        // when the task the lines are under was completed
+        let mut root = None;
+        for (index, line) in lines.iter().enumerate() {
+            let Some((indent, ticked, title)) = task(line) else {
+                if let Some(status) = last {
+                    let item: &mut Item = list.items_mut(status).last_mut().unwrap();
+                    if !line.trim().is_empty() {
+                        item.notes.push(line.trim().to_string());
+                    }
+                    if let Some(source) = item.source.as_mut() {
+                        source.lines.push(line.to_string());
+                    }
+                }
+                continue;
+            };
+            let depth = indent.saturating_sub(base) / INDENT;
+            let status = match last {
+                Some(status) if depth > 0 => status,
+                _ if ticked => Status::Done,
+                _ => Status::Todo,
+            };
+            let (state, title) = match parse_state(title, status, columns) {
+                Some((state, title)) => (Some(state), title),
+                None => (None, title),
+            };
+            let (title, completed) = match completed(title) {
+                _ if !ticked => (title, None),
+                (title, Some(date)) => (title, Some(date)),
+                (title, None) if depth == 0 => (title, None),
+                (title, None) => (title, Some(root.unwrap_or(now))),
+            };
+            if depth == 0 {
+                root = completed;
+            }
+            list.items_mut(status).push(Item {
+                state,
+                completed,
+                depth,
+                source: Some(Source {
+                    line: index,
+                    lines: vec![line.to_string()],
+                    written: String::new(),
+                }),
+                ..Item::parse(title)
+            });
+            last = Some(status);
+        }
+        for status in Status::ALL {
+            for item in list.items_mut(status).iter_mut() {
+                let written = item_lines(status, item);
+                item.source.as_mut().unwrap().written = written;
+            }
+        }
+        lists.push(list);
+    }
+    lists
+}
+
+// the lines of the item as a task, with its notes under it; ids, timestamps and other
+// bookkeeping are left out to keep the file readable but for the day it was completed, and
+// archived items are written as done
+fn item_lines(status: Status, item: &Item) -> String {
+    let ticked = item.completed.is_some() || (status != Status::Todo && item.depth == 0);
+    let mark = if ticked { 'x' } else { ' ' };
+    let indent = " ".repeat(item.depth * INDENT);
+    let state = item
+        .state
+        .as_ref()
+        .map_or(String::new(), |state| format!("{}: ", state.name()));
+    let done = item.completed.map_or(String::new(), |completed| {
+        format!(" {} {}", DONE_SIGN, completed.format("%Y-%m-%d"))
+    });
+    let mut lines = format!(
+        "{}- [{}] {}{}{}\n",
+        indent,
+        mark,
+        state,
+        item.text_line(),
+        done
+    );
+    for note in item.notes.iter() {
+        lines.push_str(&format!("{}{}{}\n", indent, " ".repeat(INDENT), note));
+    }
+    lines
+}
+
+// the lines of the items as a task list, trashed ones dropped
+pub fn write_items(out: &mut impl Write, status: Status, items: &[Item]) -> io::Result<()> {
+    if status == Status::Trashed {
+        return Ok(());
+    }
+    for item in items {
+        write!(out, "{}", item_lines(status, item))?;
+    }
+    Ok(())
+}
+
+// the item as it was in the file while it is unchanged, and otherwise written again with
+// the bullet and, at the same depth, the indentation it had
+fn write_item(out: &mut impl Write, status: Status, item: &Item) -> io::Result<()> {
+    let lines = item_lines(status, item);
+    let Some(source) = &item.source else {
+        return write!(out, "{}", lines);
+    };
+    if lines == source.written {
+        for line in source.lines.iter() {
+            writeln!(out, "{}", line)?;
+        }
+        return Ok(());
+    }
+    let indent = |lines: &str| lines.len() - lines.trim_start_matches(' ').len();
+    let old = &source.lines[0];
+    let bullet = old.trim_start_matches(' ').chars().next().unwrap();
+    // past the indentation and the `-`
+    let rest = &lines[indent(&lines) + 1..];
+    let indent = if indent(&lines) == indent(&source.written) {
+        indent(old)
+    } else {
+        indent(&lines)
+    };
+    let lines = format!("{}{}{}", " ".repeat(indent), bullet, rest);
+    write!(out, "{}", lines)
+}
+
+// the items of the list in the order of the file, each task with its subtasks: those read
+// from it where they were, and the others after the task above them in their tab or, when
+// they lead their tab, before its first task read from the file; trashed items are dropped
+fn write_list(out: &mut impl Write, list: &List) -> io::Result<()> {
+    let mut tasks: Vec<((usize, usize), Status, &[Item])> = Vec::new();
+    for status in [Status::Todo, Status::Done, Status::Archived] {
+        let items = list.items(status);
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        while start < items.len() {
+            let end = items[start + 1..]
+                .iter()
+                .position(|item| item.depth <= items[start].depth)
+                .map_or(items.len(), |offset| start + 1 + offset);
+            ranges.push(start..end);
+            start = end;
+        }
+        let first = ranges
+            .iter()
+            .find_map(|range| items[range.start].source.as_ref())
+            .map_or(usize::MAX, |source| source.line);
+        let mut place = (first, 0);
+        for range in ranges {
+            place = match &items[range.start].source {
+                Some(source) => (source.line, 1),
+                None if place.1 == 0 => place,
+                None => (place.0, place.1 + 1),
+            };
+            tasks.push((place, status, &items[range]));
+        }
+    }
+    tasks.sort_by_key(|(place, _, _)| *place);
+    for (_, status, items) in tasks {
+        for item in items {
+            write_item(out, status, item)?;
+        }
+    }
+    Ok(())
+}
+
+// `old`, the file as it is now, with its task lists swapped for the lists of the same name;
+// lists with items it doesn't have yet go at the end under a heading of their own, and task
+// lists whose list is gone are left out
+pub fn save(old: &str, lists: &[List], default: &str) -> io::Result<Vec<u8>> {
+    let blocks = blocks(old);
+    let mut names = names(&blocks, default).into_iter();
+    let mut written = Vec::new();
+    let mut out = Vec::new();
+    for block in &blocks {
+        match block {
+            Block::Text(line) | Block::Code(line) => writeln!(out, "{}", line)?,
+            Block::Tasks(_) => {
+                let name = names.next().unwrap();
+                if let Some(list) = lists.iter().find(|list| list.name == name) {
+                    write_list(&mut out, list)?;
+                    written.push(name);
+                }
+            }
+        }
+    }
+    for list in lists.iter().filter(|list| !written.contains(&list.name)) {
+        if Status::ALL
+            .into_iter()
+            .all(|status| status == Status::Trashed || list.items(status).is_empty())
+        {
+            continue;
+        }
+        // a file of nothing but the default list needs no heading
+        if !out.is_empty() || list.name != default {
+            if !out.is_empty() {
+                writeln!(out)?;
+            }
+            writeln!(out, "## {}", list.name)?;
+            writeln!(out)?;
+        }
+        write_list(&mut out, list)?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "# Week
+
+Some notes about the week.
+
+* [x] send the report ✅ 2025-06-30
+* [ ] call the plumber (A)
+    + [x] find the number ✅ 2025-06-30
+      it's on the fridge
+
+* [ ] WAITING: reply from Sam
+- [x] old task
+
+## Shopping
+
+- [ ] milk
+";
+
+    fn saved(lists: &[List]) -> String {
+        String::from_utf8(save(FILE, lists, "main").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn save_keeps_an_unchanged_file() {
+        let lists = load(FILE, &[], "main");
+        assert_eq!(saved(&lists), FILE);
+    }
+
+    #[test]
+    fn load_reads_the_day_a_task_was_completed() {
+        let lists = load(FILE, &[], "main");
+        let done = lists[0].items(Status::Done);
+        assert_eq!(done[0].text, "send the report");
+        assert_eq!(
+            done[0].completed.map(|completed| completed.date()),
+            NaiveDate::from_ymd_opt(2025, 6, 30)
+        );
+        assert_eq!(done[1].text, "old task");
+        assert_eq!(done[1].completed, None);
+    }
+
+    #[test]
+    fn save_writes_a_changed_task_where_it_was() {
+        let mut lists = load(FILE, &[], "main");
+        // completed with its subtask
+        let task: Vec<Item> = lists[0].todos.drain(..2).collect();
+        let completed = NaiveDate::from_ymd_opt(2025, 7, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0);
+        lists[0].dones.push(Item {
+            completed,
+            ..task[0].clone()
+        });
+        lists[0].dones.push(task[1].clone());
+        lists[0].todos.push(Item::parse("new task"));
+        assert_eq!(
+            saved(&lists),
+            FILE.replace(
+                "* [ ] call the plumber (A)",
+                "* [x] call the plumber (A) ✅ 2025-07-01"
+            )
+            .replace(
+                "* [ ] WAITING: reply from Sam\n",
+                "* [ ] WAITING: reply from Sam\n- [ ] new task\n"
+            )
+        );
+    }
+
+    #[test]
+    fn save_adds_new_lists_under_a_heading() {
+        let mut lists = load(FILE, &[], "main");
+        let mut errands = List::new("Errands");
+        errands.todos.push(Item::parse("post office"));
+        lists.push(errands);
+        assert_eq!(
+            saved(&lists),
+            format!("{}\n## Errands\n\n- [ ] post office\n", FILE)
+        );
+    }
+
+    #[test]
+    fn load_skips_code_blocks_and_tag_lines() {
+        let file = "# Home\n\n#errands\n- [ ] buy milk\n\n```\n# not a heading\n- [ ] not a task\n```\n\n## Work\n\n~~~md\n- [x] an example\n~~~\n- [ ] send the report\n";
+        let lists = load(file, &[], "main");
+        let names: Vec<&str> = lists.iter().map(|list| list.name.as_str()).collect();
+        assert_eq!(names, ["Home", "Work"]);
+        assert_eq!(lists[0].todos.len(), 1);
+        assert_eq!(lists[1].todos[0].text, "send the report");
+        assert!(lists[1].dones.is_empty());
+        assert_eq!(
+            String::from_utf8(save(file, &lists, "main").unwrap()).unwrap(),
+            file
+        );
+    }
+
+    #[test]
+    fn save_drops_trashed_tasks() {
+        let mut lists = load(FILE, &[], "main");
+        let task = lists[0].dones.remove(1);
+        lists[0].trashed.push(task);
+        assert_eq!(saved(&lists), FILE.replace("- [x] old task\n", ""));
+    }
+}
@@ -0,0 +1,55 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Bold,
+    Underline,
+    Code,
+}
+
+fn style_of(delimiter: char) -> Option<Style> {
+    match delimiter {
+        '*' => Some(Style::Bold),
+        '_' => Some(Style::Underline),
+        '`' => Some(Style::Code),
+        _ => None,
+    }
+}
+
+// strips `*bold*`, `_underline_` and `code` delimiters from the text and returns it with the
+// styled byte ranges; a span opens at the start of a word and closes at the end of one,
+// so `snake_case` and a lone `*` stay as they are
+pub fn parse(text: &str) -> (String, Vec<(usize, usize, Style)>) {
+    let mut plain = String::with_capacity(text.len());
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        let at_word_start = plain.chars().last().is_none_or(char::is_whitespace);
+        let inner = &rest[ch.len_utf8()..];
+        let closing = style_of(ch)
+            .filter(|_| at_word_start)
+            .filter(|_| inner.starts_with(|next: char| !next.is_whitespace()))
+            .and_then(|style| {
+                let end = inner.char_indices().skip(1).find(|&(index, other)| {
+                    other == ch
+                        && !inner[..index].ends_with(char::is_whitespace)
+                        && inner[index + 1..]
+                            .chars()
+                            .next()
+                            .is_none_or(|next| !next.is_alphanumeric())
+                })?;
+                Some((style, end.0))
+            });
+        match closing {
+            Some((style, end)) => {
+                let start = plain.len();
+                plain.push_str(&inner[..end]);
+                spans.push((start, plain.len(), style));
+                rest = &inner[end + 1..];
+            }
+            None => {
+                plain.push(ch);
+                rest = inner;
+            }
+        }
+    }
+    (plain, spans)
+}
@@ -0,0 +1,263 @@
+use crate::app::{List, Status};
+use crate::item::Item;
+use std::collections::{HashMap, HashSet};
+
+// where an item is and the id of the item above it in its tab
+#[derive(Clone)]
+struct Entry {
+    list: String,
+    status: Status,
+    item: Item,
+    after: Option<String>,
+}
+
+// the items as they are saved, without what is only kept while the program runs
+fn stored(item: &Item) -> Item {
+    Item {
+        folded: false,
+        mark: None,
+        flash: None,
+        source: None,
+        ..item.clone()
+    }
+}
+
+fn same(entry: &Entry, other: &Entry) -> bool {
+    entry.list == other.list
+        && entry.status == other.status
+        && stored(&entry.item) == stored(&other.item)
+}
+
+// the items with an id in list order
+fn entries(lists: &[List]) -> Vec<(String, Entry)> {
+    let mut entries = Vec::new();
+    for list in lists {
+        for status in Status::ALL {
+            let items = list.items(status);
+            for (index, item) in items.iter().enumerate() {
+                let Some(id) = &item.id else {
+                    continue;
+                };
+                let after = index
+                    .checked_sub(1)
+                    .and_then(|above| items[above].id.clone());
+                entries.push((
+                    id.clone(),
+                    Entry {
+                        list: list.name.clone(),
+                        status,
+                        item: item.clone(),
+                        after,
+                    },
+                ));
+            }
+        }
+    }
+    entries
+}
+
+// the list, tab and index of the item with the id
+fn find(lists: &[List], id: &str) -> Option<(usize, Status, usize)> {
+    lists.iter().enumerate().find_map(|(list, items)| {
+        Status::ALL.into_iter().find_map(|status| {
+            let index = items
+                .items(status)
+                .iter()
+                .position(|item| item.id.as_deref() == Some(id))?;
+            Some((list, status, index))
+        })
+    })
+}
+
+// puts the item where the entry says, below the item it was under when that is in the
+// same tab and at the end otherwise
+fn insert(lists: &mut Vec<List>, entry: Entry) {
+    let list = match lists.iter().position(|list| list.name == entry.list) {
+        Some(list) => list,
+        None => {
+            lists.push(List::new(entry.list.clone()));
+            lists.len() - 1
+        }
+    };
+    let items = lists[list].items_mut(entry.status);
+    let index = match &entry.after {
+        None => 0,
+        Some(after) => items
+            .iter()
+            .position(|item| item.id.as_ref() == Some(after))
+            .map_or(items.len(), |above| above + 1),
+    };
+    items.insert(index, entry.item);
+}
+
+fn remove(lists: &mut [List], id: &str) {
+    if let Some((list, status, index)) = find(lists, id) {
+        lists[list].items_mut(status).remove(index);
+    }
+}
+
+// the `local` lists with what changed in the files since they held `base`, now `remote`,
+// items being told apart by their ids; an item changed on both sides keeps its local
+// version, and an item changed on one side and deleted on the other is kept, both counted
+// as conflicts
+pub fn merge(base: &[List], local: &[List], remote: &[List]) -> (Vec<List>, usize) {
+    let base: HashMap<String, Entry> = entries(base).into_iter().collect();
+    let theirs = entries(remote);
+    let remote: HashMap<String, Entry> = theirs.iter().cloned().collect();
+    let mut merged = local.to_vec();
+    let mut conflicts = 0;
+    for (id, mine) in entries(local) {
+        match (base.get(&id), remote.get(&id)) {
+            // added here
+            (None, _) => {}
+            (Some(old), None) if same(&mine, old) => remove(&mut merged, &id),
+            (Some(_), None) => conflicts += 1,
+            (Some(old), Some(new)) if same(new, old) => {}
+            (Some(old), Some(new)) if same(&mine, old) => {
+                let (list, status, index) = find(&merged, &id).unwrap();
+                if mine.list == new.list && mine.status == new.status {
+                    merged[list].items_mut(status)[index] = new.item.clone();
+                } else {
+                    merged[list].items_mut(status).remove(index);
+                    insert(&mut merged, new.clone());
+                }
+            }
+            (Some(_), Some(new)) if same(&mine, new) => {}
+            (Some(_), Some(_)) => conflicts += 1,
+        }
+    }
+    let kept: HashSet<String> = entries(&merged).into_iter().map(|(id, _)| id).collect();
+    for (id, new) in theirs {
+        if kept.contains(&id) {
+            continue;
+        }
+        match base.get(&id) {
+            // deleted here
+            Some(old) if same(&new, old) => {}
+            Some(_) => {
+                conflicts += 1;
+                insert(&mut merged, new);
+            }
+            None => insert(&mut merged, new),
+        }
+    }
+    (merged, conflicts)
+}
+
+// gives the items of `lists` without an id the id of the same item in `base`, so that the
+// items of a file written by hand are told apart the same way each time it is read; items
+// are the same when what the user typed is, as a markdown file keeps no timestamps
+pub fn adopt_ids(lists: &mut [List], base: &[List]) {
+    let key = |list: &str, status: Status, item: &Item| {
+        format!(
+            "{}\n{:?}\n{:?}\n{}\n{}\n{:?}\n{}",
+            list,
+            status,
+            item.state,
+            item.depth,
+            item.text_line(),
+            item.notes,
+            item.completed.is_some()
+        )
+    };
+    let taken: HashSet<String> = entries(lists).into_iter().map(|(id, _)| id).collect();
+    let mut ids: HashMap<String, Vec<String>> = HashMap::new();
+    for (id, entry) in entries(base).into_iter().rev() {
+        if !taken.contains(&id) {
+            ids.entry(key(&entry.list, entry.status, &entry.item))
+                .or_default()
+                .push(id);
+        }
+    }
+    for list in lists.iter_mut() {
+        let name = list.name.clone();
+        for status in Status::ALL {
+            for item in list.items_mut(status).iter_mut() {
+                if item.id.is_none() {
+                    item.id = ids
+                        .get_mut(&key(&name, status, item))
+                        .and_then(|ids| ids.pop());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists(todos: &[&str]) -> Vec<List> {
+        let mut list = List::new("main");
+        list.todos = todos.iter().map(|line| Item::parse(line)).collect();
+        vec![list]
+    }
+
+    fn texts(lists: &[List], status: Status) -> Vec<&str> {
+        lists[0]
+            .items(status)
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect()
+    }
+
+    const BASE: &[&str] = &["a id:a", "b id:b", "c id:c"];
+
+    #[test]
+    fn merge_takes_the_changes_of_either_side() {
+        let local = lists(&["a id:a", "b mine id:b", "c id:c"]);
+        let remote = lists(&["a theirs id:a", "b id:b", "c id:c"]);
+        let (merged, conflicts) = merge(&lists(BASE), &local, &remote);
+        assert_eq!(texts(&merged, Status::Todo), ["a theirs", "b mine", "c"]);
+        assert_eq!(conflicts, 0);
+    }
+
+    #[test]
+    fn merge_keeps_the_local_side_of_a_conflict() {
+        let local = lists(&["a id:a", "b mine id:b", "c id:c"]);
+        let remote = lists(&["a id:a", "b theirs id:b", "c id:c"]);
+        let (merged, conflicts) = merge(&lists(BASE), &local, &remote);
+        assert_eq!(texts(&merged, Status::Todo), ["a", "b mine", "c"]);
+        assert_eq!(conflicts, 1);
+    }
+
+    #[test]
+    fn merge_adds_and_deletes_on_either_side() {
+        let local = lists(&["a id:a", "mine id:m", "b id:b"]);
+        let remote = lists(&["a id:a", "c id:c", "theirs id:t"]);
+        let (merged, conflicts) = merge(&lists(BASE), &local, &remote);
+        assert_eq!(texts(&merged, Status::Todo), ["a", "mine", "theirs"]);
+        assert_eq!(conflicts, 0);
+    }
+
+    #[test]
+    fn merge_keeps_an_item_changed_on_one_side_and_deleted_on_the_other() {
+        let local = lists(&["a mine id:a", "b id:b"]);
+        let remote = lists(&["b id:b", "c theirs id:c"]);
+        let (merged, conflicts) = merge(&lists(BASE), &local, &remote);
+        assert_eq!(texts(&merged, Status::Todo), ["a mine", "b", "c theirs"]);
+        assert_eq!(conflicts, 2);
+    }
+
+    #[test]
+    fn merge_moves_an_item_moved_on_the_other_side() {
+        let mut remote = lists(&["a id:a", "c id:c"]);
+        remote[0].dones.push(Item::parse("b id:b"));
+        let (merged, conflicts) = merge(&lists(BASE), &lists(BASE), &remote);
+        assert_eq!(texts(&merged, Status::Todo), ["a", "c"]);
+        assert_eq!(texts(&merged, Status::Done), ["b"]);
+        assert_eq!(conflicts, 0);
+    }
+
+    #[test]
+    fn adopt_ids_gives_the_same_items_their_ids() {
+        let mut read = lists(&["b", "new", "a"]);
+        adopt_ids(&mut read, &lists(BASE));
+        let ids: Vec<Option<&str>> = read[0]
+            .todos
+            .iter()
+            .map(|item| item.id.as_deref())
+            .collect();
+        assert_eq!(ids, [Some("b"), None, Some("a")]);
+    }
+}
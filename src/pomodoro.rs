@@ -0,0 +1,63 @@
+use crate::item::Item;
+use std::time::{Duration, Instant};
+
+const WORK: Duration = Duration::from_secs(25 * 60);
+const BREAK: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+// a running 25 minutes of work followed by a 5 minute break, for one todo
+pub struct Pomodoro {
+    // the list and the item the pomodoro is logged against when the work phase ends
+    pub list: usize,
+    pub item: Item,
+    pub phase: Phase,
+    started: Instant,
+}
+
+impl Pomodoro {
+    pub fn start(list: usize, item: Item) -> Self {
+        Self {
+            list,
+            item,
+            phase: Phase::Work,
+            started: Instant::now(),
+        }
+    }
+
+    fn length(&self) -> Duration {
+        match self.phase {
+            Phase::Work => WORK,
+            Phase::Break => BREAK,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.length().saturating_sub(self.started.elapsed())
+    }
+
+    // the phase that just ran out, the work phase rolls over into the break
+    pub fn tick(&mut self) -> Option<Phase> {
+        if !self.remaining().is_zero() {
+            return None;
+        }
+        let finished = self.phase;
+        self.phase = Phase::Break;
+        self.started = Instant::now();
+        Some(finished)
+    }
+
+    // the countdown shown in the status bar, e.g. `work 24:13`
+    pub fn describe(&self) -> String {
+        let seconds = self.remaining().as_secs();
+        let phase = match self.phase {
+            Phase::Work => "work",
+            Phase::Break => "break",
+        };
+        format!("{} {:02}:{:02}", phase, seconds / 60, seconds % 60)
+    }
+}
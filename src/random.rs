@@ -0,0 +1,51 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// xorshift64, good enough to suggest a todo or name an item and not meant for anything else
+pub struct Random(u64);
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::seeded()
+    }
+}
+
+impl Random {
+    pub fn seeded() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        // the state must never be zero
+        Self(nanos | 1)
+    }
+
+    // `len` random letters and digits
+    pub fn word(&mut self, len: usize) -> String {
+        (0..len)
+            .map(|_| char::from_digit((self.next() % 36) as u32, 36).unwrap())
+            .collect()
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // an index of `weights` picked with a chance proportional to its weight,
+    // `None` when they add up to nothing
+    pub fn weighted(&mut self, weights: &[u64]) -> Option<usize> {
+        let total: u64 = weights.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = self.next() % total;
+        weights.iter().position(|&weight| {
+            if roll < weight {
+                return true;
+            }
+            roll -= weight;
+            false
+        })
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+// prefix of the rules counted from the day the item is completed rather than from its
+// schedule, as in `rec:after-3d` for chores like watering plants
+const AFTER_COMPLETION: &str = "after-";
+
+// how often an item comes back once completed, written as `rec:<rule>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recurrence {
+    rule: Rule,
+    // the next due date counts from the day of completion instead of the one it was due on
+    after_completion: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    // `daily`
+    Daily,
+    // `weekly` on the weekday it is due, or `mon,wed` on those days
+    Weekly(Vec<Weekday>),
+    // `monthly` on the day of the month it is due, or `1st`, `15th` on that day
+    Monthly(Option<u32>),
+    // `3d`, every that many days
+    Every(u32),
+}
+
+impl Recurrence {
+    pub fn parse(rule: &str) -> Option<Self> {
+        let rule = rule.to_lowercase();
+        let (rule, after_completion) = match rule.strip_prefix(AFTER_COMPLETION) {
+            Some(rule) => (rule, true),
+            None => (rule.as_str(), false),
+        };
+        Some(Recurrence {
+            rule: Rule::parse(rule)?,
+            after_completion,
+        })
+    }
+
+    // the inverse of `parse`
+    pub fn rule(&self) -> String {
+        let prefix = if self.after_completion {
+            AFTER_COMPLETION
+        } else {
+            ""
+        };
+        format!("{}{}", prefix, self.rule.name())
+    }
+
+    // the day the item is due again when completed `today`; on schedule that is after both
+    // today and the day it was due on, which is what `weekly` and `monthly` go by, and
+    // otherwise as if it had been due today
+    pub fn next(&self, due: Option<NaiveDate>, today: NaiveDate) -> Option<NaiveDate> {
+        let due = match due {
+            Some(due) if !self.after_completion => due,
+            _ => today,
+        };
+        self.rule.next(due, today.max(due))
+    }
+}
+
+impl Rule {
+    fn parse(rule: &str) -> Option<Self> {
+        match rule {
+            "daily" => return Some(Rule::Daily),
+            "weekly" => return Some(Rule::Weekly(Vec::new())),
+            "monthly" => return Some(Rule::Monthly(None)),
+            _ => {}
+        }
+        if let Some(days) = rule.strip_suffix('d').and_then(|days| days.parse().ok()) {
+            return (days > 0).then_some(Rule::Every(days));
+        }
+        if let Some(day) = ["st", "nd", "rd", "th"]
+            .into_iter()
+            .find_map(|suffix| rule.strip_suffix(suffix)?.parse().ok())
+        {
+            return (1..=31).contains(&day).then_some(Rule::Monthly(Some(day)));
+        }
+        let days: Option<Vec<Weekday>> = rule.split(',').map(|day| day.parse().ok()).collect();
+        days.filter(|days| !days.is_empty()).map(Rule::Weekly)
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Rule::Daily => "daily".to_string(),
+            Rule::Weekly(days) if days.is_empty() => "weekly".to_string(),
+            Rule::Weekly(days) => days
+                .iter()
+                .map(|day| day.to_string().to_lowercase())
+                .collect::<Vec<_>>()
+                .join(","),
+            Rule::Monthly(None) => "monthly".to_string(),
+            Rule::Monthly(Some(day)) => format!("{}{}", day, ordinal_suffix(*day)),
+            Rule::Every(days) => format!("{}d", days),
+        }
+    }
+
+    // the first day after `after` on the rule, counting from `due`
+    fn next(&self, due: NaiveDate, after: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Rule::Daily => after.succ_opt(),
+            Rule::Every(days) => {
+                // keeps the rhythm of the due date when catching up with missed ones
+                let mut next = due.checked_add_days(Days::new(*days as u64))?;
+                while next <= after {
+                    next = next.checked_add_days(Days::new(*days as u64))?;
+                }
+                Some(next)
+            }
+            Rule::Weekly(days) => {
+                let days = if days.is_empty() {
+                    vec![due.weekday()]
+                } else {
+                    days.clone()
+                };
+                after
+                    .iter_days()
+                    .skip(1)
+                    .take(7)
+                    .find(|date| days.contains(&date.weekday()))
+            }
+            Rule::Monthly(day) => {
+                let day = day.unwrap_or(due.day());
+                // short months fall back to their last day
+                (0..=1)
+                    .filter_map(|months| {
+                        let month = after.with_day(1)?.checked_add_months(Months::new(months))?;
+                        let last = month.checked_add_months(Months::new(1))?.pred_opt()?.day();
+                        month.with_day(day.min(last))
+                    })
+                    .find(|&date| date > after)
+            }
+        }
+    }
+}
+
+fn ordinal_suffix(day: u32) -> &'static str {
+    match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
@@ -0,0 +1,125 @@
+use crate::item::Item;
+use std::collections::HashMap;
+
+// how many deletions the numbered registers `1` to `9` keep
+const NUMBERED: usize = 9;
+
+// vim-like registers holding cut and copied items: `"` is the one used when none is given,
+// `0` the last copy, `1`..`9` the last cuts and `a`..`z` are named, `A`..`Z` appending to them
+#[derive(Default)]
+pub struct Registers {
+    slots: HashMap<char, Vec<Item>>,
+}
+
+pub fn is_valid(register: char) -> bool {
+    register == '"' || register.is_ascii_alphanumeric()
+}
+
+impl Registers {
+    pub fn get(&self, register: Option<char>) -> Option<&Vec<Item>> {
+        let register = register.unwrap_or('"').to_ascii_lowercase();
+        self.slots.get(&register).filter(|items| !items.is_empty())
+    }
+
+    pub fn copy(&mut self, register: Option<char>, items: Vec<Item>) {
+        if register.is_none() {
+            self.slots.insert('0', items.clone());
+        }
+        self.store(register, items);
+    }
+
+    // cuts without a register shift the numbered registers down, dropping the oldest
+    pub fn cut(&mut self, register: Option<char>, items: Vec<Item>) {
+        if register.is_none() {
+            for number in (1..NUMBERED).rev() {
+                if let Some(older) = self.slots.remove(&digit(number)) {
+                    self.slots.insert(digit(number + 1), older);
+                }
+            }
+            self.slots.insert('1', items.clone());
+        }
+        self.store(register, items);
+    }
+
+    fn store(&mut self, register: Option<char>, items: Vec<Item>) {
+        match register {
+            Some(register) if register.is_ascii_uppercase() => {
+                let register = register.to_ascii_lowercase();
+                self.slots.entry(register).or_default().extend(items);
+                self.slots.insert('"', self.slots[&register].clone());
+            }
+            Some(register) => {
+                self.slots.insert(register, items.clone());
+                self.slots.insert('"', items);
+            }
+            None => {
+                self.slots.insert('"', items);
+            }
+        }
+    }
+}
+
+fn digit(number: usize) -> char {
+    char::from_digit(number as u32, 10).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(text: &str) -> Vec<Item> {
+        vec![Item::parse(text)]
+    }
+
+    fn text(registers: &Registers, register: char) -> Option<&str> {
+        let items = registers.get(Some(register))?;
+        Some(items[0].text.as_str())
+    }
+
+    #[test]
+    fn cuts_shift_the_numbered_registers() {
+        let mut registers = Registers::default();
+        for number in 1..=10 {
+            registers.cut(None, items(&format!("cut {}", number)));
+        }
+        assert_eq!(text(&registers, '1'), Some("cut 10"));
+        assert_eq!(text(&registers, '2'), Some("cut 9"));
+        assert_eq!(text(&registers, '9'), Some("cut 2"));
+        assert_eq!(text(&registers, '"'), Some("cut 10"));
+        // the oldest went
+        assert!((1..=9).all(|number| text(&registers, digit(number)) != Some("cut 1")));
+    }
+
+    #[test]
+    fn copies_and_named_cuts_leave_the_numbered_registers_alone() {
+        let mut registers = Registers::default();
+        registers.cut(None, items("first"));
+        registers.copy(None, items("copied"));
+        registers.cut(Some('a'), items("named"));
+        assert_eq!(text(&registers, '1'), Some("first"));
+        assert_eq!(text(&registers, '2'), None);
+        assert_eq!(text(&registers, '0'), Some("copied"));
+        assert_eq!(text(&registers, 'a'), Some("named"));
+        assert_eq!(text(&registers, '"'), Some("named"));
+    }
+
+    #[test]
+    fn uppercase_registers_append() {
+        let mut registers = Registers::default();
+        registers.copy(Some('a'), items("one"));
+        registers.copy(Some('A'), items("two"));
+        let texts = |register| -> Vec<String> {
+            registers
+                .get(Some(register))
+                .unwrap()
+                .iter()
+                .map(|item| item.text.clone())
+                .collect()
+        };
+        assert_eq!(texts('a'), ["one", "two"]);
+        assert_eq!(texts('A'), ["one", "two"]);
+        assert_eq!(texts('"'), ["one", "two"]);
+        // a copy into a named register isn't the last copy
+        assert_eq!(registers.get(Some('0')), None);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::app::Status;
+use crate::filter::Filter;
+use crate::group::Grouping;
+use chrono::NaiveDate;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// where the view of a file was left, restored the next time it is opened
+#[derive(Debug, Default)]
+pub struct Session {
+    pub list: String,
+    pub tab: Status,
+    // the cursor of each tab of each list, by list name
+    pub cursors: Vec<(String, Status, usize)>,
+    pub filter: Filter,
+    pub grouping: Grouping,
+    pub collapsed: Vec<String>,
+    pub unified: bool,
+    pub hide_done: bool,
+}
+
+// `$XDG_STATE_HOME/clitodo`, for what is kept about the files between runs
+pub fn state_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+    Some(dir.join("clitodo"))
+}
+
+// `$XDG_STATE_HOME/clitodo/sessions/<file>`, the absolute path of the todo file with its
+// slashes turned into `%`
+fn session_path(file_path: &str) -> Option<PathBuf> {
+    let file = fs::canonicalize(file_path).ok()?;
+    let name = file.to_string_lossy().replace('/', "%");
+    Some(state_dir()?.join("sessions").join(name))
+}
+
+fn parse_status(name: &str) -> Option<Status> {
+    Status::ALL.into_iter().find(|status| status.name() == name)
+}
+
+fn parse_grouping(name: &str) -> Option<Grouping> {
+    [Grouping::Project, Grouping::Tag, Grouping::Due]
+        .into_iter()
+        .find(|grouping| grouping.name() == name)
+}
+
+impl Session {
+    // a missing or unreadable session starts from the top, lines that don't make sense
+    // anymore are skipped
+    pub fn load(file_path: &str) -> Option<Self> {
+        let content = fs::read_to_string(session_path(file_path)?).ok()?;
+        let mut session = Session::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(" = ") else {
+                continue;
+            };
+            match key {
+                "list" => session.list = value.to_string(),
+                "tab" => session.tab = parse_status(value).unwrap_or_default(),
+                "cursor" => {
+                    // `<tab> <index> <list name>`, the name last as it may hold spaces
+                    let mut parts = value.splitn(3, ' ');
+                    let (Some(status), Some(index), Some(list)) =
+                        (parts.next(), parts.next(), parts.next())
+                    else {
+                        continue;
+                    };
+                    if let (Some(status), Ok(index)) = (parse_status(status), index.parse()) {
+                        session.cursors.push((list.to_string(), status, index));
+                    }
+                }
+                "filter" => session.filter.word = Some(value.to_string()),
+                "context" => session.filter.context = Some(value.to_string()),
+                "assignee" => session.filter.assignee = Some(value.to_string()),
+                "due" => session.filter.due = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+                "today" => session.filter.today = value == "true",
+                "snoozed" => session.filter.snoozed = value == "true",
+                "fit" => session.filter.fit = value.parse().ok(),
+                "grouping" => session.grouping = parse_grouping(value).unwrap_or_default(),
+                "collapsed" => session.collapsed.push(value.to_string()),
+                "unified" => session.unified = value == "true",
+                "hide_done" => session.hide_done = value == "true",
+                _ => {}
+            }
+        }
+        Some(session)
+    }
+
+    pub fn save(&self, file_path: &str) -> io::Result<()> {
+        let Some(path) = session_path(file_path) else {
+            return Ok(());
+        };
+        let mut lines = vec![
+            format!("list = {}", self.list),
+            format!("tab = {}", self.tab.name()),
+        ];
+        for (list, status, index) in &self.cursors {
+            lines.push(format!("cursor = {} {} {}", status.name(), index, list));
+        }
+        lines.extend(
+            self.filter
+                .word
+                .iter()
+                .map(|word| format!("filter = {}", word)),
+        );
+        lines.extend(
+            self.filter
+                .context
+                .iter()
+                .map(|context| format!("context = {}", context)),
+        );
+        lines.extend(
+            self.filter
+                .assignee
+                .iter()
+                .map(|assignee| format!("assignee = {}", assignee)),
+        );
+        lines.extend(self.filter.due.map(|due| format!("due = {}", due)));
+        lines.push(format!("today = {}", self.filter.today));
+        lines.push(format!("snoozed = {}", self.filter.snoozed));
+        lines.extend(self.filter.fit.map(|fit| format!("fit = {}", fit)));
+        lines.push(format!("grouping = {}", self.grouping.name()));
+        lines.extend(
+            self.collapsed
+                .iter()
+                .map(|name| format!("collapsed = {}", name)),
+        );
+        lines.push(format!("unified = {}", self.unified));
+        lines.push(format!("hide_done = {}", self.hide_done));
+
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, lines.join("\n") + "\n")
+    }
+}
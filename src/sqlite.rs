@@ -0,0 +1,489 @@
+use crate::app::{List, Status};
+use crate::item::Item;
+#[cfg(feature = "sqlite")]
+use crate::item::State;
+#[cfg(feature = "sqlite")]
+use rusqlite::{types::Type, Connection, Transaction, TransactionBehavior};
+#[cfg(feature = "sqlite")]
+use std::collections::HashMap;
+use std::path::Path;
+#[cfg(feature = "sqlite")]
+use std::path::PathBuf;
+#[cfg(feature = "sqlite")]
+use std::sync::Mutex;
+#[cfg(feature = "sqlite")]
+use std::time::Duration;
+
+// `.db`, `.sqlite` and `.sqlite3` files are SQLite databases, for lists too large for a text
+// file, with the fields other tools search by as columns of their own; needs the `sqlite`
+// cargo feature
+pub fn is_sqlite(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            ["db", "sqlite", "sqlite3"]
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+// `line` is the item as a line of the text files without its indentation and state, which
+// have columns of their own, and `notes` a JSON array; the triggers count the changes to the
+// items of each list in its `version`, whoever makes them
+#[cfg(feature = "sqlite")]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS lists (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    position INTEGER NOT NULL,
+    version INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS items (
+    list INTEGER NOT NULL REFERENCES lists (id),
+    tab TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    depth INTEGER NOT NULL,
+    uid TEXT,
+    text TEXT NOT NULL,
+    state TEXT,
+    priority TEXT,
+    due TEXT,
+    assignee TEXT,
+    completed TEXT,
+    line TEXT NOT NULL,
+    notes TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS items_by_list ON items (list, tab, position);
+CREATE INDEX IF NOT EXISTS items_by_uid ON items (uid);
+CREATE INDEX IF NOT EXISTS items_by_due ON items (due);
+CREATE TRIGGER IF NOT EXISTS item_inserted AFTER INSERT ON items BEGIN
+    UPDATE lists SET version = version + 1 WHERE id = NEW.list;
+END;
+CREATE TRIGGER IF NOT EXISTS item_updated AFTER UPDATE ON items BEGIN
+    UPDATE lists SET version = version + 1 WHERE id IN (OLD.list, NEW.list);
+END;
+CREATE TRIGGER IF NOT EXISTS item_deleted AFTER DELETE ON items BEGIN
+    UPDATE lists SET version = version + 1 WHERE id = OLD.list;
+END;
+";
+
+// how long to wait for another program writing to the database before giving up
+#[cfg(feature = "sqlite")]
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// the `tab` column of the items, named like the arrays of a JSON file
+#[cfg(feature = "sqlite")]
+fn tab(status: Status) -> &'static str {
+    match status {
+        Status::Todo => "todo",
+        Status::Done => "done",
+        Status::Archived => "archived",
+        Status::Trashed => "trashed",
+    }
+}
+
+// the depth, state, line and notes columns of an item, all that is read back from them
+#[cfg(feature = "sqlite")]
+#[derive(Clone, PartialEq)]
+struct Row {
+    depth: usize,
+    state: Option<String>,
+    line: String,
+    notes: Vec<String>,
+}
+
+#[cfg(feature = "sqlite")]
+impl Row {
+    fn new(item: &Item) -> Self {
+        Row {
+            depth: item.depth,
+            state: item.state.as_ref().map(|state| state.name().to_string()),
+            line: item.to_line().trim_start().to_string(),
+            notes: item.notes.clone(),
+        }
+    }
+
+    fn item(&self) -> Item {
+        Item {
+            state: self.state.as_deref().and_then(State::parse),
+            depth: self.depth,
+            notes: self.notes.clone(),
+            ..Item::parse(&self.line)
+        }
+    }
+}
+
+// the rows of each tab of a list
+#[cfg(feature = "sqlite")]
+fn rows(list: &List) -> Vec<Vec<Row>> {
+    Status::ALL
+        .map(|status| list.items(status).iter().map(Row::new).collect())
+        .to_vec()
+}
+
+// a list as last read from or written to a database, with its `version` then
+#[cfg(feature = "sqlite")]
+struct Known {
+    version: i64,
+    rows: Vec<Vec<Row>>,
+}
+
+// what is known of the lists of each database, so that only those changed since are read
+// or written again
+#[cfg(feature = "sqlite")]
+static KNOWN: Mutex<Vec<(PathBuf, HashMap<String, Known>)>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "sqlite")]
+fn known<T>(path: &Path, f: impl FnOnce(&mut HashMap<String, Known>) -> T) -> T {
+    let mut known = KNOWN.lock().unwrap_or_else(|err| err.into_inner());
+    let index = match known.iter().position(|(known, _)| known == path) {
+        Some(index) => index,
+        None => {
+            known.push((path.to_path_buf(), HashMap::new()));
+            known.len() - 1
+        }
+    };
+    f(&mut known[index].1)
+}
+
+// write-ahead logging lets other programs read while the lists are being saved; a database
+// from before the lists had versions gets the column
+#[cfg(feature = "sqlite")]
+fn open(path: &Path) -> rusqlite::Result<Connection> {
+    let db = Connection::open(path)?;
+    db.busy_timeout(BUSY_TIMEOUT)?;
+    db.pragma_update(None, "journal_mode", "WAL")?;
+    let columns = db
+        .prepare("SELECT name FROM pragma_table_info('lists')")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    if !columns.is_empty() && !columns.iter().any(|column| column == "version") {
+        db.execute_batch("ALTER TABLE lists ADD COLUMN version INTEGER NOT NULL DEFAULT 0")?;
+    }
+    db.execute_batch(SCHEMA)?;
+    Ok(db)
+}
+
+// the id, name and version of each list in order
+#[cfg(feature = "sqlite")]
+fn versions(db: &Connection) -> rusqlite::Result<Vec<(i64, String, i64)>> {
+    db.prepare("SELECT id, name, version FROM lists ORDER BY position")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect()
+}
+
+#[cfg(feature = "sqlite")]
+fn read_rows(db: &Connection, id: i64) -> rusqlite::Result<Vec<Vec<Row>>> {
+    let mut items = db.prepare_cached(
+        "SELECT depth, state, line, notes FROM items WHERE list = ?1 AND tab = ?2
+         ORDER BY position",
+    )?;
+    let mut tabs = Vec::new();
+    for status in Status::ALL {
+        let rows = items.query_map((id, tab(status)), |row| {
+            let notes: String = row.get(3)?;
+            let notes = serde_json::from_str(&notes).map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(3, Type::Text, Box::new(err))
+            })?;
+            Ok(Row {
+                depth: row.get(0)?,
+                state: row.get(1)?,
+                line: row.get(2)?,
+                notes,
+            })
+        })?;
+        tabs.push(rows.collect::<rusqlite::Result<_>>()?);
+    }
+    Ok(tabs)
+}
+
+// the items of only the lists changed since they were last read or written are read
+#[cfg(feature = "sqlite")]
+fn read(db: &mut Connection, path: &Path) -> rusqlite::Result<Vec<List>> {
+    let db = db.transaction()?;
+    let versions = versions(&db)?;
+    known(path, |known| {
+        known.retain(|name, _| versions.iter().any(|(_, other, _)| other == name));
+        let mut lists = Vec::new();
+        for (id, name, version) in versions {
+            if known
+                .get(&name)
+                .is_none_or(|known| known.version != version)
+            {
+                let rows = read_rows(&db, id)?;
+                known.insert(name.clone(), Known { version, rows });
+            }
+            let mut list = List::new(name.clone());
+            for (status, rows) in Status::ALL.into_iter().zip(&known[&name].rows) {
+                *list.items_mut(status) = rows.iter().map(Row::item).collect();
+            }
+            lists.push(list);
+        }
+        Ok(lists)
+    })
+}
+
+#[cfg(feature = "sqlite")]
+pub fn load(path: &Path) -> Result<Vec<List>, String> {
+    open(path)
+        .and_then(|mut db| read(&mut db, path))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn load(_path: &Path) -> Result<Vec<List>, String> {
+    Err("built without SQLite support, rebuild with `--features sqlite`".to_string())
+}
+
+#[cfg(feature = "sqlite")]
+fn insert(
+    db: &Transaction,
+    id: i64,
+    status: Status,
+    position: usize,
+    item: &Item,
+) -> rusqlite::Result<()> {
+    let row = Row::new(item);
+    db.prepare_cached(
+        "INSERT INTO items (list, tab, position, depth, uid, text, state, priority, due,
+         assignee, completed, line, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+    )?
+    .execute(rusqlite::params![
+        id,
+        tab(status),
+        position,
+        row.depth,
+        item.id,
+        item.text,
+        row.state,
+        item.priority.map(String::from),
+        item.due.map(|due| due.to_string()),
+        item.assignee,
+        item.completed.map(crate::item::zoned),
+        row.line,
+        serde_json::to_string(&row.notes).unwrap(),
+    ])?;
+    Ok(())
+}
+
+// in one transaction, where only the lists whose columns differ from what the database
+// holds are written again to keep saving a large one short; a list changed by another
+// program since it was last read is written over
+#[cfg(feature = "sqlite")]
+fn write(db: &mut Connection, path: &Path, lists: &[List]) -> rusqlite::Result<()> {
+    let db = db.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let old = versions(&db)?;
+    for (id, _, _) in old
+        .iter()
+        .filter(|(_, old, _)| !lists.iter().any(|list| &list.name == old))
+    {
+        db.execute("DELETE FROM items WHERE list = ?1", [id])?;
+        db.execute("DELETE FROM lists WHERE id = ?1", [id])?;
+    }
+    let mut written = Vec::new();
+    for (position, list) in lists.iter().enumerate() {
+        let id: i64 = db.query_row(
+            "INSERT INTO lists (name, position) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET position = ?2 RETURNING id",
+            (&list.name, position),
+            |row| row.get(0),
+        )?;
+        let rows = rows(list);
+        let version = old
+            .iter()
+            .find(|(other, _, _)| *other == id)
+            .map(|(_, _, version)| *version);
+        let same = known(path, |known| {
+            known
+                .get(&list.name)
+                .is_some_and(|known| Some(known.version) == version && known.rows == rows)
+        });
+        if same {
+            continue;
+        }
+        db.execute("DELETE FROM items WHERE list = ?1", [id])?;
+        for status in Status::ALL {
+            for (position, item) in list.items(status).iter().enumerate() {
+                insert(&db, id, status, position, item)?;
+            }
+        }
+        let version = db.query_row("SELECT version FROM lists WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })?;
+        written.push((list.name.clone(), Known { version, rows }));
+    }
+    db.commit()?;
+    known(path, |known| {
+        known.retain(|name, _| lists.iter().any(|list| &list.name == name));
+        known.extend(written);
+    });
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+pub fn save(path: &Path, lists: &[List]) -> Result<(), String> {
+    open(path)
+        .and_then(|mut db| write(&mut db, path, lists))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn save(_path: &Path, _lists: &[List]) -> Result<(), String> {
+    Err("built without SQLite support, rebuild with `--features sqlite`".to_string())
+}
+
+// adds the items to the end of the tab of the last list, or of a new `default` one when
+// there is none, without reading the others
+#[cfg(feature = "sqlite")]
+pub fn append(path: &Path, status: Status, items: &[Item], default: &str) -> Result<(), String> {
+    let append = || {
+        let mut db = open(path)?;
+        let db = db.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let id: i64 = match versions(&db)?.last() {
+            Some((id, _, _)) => *id,
+            None => db.query_row(
+                "INSERT INTO lists (name, position) VALUES (?1, 0) RETURNING id",
+                [default],
+                |row| row.get(0),
+            )?,
+        };
+        let end: usize = db.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM items WHERE list = ?1 AND tab = ?2",
+            (id, tab(status)),
+            |row| row.get(0),
+        )?;
+        for (offset, item) in items.iter().enumerate() {
+            insert(&db, id, status, end + offset, item)?;
+        }
+        db.commit()
+    };
+    append().map_err(|err: rusqlite::Error| err.to_string())
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn append(
+    _path: &Path,
+    _status: Status,
+    _items: &[Item],
+    _default: &str,
+) -> Result<(), String> {
+    Err("built without SQLite support, rebuild with `--features sqlite`".to_string())
+}
+
+// copies the database to `backup` as it is, the changes still in its write-ahead log
+// included, which copying the file would miss
+#[cfg(feature = "sqlite")]
+pub fn back_up(path: &Path, backup: &Path) -> Result<(), String> {
+    open(path)
+        .and_then(|db| db.execute("VACUUM INTO ?1", [backup.to_string_lossy()]))
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn back_up(_path: &Path, _backup: &Path) -> Result<(), String> {
+    Err("built without SQLite support, rebuild with `--features sqlite`".to_string())
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn lists() -> Vec<List> {
+        let mut home = List::new("home");
+        home.todos = vec![
+            Item::parse("(A) fix the sink due:2025-07-01 id:k3x9"),
+            Item {
+                notes: vec!["the red one".to_string()],
+                ..Item::parse("  buy a washer id:p2m4")
+            },
+        ];
+        home.dones.push(Item::parse("water the plants id:q8w1"));
+        let mut work = List::new("work");
+        work.todos.push(Item::parse("send the report id:z5c7"));
+        vec![home, work]
+    }
+
+    fn texts(lists: &[List]) -> Vec<Vec<&str>> {
+        lists
+            .iter()
+            .map(|list| list.todos.iter().map(|item| item.text.as_str()).collect())
+            .collect()
+    }
+
+    fn version(path: &Path, name: &str) -> i64 {
+        Connection::open(path)
+            .unwrap()
+            .query_row("SELECT version FROM lists WHERE name = ?1", [name], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn load_reads_what_save_wrote() {
+        let dir = TempDir::new("sqlite");
+        let path = dir.path().join("todo.db");
+        save(&path, &lists()).unwrap();
+        // as another run would, with nothing known of the database
+        KNOWN.lock().unwrap().retain(|(known, _)| known != &path);
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        for (loaded, list) in loaded.iter().zip(lists()) {
+            assert_eq!(loaded.name, list.name);
+            for status in Status::ALL {
+                assert_eq!(loaded.items(status), list.items(status));
+            }
+        }
+    }
+
+    #[test]
+    fn load_reads_a_list_changed_by_another_program_again() {
+        let dir = TempDir::new("sqlite");
+        let path = dir.path().join("todo.db");
+        save(&path, &lists()).unwrap();
+        let before = version(&path, "home");
+        Connection::open(&path)
+            .unwrap()
+            .execute(
+                "UPDATE items SET line = 'fix the tap id:k3x9' WHERE uid = 'k3x9'",
+                [],
+            )
+            .unwrap();
+        assert!(version(&path, "home") > before);
+        let loaded = load(&path).unwrap();
+        assert_eq!(
+            texts(&loaded),
+            [vec!["fix the tap", "buy a washer"], vec!["send the report"]]
+        );
+    }
+
+    #[test]
+    fn unchanged_lists_are_neither_written_nor_read_again() {
+        let dir = TempDir::new("sqlite");
+        let path = dir.path().join("todo.db");
+        let mut lists = lists();
+        save(&path, &lists).unwrap();
+        let (home, work) = (version(&path, "home"), version(&path, "work"));
+        lists[1].todos.push(Item::parse("book a room id:m1n2"));
+        save(&path, &lists).unwrap();
+        assert_eq!(version(&path, "home"), home);
+        assert!(version(&path, "work") > work);
+        // changed behind the version's back, which only a list read again would show
+        let db = Connection::open(&path).unwrap();
+        db.execute("UPDATE items SET line = 'changed' WHERE uid = 'k3x9'", [])
+            .unwrap();
+        db.execute("UPDATE lists SET version = ?1 WHERE name = 'home'", [home])
+            .unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(
+            texts(&loaded),
+            [
+                vec!["fix the sink", "buy a washer"],
+                vec!["send the report", "book a room"]
+            ]
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::app::{self, App, Status};
+use crate::dates::DateFormat;
+use crate::item::{age, hours, State};
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime};
+
+const BAR_WIDTH: usize = 20;
+const DAYS: u64 = 7;
+const WEEKS: u64 = 4;
+const TOP_TAGS: usize = 5;
+
+// `####    ` scaled so that `max` fills the bar
+fn bar(count: usize, max: usize) -> String {
+    let filled = (count * BAR_WIDTH).checked_div(max).unwrap_or(0);
+    format!("{}{}", "#".repeat(filled), " ".repeat(BAR_WIDTH - filled))
+}
+
+// the stats screen for the active list, done and archived items counting as completed
+// unless they were cancelled
+pub fn lines(app: &App, now: NaiveDateTime, dates: &DateFormat) -> Vec<String> {
+    let today = now.date();
+    let completed: Vec<NaiveDate> = [Status::Done, Status::Archived]
+        .into_iter()
+        .flat_map(|status| app.list(status))
+        .filter(|item| item.state != Some(State::Cancelled))
+        .filter_map(|item| item.completed.map(|completed| completed.date()))
+        .collect();
+    let on = |date: NaiveDate| completed.iter().filter(|&&other| other == date).count();
+
+    let mut lines = vec!["Completed per day".to_string()];
+    let days: Vec<(NaiveDate, usize)> = (0..DAYS)
+        .rev()
+        .map(|offset| today - Days::new(offset))
+        .map(|date| (date, on(date)))
+        .collect();
+    let max = days.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    for (date, count) in days {
+        lines.push(format!(
+            "  {}  {} {}",
+            dates.day(date),
+            bar(count, max),
+            count
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push("Completed per week".to_string());
+    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    let weeks: Vec<(NaiveDate, usize)> = (0..WEEKS)
+        .rev()
+        .map(|offset| monday - Days::new(offset * 7))
+        .map(|start| {
+            let end = start + Days::new(7);
+            let count = completed
+                .iter()
+                .filter(|&&date| (start..end).contains(&date))
+                .count();
+            (start, count)
+        })
+        .collect();
+    let max = weeks.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    for (start, count) in weeks {
+        lines.push(format!(
+            "  wk {}  {} {}",
+            dates.short_date(start),
+            bar(count, max),
+            count
+        ));
+    }
+
+    // a streak still counts when nothing was completed yet today
+    let mut day = if on(today) > 0 {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+    while on(day) > 0 {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+
+    let ages: Vec<i64> = app
+        .list(Status::Todo)
+        .iter()
+        .filter_map(|item| item.created)
+        .map(|created| (now - created).num_minutes())
+        .collect();
+    let average = match ages.len() {
+        0 => "-".to_string(),
+        len => age(
+            now - chrono::Duration::minutes(ages.iter().sum::<i64>() / len as i64),
+            now,
+        ),
+    };
+
+    lines.push(String::new());
+    lines.push(format!(
+        "Current streak:    {} day{}",
+        streak,
+        if streak == 1 { "" } else { "s" }
+    ));
+    lines.push(format!("Average todo age:  {}", average));
+
+    // each tag with how many items have it and the minutes tracked on them
+    let mut tags: Vec<(String, usize, u32)> = Vec::new();
+    for item in Status::ALL
+        .into_iter()
+        .filter(|&status| status != Status::Trashed)
+        .flat_map(|status| app.list(status))
+    {
+        for tag in app::tags(&item.text) {
+            let tag = tag.to_lowercase();
+            match tags.iter_mut().find(|(other, ..)| *other == tag) {
+                Some((_, count, minutes)) => {
+                    *count += 1;
+                    *minutes += item.tracked;
+                }
+                None => tags.push((tag, 1, item.tracked)),
+            }
+        }
+    }
+    tags.sort_by(|(a, x, _), (b, y, _)| y.cmp(x).then(a.cmp(b)));
+    lines.push(String::new());
+    lines.push("Busiest tags, with the time tracked on them".to_string());
+    if tags.is_empty() {
+        lines.push("  -".to_string());
+    }
+    let max = tags.first().map_or(0, |(_, count, _)| *count);
+    for (tag, count, minutes) in tags.iter().take(TOP_TAGS) {
+        let mut line = format!("  {:<12} {} {:<3}", tag, bar(*count, max), count);
+        if *minutes > 0 {
+            line.push_str(&format!(" {}", hours(*minutes)));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
@@ -0,0 +1,65 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+const SPINNER: &[char] = &['|', '/', '-', '\\'];
+
+// what a task reports when it's done, shown as an info or error message
+pub type Outcome = Result<String, String>;
+
+// work running on threads while the list stays usable, e.g. saving a large file
+#[derive(Default)]
+pub struct Tasks {
+    running: Vec<(String, Receiver<Outcome>)>,
+    frame: usize,
+}
+
+impl Tasks {
+    pub fn spawn(&mut self, label: &str, work: impl FnOnce() -> Outcome + Send + 'static) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(work());
+        });
+        self.running.push((label.to_string(), receiver));
+    }
+
+    // outcomes of the tasks that finished since the last poll
+    pub fn poll(&mut self) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+        self.running
+            .retain(|(label, receiver)| match receiver.try_recv() {
+                Ok(outcome) => {
+                    outcomes.push(outcome);
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                Err(TryRecvError::Disconnected) => {
+                    outcomes.push(Err(format!("ERROR: {} failed", label)));
+                    false
+                }
+            });
+        outcomes
+    }
+
+    // blocks until every task is done, e.g. before quitting
+    pub fn wait(&mut self) -> Vec<Outcome> {
+        self.running
+            .drain(..)
+            .map(|(label, receiver)| {
+                receiver
+                    .recv()
+                    .unwrap_or_else(|_| Err(format!("ERROR: {} failed", label)))
+            })
+            .collect()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    // a spinner and the label of the oldest running task, one spinner step per call
+    pub fn status(&mut self) -> Option<String> {
+        let (label, _) = self.running.first()?;
+        self.frame = (self.frame + 1) % SPINNER.len();
+        Some(format!("{} {}...", SPINNER[self.frame], label))
+    }
+}
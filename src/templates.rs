@@ -0,0 +1,102 @@
+use crate::config::config_path;
+use chrono::NaiveDate;
+use std::fs;
+use std::path::PathBuf;
+
+// spaces of indentation per level of subtasks in the templates file
+const INDENT: usize = 2;
+// the placeholder filled in with the day the template is used on
+const TODAY: &str = "today";
+
+// items to add together, read from `[name]` sections of the templates file with a line per
+// item, subtasks indented under their item and `{placeholders}` asked for when used
+pub struct Template {
+    pub name: String,
+    // the lines with their nesting level
+    lines: Vec<(usize, String)>,
+}
+
+// `templates` next to the config file
+pub fn templates_path() -> Option<PathBuf> {
+    Some(config_path()?.with_file_name("templates"))
+}
+
+// a missing file has no templates, a malformed one is reported as `path:line: message`
+pub fn load() -> Result<Vec<Template>, String> {
+    let Some(path) = templates_path() else {
+        return Ok(Vec::new());
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    let mut templates: Vec<Template> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Some(name) = text
+            .strip_prefix('[')
+            .and_then(|text| text.strip_suffix(']'))
+        {
+            templates.push(Template {
+                name: name.trim().to_string(),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let Some(template) = templates.last_mut() else {
+            return Err(format!(
+                "{}:{}: ERROR: expected a `[name]` line before the items",
+                path.display(),
+                index + 1
+            ));
+        };
+        let depth = (line.len() - line.trim_start_matches(' ').len()) / INDENT;
+        template.lines.push((depth, text.to_string()));
+    }
+    Ok(templates)
+}
+
+impl Template {
+    // the names of the placeholders to ask for, in the order they first appear
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for (_, line) in &self.lines {
+            let mut rest = line.as_str();
+            while let Some((name, after)) = placeholder(rest) {
+                if name != TODAY && !names.iter().any(|other| other == name) {
+                    names.push(name.to_string());
+                }
+                rest = after;
+            }
+        }
+        names
+    }
+
+    // the lines with the placeholders replaced by their `values`, `{today}` by the date
+    pub fn expand(&self, values: &[(String, String)], today: NaiveDate) -> Vec<(usize, String)> {
+        let today = today.format("%Y-%m-%d").to_string();
+        self.lines
+            .iter()
+            .map(|(depth, line)| {
+                let mut line = line.replace(&format!("{{{}}}", TODAY), &today);
+                for (name, value) in values {
+                    line = line.replace(&format!("{{{}}}", name), value);
+                }
+                (*depth, line)
+            })
+            .collect()
+    }
+}
+
+// the name of the first `{placeholder}` in `text` and the text after it
+fn placeholder(text: &str) -> Option<(&str, &str)> {
+    let (_, rest) = text.split_once('{')?;
+    let (name, after) = rest.split_once('}')?;
+    match name.trim() {
+        _ if name.contains('{') => placeholder(rest),
+        "" => placeholder(after),
+        _ => Some((name, after)),
+    }
+}
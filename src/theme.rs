@@ -0,0 +1,379 @@
+use crate::ui::*;
+use ncurses::*;
+
+// xterm's default RGB values of the 16 base colors
+const BASE_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+// the steps of each channel in the 6x6x6 color cube of 256 color terminals
+const CUBE_STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+// terminals with this many colors take 24-bit RGB values as color numbers
+const DIRECT_COLORS: i32 = 1 << 24;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Color {
+    // the terminal's own foreground or background
+    Default,
+    // a number of the 256 color palette, the first 8 being the base colors
+    Index(u8),
+    Rgb(u8, u8, u8),
+}
+
+// foreground and background of a color pair
+pub type Colors = (Color, Color);
+
+// colors of the built-in themes by number, -1 being the terminal's default color
+const fn colors(fg: i16, bg: i16) -> Colors {
+    (Color::of(fg), Color::of(bg))
+}
+
+impl Color {
+    const fn of(number: i16) -> Self {
+        if number < 0 {
+            Color::Default
+        } else {
+            Color::Index(number as u8)
+        }
+    }
+
+    // the color number on a terminal with `count` colors, the closest one it has when it
+    // can't show this one; truecolor terminals take any RGB value
+    fn number(self, count: i32) -> i32 {
+        match self {
+            Color::Default => -1,
+            Color::Index(index)
+                if i32::from(index) < count.min(256) && (index < 8 || count < DIRECT_COLORS) =>
+            {
+                index.into()
+            }
+            Color::Index(index) => {
+                let (r, g, b) = rgb_of(index);
+                Color::Rgb(r, g, b).number(count)
+            }
+            Color::Rgb(r, g, b) if count >= DIRECT_COLORS => {
+                i32::from(r) << 16 | i32::from(g) << 8 | i32::from(b)
+            }
+            Color::Rgb(r, g, b) if count >= 256 => closest((r, g, b), 16..=255),
+            Color::Rgb(r, g, b) => closest((r, g, b), 0..=count.clamp(1, 16) as u8 - 1),
+        }
+    }
+}
+
+// the RGB value of a palette number in xterm
+fn rgb_of(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASE_COLORS[index as usize],
+        16..=231 => {
+            let cube = index as usize - 16;
+            (
+                CUBE_STEPS[cube / 36],
+                CUBE_STEPS[cube / 6 % 6],
+                CUBE_STEPS[cube % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+// the palette number of `range` that looks the most like the RGB value
+fn closest(rgb: (u8, u8, u8), range: std::ops::RangeInclusive<u8>) -> i32 {
+    let distance = |index: &u8| {
+        let other = rgb_of(*index);
+        [(rgb.0, other.0), (rgb.1, other.1), (rgb.2, other.2)]
+            .into_iter()
+            .map(|(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+    range.min_by_key(distance).map_or(-1, i32::from)
+}
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub regular: Colors,
+    pub highlight: Colors,
+    pub header: Colors,
+    pub done: Colors,
+    pub overdue: Colors,
+    pub today: Colors,
+    pub priority_a: Colors,
+    pub priority_b: Colors,
+    pub priority_c: Colors,
+    // TODO items pending for longer than `stale_days`
+    pub stale: Colors,
+    // items in a state, see `item::State`
+    pub in_progress: Colors,
+    pub waiting: Colors,
+    pub cancelled: Colors,
+    // pinned TODO items, see `Item::pinned`
+    pub pinned: Colors,
+    pub status: Colors,
+    pub error: Colors,
+    pub matched: Colors,
+    pub selection: Colors,
+    // only its background is used, for every other row of the lists with `zebra = true`
+    pub stripe: Colors,
+    // sets rows apart with bold, reverse and a `>` cursor too, not with colors alone
+    pub accessible: bool,
+}
+
+pub const THEMES: &[(&str, Theme)] = &[
+    (
+        "default",
+        Theme {
+            regular: colors(-1, -1),
+            highlight: colors(COLOR_BLACK, COLOR_WHITE),
+            header: colors(-1, -1),
+            done: colors(-1, -1),
+            overdue: colors(COLOR_RED, -1),
+            today: colors(COLOR_YELLOW, -1),
+            priority_a: colors(COLOR_MAGENTA, -1),
+            priority_b: colors(COLOR_CYAN, -1),
+            priority_c: colors(COLOR_BLUE, -1),
+            stale: colors(COLOR_RED, -1),
+            in_progress: colors(COLOR_GREEN, -1),
+            waiting: colors(245, -1),
+            cancelled: colors(COLOR_RED, -1),
+            pinned: colors(214, -1),
+            status: colors(COLOR_BLACK, COLOR_CYAN),
+            error: colors(COLOR_WHITE, COLOR_RED),
+            matched: colors(COLOR_BLACK, COLOR_YELLOW),
+            selection: colors(COLOR_WHITE, COLOR_BLUE),
+            stripe: colors(-1, 236),
+            accessible: false,
+        },
+    ),
+    (
+        "dark",
+        Theme {
+            regular: colors(COLOR_WHITE, COLOR_BLACK),
+            highlight: colors(COLOR_BLACK, COLOR_CYAN),
+            header: colors(COLOR_CYAN, COLOR_BLACK),
+            done: colors(COLOR_GREEN, COLOR_BLACK),
+            overdue: colors(COLOR_RED, COLOR_BLACK),
+            today: colors(COLOR_YELLOW, COLOR_BLACK),
+            priority_a: colors(COLOR_MAGENTA, COLOR_BLACK),
+            priority_b: colors(COLOR_CYAN, COLOR_BLACK),
+            priority_c: colors(COLOR_BLUE, COLOR_BLACK),
+            stale: colors(COLOR_RED, COLOR_BLACK),
+            in_progress: colors(COLOR_GREEN, COLOR_BLACK),
+            waiting: colors(245, COLOR_BLACK),
+            cancelled: colors(COLOR_RED, COLOR_BLACK),
+            pinned: colors(214, COLOR_BLACK),
+            status: colors(COLOR_BLACK, COLOR_WHITE),
+            error: colors(COLOR_WHITE, COLOR_RED),
+            matched: colors(COLOR_BLACK, COLOR_YELLOW),
+            selection: colors(COLOR_WHITE, COLOR_BLUE),
+            stripe: colors(-1, 236),
+            accessible: false,
+        },
+    ),
+    (
+        "light",
+        Theme {
+            regular: colors(COLOR_BLACK, COLOR_WHITE),
+            highlight: colors(COLOR_WHITE, COLOR_BLUE),
+            header: colors(COLOR_BLUE, COLOR_WHITE),
+            done: colors(COLOR_GREEN, COLOR_WHITE),
+            overdue: colors(COLOR_RED, COLOR_WHITE),
+            today: colors(COLOR_MAGENTA, COLOR_WHITE),
+            priority_a: colors(COLOR_RED, COLOR_WHITE),
+            priority_b: colors(COLOR_BLUE, COLOR_WHITE),
+            priority_c: colors(COLOR_CYAN, COLOR_WHITE),
+            stale: colors(COLOR_RED, COLOR_WHITE),
+            in_progress: colors(COLOR_GREEN, COLOR_WHITE),
+            waiting: colors(243, COLOR_WHITE),
+            cancelled: colors(COLOR_RED, COLOR_WHITE),
+            pinned: colors(166, COLOR_WHITE),
+            status: colors(COLOR_WHITE, COLOR_BLACK),
+            error: colors(COLOR_WHITE, COLOR_RED),
+            matched: colors(COLOR_BLACK, COLOR_YELLOW),
+            selection: colors(COLOR_BLACK, COLOR_CYAN),
+            stripe: colors(-1, 254),
+            accessible: false,
+        },
+    ),
+    (
+        "ocean",
+        Theme {
+            regular: colors(COLOR_CYAN, COLOR_BLUE),
+            highlight: colors(COLOR_BLUE, COLOR_CYAN),
+            header: colors(COLOR_WHITE, COLOR_BLUE),
+            done: colors(COLOR_GREEN, COLOR_BLUE),
+            overdue: colors(COLOR_RED, COLOR_BLUE),
+            today: colors(COLOR_YELLOW, COLOR_BLUE),
+            priority_a: colors(COLOR_MAGENTA, COLOR_BLUE),
+            priority_b: colors(COLOR_WHITE, COLOR_BLUE),
+            priority_c: colors(COLOR_BLACK, COLOR_BLUE),
+            stale: colors(COLOR_RED, COLOR_BLUE),
+            in_progress: colors(COLOR_GREEN, COLOR_BLUE),
+            waiting: colors(COLOR_WHITE, COLOR_BLUE),
+            cancelled: colors(COLOR_RED, COLOR_BLUE),
+            pinned: colors(COLOR_YELLOW, COLOR_BLUE),
+            status: colors(COLOR_BLUE, COLOR_WHITE),
+            error: colors(COLOR_WHITE, COLOR_RED),
+            matched: colors(COLOR_BLACK, COLOR_YELLOW),
+            selection: colors(COLOR_BLACK, COLOR_WHITE),
+            stripe: colors(-1, 25),
+            accessible: false,
+        },
+    ),
+    (
+        "high-contrast",
+        Theme {
+            regular: colors(COLOR_WHITE, COLOR_BLACK),
+            highlight: colors(COLOR_BLACK, COLOR_WHITE),
+            header: colors(COLOR_WHITE, COLOR_BLACK),
+            done: colors(COLOR_WHITE, COLOR_BLACK),
+            overdue: colors(COLOR_YELLOW, COLOR_BLACK),
+            today: colors(COLOR_YELLOW, COLOR_BLACK),
+            priority_a: colors(COLOR_WHITE, COLOR_BLACK),
+            priority_b: colors(COLOR_WHITE, COLOR_BLACK),
+            priority_c: colors(COLOR_WHITE, COLOR_BLACK),
+            stale: colors(COLOR_WHITE, COLOR_BLACK),
+            in_progress: colors(COLOR_WHITE, COLOR_BLACK),
+            waiting: colors(COLOR_WHITE, COLOR_BLACK),
+            cancelled: colors(COLOR_WHITE, COLOR_BLACK),
+            pinned: colors(COLOR_WHITE, COLOR_BLACK),
+            status: colors(COLOR_BLACK, COLOR_WHITE),
+            error: colors(COLOR_BLACK, COLOR_YELLOW),
+            matched: colors(COLOR_BLACK, COLOR_YELLOW),
+            selection: colors(COLOR_BLACK, COLOR_CYAN),
+            stripe: colors(-1, COLOR_BLACK),
+            accessible: true,
+        },
+    ),
+];
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0].1
+    }
+}
+
+impl Theme {
+    pub fn builtin(name: &str) -> Option<Self> {
+        THEMES
+            .iter()
+            .find(|(theme, _)| *theme == name)
+            .map(|(_, theme)| *theme)
+    }
+
+    pub fn role_mut(&mut self, role: &str) -> Option<&mut Colors> {
+        match role {
+            "regular" => Some(&mut self.regular),
+            "highlight" => Some(&mut self.highlight),
+            "header" => Some(&mut self.header),
+            "done" => Some(&mut self.done),
+            "overdue" => Some(&mut self.overdue),
+            "today" => Some(&mut self.today),
+            "priority_a" => Some(&mut self.priority_a),
+            "priority_b" => Some(&mut self.priority_b),
+            "priority_c" => Some(&mut self.priority_c),
+            "stale" => Some(&mut self.stale),
+            "in_progress" => Some(&mut self.in_progress),
+            "waiting" => Some(&mut self.waiting),
+            "cancelled" => Some(&mut self.cancelled),
+            "pinned" => Some(&mut self.pinned),
+            "status" => Some(&mut self.status),
+            "error" => Some(&mut self.error),
+            "match" => Some(&mut self.matched),
+            "selection" => Some(&mut self.selection),
+            "stripe" => Some(&mut self.stripe),
+            _ => None,
+        }
+    }
+
+    // has to run after start_color(), picks the closest colors the terminal has; `columns`
+    // are the colors of the custom columns of the board
+    pub fn apply(&self, columns: &[Colors]) {
+        use_default_colors();
+        let count = COLORS();
+        for (pair, (fg, bg)) in [
+            (REGULAR_PAIR, self.regular),
+            (HIGHLIGHT_PAIR, self.highlight),
+            (HEADER_PAIR, self.header),
+            (DONE_PAIR, self.done),
+            (OVERDUE_PAIR, self.overdue),
+            (TODAY_PAIR, self.today),
+            (PRIORITY_A_PAIR, self.priority_a),
+            (PRIORITY_B_PAIR, self.priority_b),
+            (PRIORITY_C_PAIR, self.priority_c),
+            (STALE_PAIR, self.stale),
+            (IN_PROGRESS_PAIR, self.in_progress),
+            (WAITING_PAIR, self.waiting),
+            (CANCELLED_PAIR, self.cancelled),
+            (PINNED_PAIR, self.pinned),
+            (STATUS_PAIR, self.status),
+            (ERROR_PAIR, self.error),
+            (MATCH_PAIR, self.matched),
+            (SELECTION_PAIR, self.selection),
+        ]
+        .into_iter()
+        .chain(
+            columns
+                .iter()
+                .enumerate()
+                .map(|(index, colors)| (COLUMN_PAIR + index as i16, *colors)),
+        ) {
+            let (fg, bg) = (fg.number(count), bg.number(count));
+            init_extended_pair(pair.into(), fg, bg);
+            init_extended_pair(
+                (pair + STRIPED_OFFSET).into(),
+                fg,
+                self.stripe.1.number(count),
+            );
+        }
+        bkgd(COLOR_PAIR(REGULAR_PAIR));
+    }
+}
+
+fn parse_color(name: &str) -> Option<Color> {
+    let color = match name {
+        "default" => -1,
+        "black" => COLOR_BLACK,
+        "red" => COLOR_RED,
+        "green" => COLOR_GREEN,
+        "yellow" => COLOR_YELLOW,
+        "blue" => COLOR_BLUE,
+        "magenta" => COLOR_MAGENTA,
+        "cyan" => COLOR_CYAN,
+        "white" => COLOR_WHITE,
+        // `#rrggbb`
+        hex if hex.starts_with('#') => {
+            let hex = hex.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+            let channel = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+            return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        // a number of the terminal's palette, e.g. 236 for a dark gray
+        number => return number.parse().ok().map(Color::Index),
+    };
+    Some(Color::of(color))
+}
+
+// "fg" or "fg on bg", e.g. "black on yellow", "245" or "#ffaf00 on default"
+pub fn parse_colors(value: &str) -> Option<Colors> {
+    let value = value.to_ascii_lowercase();
+    let (fg, bg) = match value.split_once(" on ") {
+        Some((fg, bg)) => (fg.trim(), bg.trim()),
+        None => (value.trim(), "default"),
+    };
+    Some((parse_color(fg)?, parse_color(bg)?))
+}
@@ -0,0 +1,36 @@
+use crate::item::Item;
+use std::time::Instant;
+
+// time being spent on one todo, toggled with `t` and added to the item when stopped
+pub struct Tracker {
+    // the list and the item the time is logged against
+    pub list: usize,
+    pub item: Item,
+    started: Instant,
+}
+
+impl Tracker {
+    pub fn start(list: usize, item: Item) -> Self {
+        Self {
+            list,
+            item,
+            started: Instant::now(),
+        }
+    }
+
+    // the tracked time rounded to the nearest minute
+    pub fn minutes(&self) -> u32 {
+        ((self.started.elapsed().as_secs() + 30) / 60) as u32
+    }
+
+    // the running clock shown in the status bar, e.g. `tracking 1:05:09`
+    pub fn describe(&self) -> String {
+        let seconds = self.started.elapsed().as_secs();
+        format!(
+            "tracking {}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+}
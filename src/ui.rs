@@ -0,0 +1,1046 @@
+use crate::dates::DateFormat;
+use crate::icons::Icons;
+use crate::macros::Macros;
+use crate::markup::{self, Style};
+use ncurses::*;
+use std::os::raw::c_int;
+use std::time::{Duration, Instant};
+
+pub const REGULAR_PAIR: i16 = 1;
+pub const HIGHLIGHT_PAIR: i16 = 2;
+pub const STATUS_PAIR: i16 = 3;
+pub const ERROR_PAIR: i16 = 4;
+pub const MATCH_PAIR: i16 = 5;
+pub const SELECTION_PAIR: i16 = 6;
+pub const HEADER_PAIR: i16 = 7;
+pub const DONE_PAIR: i16 = 8;
+pub const OVERDUE_PAIR: i16 = 9;
+pub const TODAY_PAIR: i16 = 10;
+pub const PRIORITY_A_PAIR: i16 = 11;
+pub const PRIORITY_B_PAIR: i16 = 12;
+pub const PRIORITY_C_PAIR: i16 = 13;
+pub const STALE_PAIR: i16 = 14;
+pub const IN_PROGRESS_PAIR: i16 = 15;
+pub const WAITING_PAIR: i16 = 16;
+pub const CANCELLED_PAIR: i16 = 17;
+pub const PINNED_PAIR: i16 = 18;
+// the custom columns of the board take the pairs from here on, up to the striped ones
+pub const COLUMN_PAIR: i16 = 19;
+pub const MAX_COLUMNS: usize = (STRIPED_OFFSET - COLUMN_PAIR) as usize;
+// every other list row uses the pair this far above its own, with the stripe background
+pub const STRIPED_OFFSET: i16 = 32;
+
+const PROMPT_WIDTH: i32 = 60;
+// the footer while typing into a prompt
+const EDIT_HINTS: &[(&str, &str)] = &[
+    ("ENTER", "done"),
+    ("ESC", "cancel"),
+    ("LEFT/RIGHT", "move"),
+    ("Ctrl-A", "start"),
+    ("Ctrl-E", "end"),
+    ("DEL", "delete"),
+];
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(3);
+const TOAST_TIMEOUT: Duration = Duration::from_secs(5);
+const TOAST_WIDTH: usize = 40;
+// toasts on screen at once, the rest wait their turn
+const MAX_TOASTS: usize = 3;
+
+pub const KEY_ESCAPE: i32 = 27;
+pub const KEY_DELETE: i32 = 127;
+pub const KEY_CTRL_N: i32 = 14;
+pub const KEY_CTRL_P: i32 = 16;
+pub const KEY_CTRL_R: i32 = 18;
+
+// characters outside ASCII are shifted above every ncurses key code, so a key
+// can keep travelling through the app as a single i32
+const WIDE_CHAR_OFFSET: i32 = 0x0100_0000;
+
+type Id = usize;
+
+extern "C" {
+    fn wcwidth(ch: i32) -> c_int;
+}
+
+fn read_key() -> i32 {
+    match get_wch() {
+        Some(WchResult::KeyCode(code)) => code,
+        Some(WchResult::Char(ch)) if ch < 0x80 => ch as i32,
+        Some(WchResult::Char(ch)) => ch as i32 + WIDE_CHAR_OFFSET,
+        None => ERR,
+    }
+}
+
+// the printable character a key stands for, if any
+pub fn key_char(key: i32) -> Option<char> {
+    match key {
+        32..=126 => Some(key as u8 as char),
+        _ if key >= WIDE_CHAR_OFFSET => char::from_u32((key - WIDE_CHAR_OFFSET) as u32),
+        _ => None,
+    }
+}
+
+// terminal columns taken by the character, 2 for CJK and most emoji, 0 for combining marks
+pub fn char_width(ch: char) -> usize {
+    if ch.is_ascii() {
+        return usize::from(!ch.is_ascii_control());
+    }
+    unsafe { wcwidth(ch as i32) }.max(0) as usize
+}
+
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+// continuation rows of a wrapped list element line up after the `[ ] ` checkbox
+const WRAP_INDENT: &str = "    ";
+// in front of the element under the cursor when `accessible` is on
+const CURSOR_MARKER: &str = "> ";
+const ELLIPSIS: char = '…';
+
+// the longest prefix of `text` fitting in `width` columns
+pub fn truncate(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, ch) in text.char_indices() {
+        used += char_width(ch);
+        if used > width {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+// `text` cut down to `width` columns, ending in an ellipsis when anything was cut
+pub fn ellipsize(text: &str, width: usize) -> String {
+    if text_width(text) <= width {
+        return text.to_string();
+    }
+    let mut short = truncate(text, width.saturating_sub(1)).to_string();
+    if width > 0 {
+        short.push(ELLIPSIS);
+    }
+    short
+}
+
+// splits `text` into rows of at most `width` columns, breaking after a space when possible
+pub fn wrap(text: &str, width: usize) -> Vec<&str> {
+    let mut rows = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = truncate(rest, width).len();
+        if end == 0 {
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+        if end < rest.len() {
+            if let Some(space) = rest[..end].rfind(' ').filter(|&space| space > 0) {
+                end = space + 1;
+            }
+        }
+        rows.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    rows
+}
+
+pub struct Message {
+    text: String,
+    error: bool,
+    created: Instant,
+}
+
+impl Message {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: false,
+            created: Instant::now(),
+        }
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: true,
+            created: Instant::now(),
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.created.elapsed() >= MESSAGE_TIMEOUT
+    }
+}
+
+// a notification in the top right corner, its timer starts once it is on screen
+pub struct Toast {
+    text: String,
+    shown: Option<Instant>,
+}
+
+impl Toast {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            shown: None,
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.shown
+            .is_some_and(|shown| shown.elapsed() >= TOAST_TIMEOUT)
+    }
+}
+
+// a column of the screen holding one list, kept across frames for scrolling and mouse hits
+#[derive(Default, Clone)]
+struct Pane {
+    top: usize,
+    col: usize,
+    len: usize,
+    scroll: usize,
+    visible: bool,
+    // the element drawn on each screen row starting at `top`
+    rows: Vec<Id>,
+    // columns in front of the elements' checkboxes, e.g. for line numbers
+    gutter: usize,
+}
+
+#[derive(Default)]
+pub struct Ui {
+    list_current: Option<Id>,
+    panes: Vec<Pane>,
+    pane: usize,
+    focused: bool,
+    row: usize,
+    col: usize,
+    limit: usize,
+    pub scroll_margin: usize,
+    pub height: i32,
+    pub width: i32,
+    pub search: Option<String>,
+    pub selection: Option<(Id, Id)>,
+    // wrap the selected element over several rows instead of cutting it off
+    pub expand: bool,
+    // columns the selected element is scrolled to the left by when it isn't wrapped,
+    // at most as far as its end
+    pub shift: usize,
+    // render `*bold*`, `_underline_` and `code` in list elements
+    pub styling: bool,
+    // give every other list row the stripe background
+    pub zebra: bool,
+    // set rows apart with bold and reverse and mark the cursor with `>` too, for
+    // monochrome terminals and colorblind users
+    pub accessible: bool,
+    pub icons: Icons,
+    pub dates: DateFormat,
+    // TODO items pending for longer than this many days are flagged, never with 0
+    pub stale_days: i64,
+    // the row of key hints above the status bar
+    pub footer: bool,
+    // shown at the right end of the status bar, e.g. a countdown or a spinner
+    pub indicator: Option<String>,
+    pub macros: Macros,
+}
+
+impl Ui {
+    // every key goes through here so macros can record and replay it
+    pub fn read_key(&mut self) -> i32 {
+        self.macros.next_key(read_key)
+    }
+
+    pub fn resize(&mut self) {
+        getmaxyx(stdscr(), &mut self.height, &mut self.width);
+    }
+
+    pub fn begin(&mut self, row: usize, col: usize) {
+        self.row = row;
+        self.col = col;
+        self.limit = (self.width.max(0) as usize).saturating_sub(col);
+        for pane in self.panes.iter_mut() {
+            pane.visible = false;
+        }
+    }
+
+    pub fn begin_pane(&mut self, pane: usize, row: usize, col: usize, width: usize, focused: bool) {
+        if self.panes.len() <= pane {
+            self.panes.resize(pane + 1, Pane::default());
+        }
+        self.pane = pane;
+        self.focused = focused;
+        self.row = row;
+        self.col = col;
+        self.limit = width;
+    }
+
+    // `current_rows` is how many rows the element `id` takes, see `element_rows`
+    pub fn begin_list(&mut self, id: Id, len: usize, current_rows: usize) {
+        assert!(self.list_current.is_none(), "NESTED LISTS -> NOT ALLOWED");
+        self.list_current = Some(id);
+        let height = self.list_height(self.row);
+        let margin = self.scroll_margin;
+        let pane = &mut self.panes[self.pane];
+        pane.top = self.row;
+        pane.col = self.col;
+        pane.len = len;
+        pane.visible = true;
+        pane.rows.clear();
+        let extra = current_rows.saturating_sub(1).min(height.saturating_sub(1));
+        pane.scroll_to(id, height - extra, margin);
+    }
+
+    // see `Pane::gutter`, set after `begin_list`
+    pub fn set_gutter(&mut self, width: usize) {
+        self.panes[self.pane].gutter = width;
+    }
+
+    // rows the focused, selected element with this label is drawn on
+    pub fn element_rows(&self, label: &str) -> usize {
+        if self.expand && self.focused {
+            self.wrap_element(&self.marked(label, true)).len().max(1)
+        } else {
+            1
+        }
+    }
+
+    fn wrap_element<'a>(&self, label: &'a str) -> Vec<&'a str> {
+        wrap(label, self.limit.saturating_sub(WRAP_INDENT.len()).max(1))
+    }
+
+    // rows left for a list starting at `top`, keeping the bottom line for the status bar
+    // and the one above it for the footer
+    fn list_height(&self, top: usize) -> usize {
+        let reserved = 1 + self.footer as usize;
+        (self.height.max(0) as usize)
+            .saturating_sub(top + reserved)
+            .max(1)
+    }
+
+    // maps a screen position to the pane and list element rendered there
+    pub fn list_hit(&self, row: i32, col: i32) -> Option<(usize, Id)> {
+        let row = usize::try_from(row).ok()?;
+        let col = usize::try_from(col).ok()?;
+        self.panes
+            .iter()
+            .enumerate()
+            .filter(|(_, pane)| pane.visible && row >= pane.top && col >= pane.col)
+            .max_by_key(|(_, pane)| pane.col)
+            .and_then(|(index, pane)| Some((index, *pane.rows.get(row - pane.top)?)))
+    }
+
+    // the label behind the cursor marker when the rows are set apart without colors
+    fn marked(&self, label: &str, current: bool) -> String {
+        match (self.accessible, current) {
+            (false, _) => label.to_string(),
+            (true, false) => " ".repeat(CURSOR_MARKER.len()) + label,
+            (true, true) => format!("{}{}", CURSOR_MARKER, label),
+        }
+    }
+
+    // whether the column falls on the `[ ]` checkbox of a list element in the pane
+    pub fn on_checkbox(&self, pane: usize, col: i32) -> bool {
+        let marker = if self.accessible {
+            CURSOR_MARKER.len()
+        } else {
+            0
+        };
+        let start = (self.panes[pane].col + self.panes[pane].gutter + marker) as i32;
+        (start..start + text_width(&self.icons.todo) as i32).contains(&col)
+    }
+
+    pub fn list_element(&mut self, label: &str, id: Id, pair: i16) -> bool {
+        let id_current = self
+            .list_current
+            .expect("LIST ELEMENTS -> NOT ALLOWED TO CREATE ELEMENT OUTSIDE OF LIST");
+
+        let pane = &self.panes[self.pane];
+        let bottom = pane.top + self.list_height(pane.top);
+        if id < pane.scroll || self.row >= bottom {
+            return false;
+        }
+
+        let current = id_current == id && self.focused;
+        let selected = self.focused
+            && self
+                .selection
+                .is_some_and(|(start, end)| (start..=end).contains(&id));
+        let striped = self.zebra && id % 2 == 1 && !current && !selected;
+        let pair = if current {
+            HIGHLIGHT_PAIR
+        } else if selected {
+            SELECTION_PAIR
+        } else if striped {
+            pair + STRIPED_OFFSET
+        } else {
+            pair
+        };
+
+        let label = &self.marked(label, current);
+        if current && self.expand {
+            for (index, row) in self.wrap_element(label).into_iter().enumerate() {
+                if self.row >= bottom {
+                    break;
+                }
+                let indent = if index == 0 { "" } else { WRAP_INDENT };
+                self.panes[self.pane].rows.push(id);
+                self.styled_label(&format!("{}{}", indent, row), pair, striped);
+            }
+        } else if current && self.shift > 0 {
+            let overflow = text_width(label).saturating_sub(self.limit.saturating_sub(1));
+            self.shift = self.shift.min(overflow);
+            let hidden = truncate(label, self.shift).len();
+            let label = format!("{}{}", ELLIPSIS, &label[hidden..]);
+            self.panes[self.pane].rows.push(id);
+            self.styled_label(&label, pair, striped);
+        } else {
+            self.panes[self.pane].rows.push(id);
+            self.styled_label(label, pair, striped);
+        }
+
+        false
+    }
+
+    // a row of a list element with its markup turned into attributes, see `markup::parse`,
+    // its colors reaching the edge of the pane when `fill` is set
+    fn styled_label(&mut self, text: &str, pair: i16, fill: bool) {
+        let (plain, spans) = if self.styling {
+            markup::parse(text)
+        } else {
+            (text.to_string(), Vec::new())
+        };
+        let mut shown = ellipsize(&plain, self.limit);
+        if fill {
+            shown.push_str(&" ".repeat(self.limit.saturating_sub(text_width(&shown))));
+        }
+        self.label(&shown, pair);
+        let row = self.row as i32 - 1;
+        for (start, end, style) in spans {
+            let col = text_width(&plain[..start]);
+            if col >= self.limit {
+                break;
+            }
+            let attributes = match style {
+                Style::Bold => A_BOLD(),
+                Style::Underline => A_UNDERLINE(),
+                Style::Code => A_REVERSE(),
+            };
+            let width = text_width(&plain[start..end]).min(self.limit - col);
+            mvchgat(
+                row,
+                (self.col + col) as i32,
+                width as i32,
+                attributes | self.style(pair),
+                pair,
+            );
+        }
+        self.highlight_matches(&plain);
+    }
+
+    // marks search matches in the row just drawn
+    fn highlight_matches(&self, label: &str) {
+        let Some(query) = self.search.as_deref().filter(|query| !query.is_empty()) else {
+            return;
+        };
+        let row = self.row as i32 - 1;
+        let haystack = label.to_ascii_lowercase();
+        let needle = query.to_ascii_lowercase();
+        for (start, _) in haystack.match_indices(&needle) {
+            let col = text_width(&label[..start]);
+            if col >= self.limit {
+                break;
+            }
+            let width = text_width(&label[start..start + needle.len()]);
+            mvchgat(
+                row,
+                (self.col + col) as i32,
+                width.min(self.limit - col) as i32,
+                self.style(MATCH_PAIR),
+                MATCH_PAIR,
+            );
+        }
+    }
+
+    pub fn end_list(&mut self) {
+        self.list_current = None;
+    }
+
+    // the line under a header
+    pub fn rule(&mut self) {
+        let rule = self.icons.rule();
+        self.label(&rule, HEADER_PAIR);
+    }
+
+    // the attributes drawing with `pair`, which set it apart without colors too when
+    // `accessible` is on
+    fn style(&self, pair: i16) -> attr_t {
+        let attributes = match pair % STRIPED_OFFSET {
+            _ if !self.accessible => A_NORMAL(),
+            HIGHLIGHT_PAIR | STATUS_PAIR | ERROR_PAIR => A_REVERSE() | A_BOLD(),
+            SELECTION_PAIR => A_REVERSE(),
+            MATCH_PAIR => A_UNDERLINE() | A_BOLD(),
+            HEADER_PAIR | OVERDUE_PAIR | PRIORITY_A_PAIR | IN_PROGRESS_PAIR | PINNED_PAIR => {
+                A_BOLD()
+            }
+            TODAY_PAIR | STALE_PAIR => A_UNDERLINE(),
+            DONE_PAIR | WAITING_PAIR | CANCELLED_PAIR => A_DIM(),
+            _ => A_NORMAL(),
+        };
+        COLOR_PAIR(pair) | attributes
+    }
+
+    pub fn label(&mut self, text: &str, pair: i16) {
+        mv(self.row as i32, self.col as i32);
+        attron(self.style(pair));
+        addstr(truncate(text, self.limit));
+        attroff(self.style(pair));
+        self.row += 1;
+    }
+
+    pub fn tab_bar(&mut self, labels: &[String], active: usize) {
+        mv(self.row as i32, self.col as i32);
+        let mut left = self.limit;
+        for (index, label) in labels.iter().enumerate() {
+            let tab = format!(" {} ", label);
+            let pair = if index == active {
+                HIGHLIGHT_PAIR
+            } else {
+                HEADER_PAIR
+            };
+            attron(self.style(pair));
+            addstr(truncate(&tab, left));
+            attroff(self.style(pair));
+            left = left.saturating_sub(text_width(&tab) + 1);
+            if left == 0 {
+                break;
+            }
+            addstr(" ");
+        }
+        self.row += 1;
+    }
+
+    // a column on the right of the lists, separated by a vertical line; long lines are wrapped
+    // `lines` wrapped to the pane, the `highlight`th of them in the selection colors
+    pub fn detail_pane(
+        &mut self,
+        top: usize,
+        col: usize,
+        width: usize,
+        lines: &[String],
+        highlight: Option<usize>,
+    ) {
+        let height = self.list_height(top);
+        let inner = width.saturating_sub(2).max(1);
+        mvvline(top as i32, col as i32, ACS_VLINE(), height as i32);
+        let rows = lines.iter().enumerate().flat_map(|(index, line)| {
+            let rows = if line.is_empty() {
+                vec![""]
+            } else {
+                wrap(line, inner)
+            };
+            rows.into_iter().map(move |row| (index, row))
+        });
+        for (row, (index, text)) in rows.take(height).enumerate() {
+            let pair = if highlight == Some(index) {
+                HIGHLIGHT_PAIR
+            } else {
+                REGULAR_PAIR
+            };
+            attron(self.style(pair));
+            mvaddstr((top + row) as i32, (col + 2) as i32, truncate(text, inner));
+            attroff(self.style(pair));
+        }
+    }
+
+    // centered box asking for a line of text, `None` when cancelled with ESC or left empty
+    pub fn prompt(&mut self, title: &str, initial: &str) -> Option<String> {
+        self.prompt_with(title, initial, |_| None, |_| false)
+    }
+
+    // `prompt` showing what `preview` makes of the text typed so far under it, with the
+    // words `highlight` accepts in bold
+    pub fn prompt_with(
+        &mut self,
+        title: &str,
+        initial: &str,
+        preview: impl Fn(&str) -> Option<String>,
+        highlight: impl Fn(&str) -> bool,
+    ) -> Option<String> {
+        let mut input = LineEdit::default();
+        input.start(initial);
+        curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+        let text = loop {
+            self.draw_prompt(title, &input, preview(input.text()).as_deref(), &highlight);
+            refresh();
+            match self.read_key() {
+                ERR => {}
+                KEY_RESIZE => self.resize(),
+                KEY_ESCAPE => break None,
+                10 | KEY_ENTER => break input.take(),
+                key => input.handle_key(key),
+            }
+        };
+        curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+        text
+    }
+
+    fn draw_prompt(
+        &self,
+        title: &str,
+        input: &LineEdit,
+        preview: Option<&str>,
+        highlight: &dyn Fn(&str) -> bool,
+    ) {
+        self.footer_bar(EDIT_HINTS);
+        let width = (self.width - 4).clamp(0, PROMPT_WIDTH);
+        let (top, left) = self.centered(5, width);
+        draw_box(top, left, 5, width);
+        mvaddstr(top, left + 2, title);
+        if let Some(preview) = preview {
+            attron(self.style(HEADER_PAIR));
+            mvaddstr(
+                top + 3,
+                left + 4,
+                truncate(preview, (width - 6).max(0) as usize),
+            );
+            attroff(self.style(HEADER_PAIR));
+        }
+
+        // scroll the text horizontally so the cursor stays inside the box
+        let room = (width - 6).max(1) as usize;
+        let text = input.text();
+        let mut start = 0;
+        while text_width(&text[start..input.cursor]) >= room {
+            start += text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        mvaddstr(top + 2, left + 2, "> ");
+        addstr(truncate(&text[start..], room));
+        let mut offset = start;
+        for word in text[start..].split(' ') {
+            let col = text_width(&text[start..offset]);
+            if col >= room {
+                break;
+            }
+            if highlight(word) {
+                let width = text_width(word).min(room - col);
+                mvchgat(
+                    top + 2,
+                    left + 4 + col as i32,
+                    width as i32,
+                    A_BOLD() | self.style(HEADER_PAIR),
+                    HEADER_PAIR,
+                );
+            }
+            offset += word.len() + 1;
+        }
+        mv(
+            top + 2,
+            left + 4 + text_width(&text[start..input.cursor]) as i32,
+        );
+    }
+
+    // centered yes/no question, anything but `y` counts as no
+    pub fn confirm(&mut self, question: &str) -> bool {
+        loop {
+            let text = format!("{} (y/n)", question);
+            let width = (text_width(&text) as i32 + 4).min(self.width);
+            let (top, left) = self.centered(3, width);
+            draw_box(top, left, 3, width);
+            mvaddstr(
+                top + 1,
+                left + 2,
+                truncate(&text, (width - 4).max(0) as usize),
+            );
+            refresh();
+            match self.read_key() {
+                ERR => {}
+                KEY_RESIZE => self.resize(),
+                key => break key == 'y' as i32 || key == 'Y' as i32,
+            }
+        }
+    }
+
+    // centered list of options, picked with j/k and ENTER or by the first character of
+    // an option; `None` when cancelled with ESC
+    pub fn menu(&mut self, title: &str, options: &[&str]) -> Option<usize> {
+        let mut selected = 0;
+        loop {
+            let inner = options
+                .iter()
+                .map(|option| text_width(option))
+                .chain([text_width(title)])
+                .max()
+                .unwrap_or(0);
+            let width = (inner as i32 + 6).min(self.width);
+            let height = options.len() as i32 + 4;
+            let (top, left) = self.centered(height, width);
+            draw_box(top, left, height, width);
+            mvaddstr(top, left + 2, title);
+            for (index, option) in options.iter().enumerate() {
+                let pair = if index == selected {
+                    HIGHLIGHT_PAIR
+                } else {
+                    REGULAR_PAIR
+                };
+                attron(self.style(pair));
+                mvaddstr(top + 2 + index as i32, left + 3, option);
+                attroff(self.style(pair));
+            }
+            refresh();
+
+            match self.read_key() {
+                ERR => {}
+                KEY_RESIZE => self.resize(),
+                KEY_ESCAPE => return None,
+                10 | KEY_ENTER => return Some(selected),
+                KEY_UP => selected = selected.saturating_sub(1),
+                KEY_DOWN => selected = (selected + 1).min(options.len().saturating_sub(1)),
+                key if key == 'k' as i32 => selected = selected.saturating_sub(1),
+                key if key == 'j' as i32 => {
+                    selected = (selected + 1).min(options.len().saturating_sub(1))
+                }
+                key => {
+                    let hotkey = key_char(key);
+                    if let Some(index) = options
+                        .iter()
+                        .position(|option| option.chars().next() == hotkey)
+                    {
+                        return Some(index);
+                    }
+                }
+            }
+        }
+    }
+
+    fn centered(&self, height: i32, width: i32) -> (i32, i32) {
+        (
+            ((self.height - height) / 2).max(0),
+            ((self.width - width) / 2).max(0),
+        )
+    }
+
+    // single line input at the bottom of the screen, vim style
+    pub fn command_line(&mut self, prefix: &str, input: &LineEdit) {
+        let row = self.height - 1;
+        mv(row, 0);
+        clrtoeol();
+        addstr(prefix);
+        addstr(&input.buffer);
+        mv(row, (text_width(prefix) + input.cursor_width()) as i32);
+    }
+
+    // the completions of the command line, on the row above it
+    pub fn wildmenu(&mut self, items: &[String]) {
+        let row = self.height - 2;
+        attron(self.style(STATUS_PAIR));
+        mvhline(row, 0, ' ' as chtype, self.width);
+        mvaddstr(
+            row,
+            0,
+            truncate(&items.join("  "), self.width.max(0) as usize),
+        );
+        attroff(self.style(STATUS_PAIR));
+    }
+
+    // `(keys, what they do)` pairs on the row above the status bar, as many as fit
+    pub fn footer_bar(&self, hints: &[(&str, &str)]) {
+        if !self.footer {
+            return;
+        }
+        let row = self.height - 2;
+        mv(row, 0);
+        clrtoeol();
+        let mut col = 0;
+        for (keys, action) in hints {
+            let width = (text_width(keys) + text_width(action) + 3) as i32;
+            if col + width > self.width {
+                break;
+            }
+            attron(self.style(HEADER_PAIR) | A_BOLD());
+            mvaddstr(row, col, keys);
+            attroff(self.style(HEADER_PAIR) | A_BOLD());
+            attron(self.style(REGULAR_PAIR));
+            addstr(&format!(" {}", action));
+            attroff(self.style(REGULAR_PAIR));
+            col += width;
+        }
+    }
+
+    pub fn status_bar(&mut self, hint: &str, message: Option<&Message>) {
+        let row = self.height - 1;
+        let (text, pair) = match message {
+            Some(message) if message.error => (message.text.as_str(), ERROR_PAIR),
+            Some(message) => (message.text.as_str(), STATUS_PAIR),
+            None => (hint, STATUS_PAIR),
+        };
+        attron(self.style(pair));
+        mvhline(row, 0, ' ' as chtype, self.width);
+        mvaddstr(row, 0, text);
+        attroff(self.style(pair));
+        if let Some(indicator) = &self.indicator {
+            let col = self.width - text_width(indicator) as i32 - 1;
+            if col > text_width(text) as i32 {
+                attron(self.style(STATUS_PAIR));
+                mvaddstr(row, col, indicator);
+                attroff(self.style(STATUS_PAIR));
+            }
+        }
+    }
+
+    pub fn overlay(&mut self, title: &str, lines: &[String]) {
+        let inner = lines
+            .iter()
+            .map(|line| text_width(line))
+            .chain([text_width(title)])
+            .max()
+            .unwrap_or(0);
+        let width = (inner + 4) as i32;
+        let height = (lines.len() + 4) as i32;
+        let (top, left) = self.centered(height, width);
+
+        draw_box(top, left, height, width);
+
+        mvaddstr(top, left + 2, title);
+        for (index, line) in lines.iter().enumerate() {
+            mvaddstr(top + 2 + index as i32, left + 2, line);
+        }
+    }
+
+    // `text` alone in the middle of the screen, bold with its letters spread apart to stand
+    // out, and `detail` under it
+    pub fn zen(&mut self, text: &str, detail: &str) {
+        let width = (self.width.max(0) as usize / 2).max(1);
+        let rows: Vec<String> = wrap(text, width)
+            .into_iter()
+            .map(|row| {
+                let mut spaced: String = row.trim_end().chars().flat_map(|ch| [ch, ' ']).collect();
+                spaced.pop();
+                spaced
+            })
+            .collect();
+        let height = rows.len() as i32 * 2 + 1;
+        let top = ((self.height - height) / 2).max(0);
+        let left = |text: &str| ((self.width - text_width(text) as i32) / 2).max(0);
+
+        attron(A_BOLD());
+        for (index, row) in rows.iter().enumerate() {
+            mvaddstr(top + index as i32 * 2, left(row), row);
+        }
+        attroff(A_BOLD());
+        attron(self.style(HEADER_PAIR));
+        mvaddstr(top + height, left(detail), detail);
+        attroff(self.style(HEADER_PAIR));
+    }
+
+    // a month grid starting on monday; `counts` holds how many items are due on each day,
+    // `first_weekday` is the column of the 1st, 0 being monday, and days count from 1
+    pub fn calendar(
+        &mut self,
+        title: &str,
+        counts: &[usize],
+        first_weekday: usize,
+        selected: usize,
+        today: Option<usize>,
+    ) {
+        const CELL: usize = 5;
+        let weeks = (first_weekday + counts.len()).div_ceil(7);
+        let width = (7 * CELL + 3) as i32;
+        let height = (weeks + 5) as i32;
+        let (top, left) = self.centered(height, width);
+
+        draw_box(top, left, height, width);
+        mvaddstr(top, left + 2, title);
+        attron(self.style(HEADER_PAIR));
+        mvaddstr(top + 2, left + 2, " Mo   Tu   We   Th   Fr   Sa   Su");
+        attroff(self.style(HEADER_PAIR));
+
+        for (index, &count) in counts.iter().enumerate() {
+            let day = index + 1;
+            let cell = first_weekday + index;
+            let row = top + 3 + (cell / 7) as i32;
+            let col = left + 2 + (cell % 7 * CELL) as i32;
+            let marker = match count {
+                0 => String::new(),
+                1..=9 => format!("·{}", count),
+                _ => "·+".to_string(),
+            };
+            let pair = if day == selected {
+                HIGHLIGHT_PAIR
+            } else if Some(day) == today {
+                TODAY_PAIR
+            } else {
+                REGULAR_PAIR
+            };
+            let attributes = if count > 0 { A_BOLD() } else { A_NORMAL() };
+            attron(self.style(pair) | attributes);
+            mvaddstr(row, col, &format!("{:>3}{}", day, marker));
+            attroff(self.style(pair) | attributes);
+        }
+    }
+
+    // draws the first few toasts of the queue stacked in the top right corner
+    pub fn toasts(&mut self, toasts: &mut [Toast]) {
+        let width = TOAST_WIDTH.min(self.width.max(0) as usize);
+        let left = self.width - width as i32;
+        for (index, toast) in toasts.iter_mut().take(MAX_TOASTS).enumerate() {
+            toast.shown.get_or_insert_with(Instant::now);
+            let top = index as i32 * 3;
+            draw_box(top, left, 3, width as i32);
+            attron(self.style(STATUS_PAIR));
+            mvaddstr(
+                top + 1,
+                left + 1,
+                &format!(
+                    " {:<1$}",
+                    ellipsize(&toast.text, width.saturating_sub(4)),
+                    width.saturating_sub(3)
+                ),
+            );
+            attroff(self.style(STATUS_PAIR));
+        }
+    }
+
+    pub fn picker(&mut self, title: &str, input: &LineEdit, items: &[String], selected: usize) {
+        let width = (self.width - 4).clamp(0, 64);
+        let height = (self.height - 4).clamp(0, items.len() as i32 + 4).max(5);
+        let (top, left) = self.centered(height, width);
+        let inner = (width - 4).max(0);
+
+        draw_box(top, left, height, width);
+        mvaddstr(top, left + 2, title);
+
+        let rows = (height - 4).max(0) as usize;
+        let first = (selected + 1).saturating_sub(rows);
+        for (row, (index, item)) in items.iter().enumerate().skip(first).take(rows).enumerate() {
+            let pair = if index == selected {
+                HIGHLIGHT_PAIR
+            } else {
+                REGULAR_PAIR
+            };
+            attron(self.style(pair));
+            mvaddstr(
+                top + 3 + row as i32,
+                left + 2,
+                truncate(item, inner as usize),
+            );
+            attroff(self.style(pair));
+        }
+
+        mvaddstr(top + 1, left + 2, "> ");
+        let room = (inner - 2).max(0) as usize;
+        addstr(truncate(input.text(), room));
+        mv(top + 1, left + 4 + input.cursor_width().min(room) as i32);
+    }
+
+    pub fn end(&mut self) {}
+}
+
+impl Pane {
+    fn scroll_to(&mut self, id: Id, height: usize, margin: usize) {
+        let margin = margin.min(height.saturating_sub(1) / 2);
+        if id < self.scroll + margin {
+            self.scroll = id.saturating_sub(margin);
+        }
+        if id + margin >= self.scroll + height {
+            self.scroll = id + margin + 1 - height;
+        }
+        self.scroll = self.scroll.min(self.len.saturating_sub(height));
+    }
+}
+
+fn draw_box(top: i32, left: i32, height: i32, width: i32) {
+    for row in top..top + height {
+        mvhline(row, left, ' ' as chtype, width);
+    }
+    mvhline(top, left, ACS_HLINE(), width);
+    mvhline(top + height - 1, left, ACS_HLINE(), width);
+    mvvline(top, left, ACS_VLINE(), height);
+    mvvline(top, left + width - 1, ACS_VLINE(), height);
+    mvaddch(top, left, ACS_ULCORNER());
+    mvaddch(top, left + width - 1, ACS_URCORNER());
+    mvaddch(top + height - 1, left, ACS_LLCORNER());
+    mvaddch(top + height - 1, left + width - 1, ACS_LRCORNER());
+}
+
+#[derive(Default)]
+pub struct LineEdit {
+    buffer: String,
+    cursor: usize,
+}
+
+impl LineEdit {
+    pub fn start(&mut self, text: &str) {
+        self.buffer = text.to_string();
+        self.cursor = self.buffer.len();
+    }
+
+    fn insert(&mut self, ch: char) {
+        self.buffer.insert(self.cursor, ch);
+        self.cursor += ch.len_utf8();
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.left();
+            self.buffer.remove(self.cursor);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.buffer.len() {
+            self.buffer.remove(self.cursor);
+        }
+    }
+
+    fn left(&mut self) {
+        if let Some(ch) = self.buffer[..self.cursor].chars().next_back() {
+            self.cursor -= ch.len_utf8();
+        }
+    }
+
+    fn right(&mut self) {
+        if let Some(ch) = self.buffer[self.cursor..].chars().next() {
+            self.cursor += ch.len_utf8();
+        }
+    }
+
+    fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.buffer.len();
+    }
+
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    // screen columns between the start of the text and the cursor
+    pub fn cursor_width(&self) -> usize {
+        text_width(&self.buffer[..self.cursor])
+    }
+
+    pub fn handle_key(&mut self, key: i32) {
+        match key {
+            KEY_BACKSPACE | KEY_DELETE | 8 => self.backspace(),
+            KEY_DC => self.delete(),
+            KEY_LEFT => self.left(),
+            KEY_RIGHT => self.right(),
+            KEY_HOME | 1 => self.home(),
+            KEY_END | 5 => self.end(),
+            _ => {
+                if let Some(ch) = key_char(key) {
+                    self.insert(ch);
+                }
+            }
+        }
+    }
+
+    pub fn take(&mut self) -> Option<String> {
+        let text = self.buffer.trim().to_string();
+        self.start("");
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+}
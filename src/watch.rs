@@ -0,0 +1,97 @@
+use crate::app::List;
+use crate::sqlite;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// the size and modification time of each file the lists are read from
+type Stamp = Vec<(PathBuf, u64, SystemTime)>;
+
+// the files of a directory are those `load_state` reads, the hidden ones being left out
+fn stamp(file_path: &str) -> Stamp {
+    let path = Path::new(file_path);
+    let mut files = if path.is_dir() {
+        fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| Some(entry.ok()?.path()))
+                    .filter(|entry| {
+                        !entry
+                            .file_name()
+                            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else if sqlite::is_sqlite(path) {
+        // what is written to a database goes to its write-ahead log first
+        vec![
+            path.to_path_buf(),
+            PathBuf::from(format!("{}-wal", file_path)),
+        ]
+    } else {
+        vec![path.to_path_buf()]
+    };
+    files.sort();
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let metadata = fs::metadata(&file)
+                .ok()
+                .filter(|metadata| metadata.is_file())?;
+            Some((file, metadata.len(), metadata.modified().ok()?))
+        })
+        .collect()
+}
+
+// what the files held when they were last read or written, to tell when another program
+// changes them while the lists are open
+pub struct Watch {
+    file_path: String,
+    stamp: Stamp,
+    // the lists as the files hold them, what `merge::merge` compares both sides with
+    pub base: Vec<List>,
+    // the lists a save running in the background writes
+    saving: Option<Vec<List>>,
+}
+
+impl Watch {
+    pub fn new(file_path: &str, lists: &[List]) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            stamp: stamp(file_path),
+            base: lists.to_vec(),
+            saving: None,
+        }
+    }
+
+    // the files hold `lists` now, having just been read or written
+    pub fn synced(&mut self, lists: &[List]) {
+        self.stamp = stamp(&self.file_path);
+        self.base = lists.to_vec();
+        self.saving = None;
+    }
+
+    // a save of `lists` started in the background, see `saved`
+    pub fn saving(&mut self, lists: &[List]) {
+        self.saving = Some(lists.to_vec());
+    }
+
+    // the saves running in the background are done, and have written the files unless
+    // one failed
+    pub fn saved(&mut self, written: bool) {
+        self.stamp = stamp(&self.file_path);
+        if let Some(lists) = self.saving.take().filter(|_| written) {
+            self.base = lists;
+        }
+    }
+
+    pub fn changed(&self) -> bool {
+        stamp(&self.file_path) != self.stamp
+    }
+
+    // the change is let be, e.g. when the files can't be read while still being written
+    pub fn skip(&mut self) {
+        self.stamp = stamp(&self.file_path);
+    }
+}
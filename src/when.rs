@@ -0,0 +1,151 @@
+use chrono::{Datelike, Days, Month, Months, NaiveDate, NaiveTime, Weekday};
+
+// the most words a date at the end of a typed line takes, as in `in 3 days`
+const MAX_WORDS: usize = 3;
+
+// a date the way people say it, like `tomorrow`, `friday`, `on fri`, `next friday`,
+// `next week`, `in 3 days`, `in a month` or `jun 15`, counted from `today`; a weekday on its
+// own is only taken written out, as words like `sat` or `sun` end plenty of tasks
+pub fn parse(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let words: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let next_monday = today.week(Weekday::Mon).first_day() + Days::new(7);
+    match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => today.succ_opt(),
+        [day] if day.len() > 3 => weekday_after(day, today),
+        ["on", day] => weekday_after(day, today),
+        ["next", "week"] => Some(next_monday),
+        ["next", "month"] => today.checked_add_months(Months::new(1)),
+        ["next", day] => {
+            let day = day.parse::<Weekday>().ok()?;
+            next_monday.checked_add_days(Days::new(day.num_days_from_monday() as u64))
+        }
+        ["in", count, unit] => {
+            let count: u32 = match *count {
+                "a" | "an" => 1,
+                count => count.parse().ok()?,
+            };
+            match unit.trim_end_matches('s') {
+                "day" => today.checked_add_days(Days::new(count as u64)),
+                "week" => today.checked_add_days(Days::new(count as u64 * 7)),
+                "month" => today.checked_add_months(Months::new(count)),
+                _ => None,
+            }
+        }
+        [first, second] => {
+            let (month, day) = match first.parse::<Month>() {
+                Ok(month) => (month, second),
+                Err(_) => (second.parse::<Month>().ok()?, first),
+            };
+            let (month, day) = (month.number_from_month(), day.parse().ok()?);
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if date < today {
+                NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+            } else {
+                Some(date)
+            }
+        }
+        _ => None,
+    }
+}
+
+// the first `day` after `today`, a week ahead on that day itself
+fn weekday_after(day: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let day = day.parse::<Weekday>().ok()?;
+    let ahead = (day.num_days_from_monday() + 6 - today.weekday().num_days_from_monday()) % 7 + 1;
+    today.checked_add_days(Days::new(ahead as u64))
+}
+
+// the typed line without the date at its end, that date and the time typed after it as in
+// `tomorrow 14:30`; a line that is only a date stays text
+pub fn split(line: &str, today: NaiveDate) -> (String, Option<NaiveDate>, Option<NaiveTime>) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let time = words
+        .last()
+        .and_then(|word| NaiveTime::parse_from_str(word, "%H:%M").ok());
+    let words = &words[..words.len() - time.is_some() as usize];
+    for count in (1..=MAX_WORDS.min(words.len().saturating_sub(1))).rev() {
+        let (text, date) = words.split_at(words.len() - count);
+        if let Some(date) = parse(date, today) {
+            return (text.join(" "), Some(date), time);
+        }
+    }
+    (line.to_string(), None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 7, 2).unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    fn parsed(words: &str) -> Option<NaiveDate> {
+        parse(&words.split(' ').collect::<Vec<_>>(), today())
+    }
+
+    #[test]
+    fn parse_reads_days_said_the_usual_ways() {
+        assert_eq!(parsed("today"), Some(today()));
+        assert_eq!(parsed("Tomorrow"), Some(date(7, 3)));
+        assert_eq!(parsed("friday"), Some(date(7, 4)));
+        assert_eq!(parsed("wednesday"), Some(date(7, 9)));
+        assert_eq!(parsed("on fri"), Some(date(7, 4)));
+        assert_eq!(parsed("next fri"), Some(date(7, 11)));
+        assert_eq!(parsed("next week"), Some(date(7, 7)));
+        assert_eq!(parsed("next month"), Some(date(8, 2)));
+        assert_eq!(parsed("in 3 days"), Some(date(7, 5)));
+        assert_eq!(parsed("in a week"), Some(date(7, 9)));
+        assert_eq!(parsed("in 2 months"), Some(date(9, 2)));
+        assert_eq!(parsed("jul 15"), Some(date(7, 15)));
+        assert_eq!(parsed("15 july"), Some(date(7, 15)));
+        assert_eq!(parsed("jun 15"), NaiveDate::from_ymd_opt(2026, 6, 15));
+    }
+
+    #[test]
+    fn parse_leaves_abbreviated_weekdays_alone() {
+        assert_eq!(parsed("sat"), None);
+        assert_eq!(parsed("sun"), None);
+        assert_eq!(parsed("in 3 weeks ago"), None);
+        assert_eq!(parsed("feb 30"), None);
+    }
+
+    #[test]
+    fn split_takes_the_date_and_time_off_the_end() {
+        assert_eq!(
+            split("call mom tomorrow 14:30", today()),
+            (
+                "call mom".to_string(),
+                Some(date(7, 3)),
+                NaiveTime::from_hms_opt(14, 30, 0)
+            )
+        );
+        assert_eq!(
+            split("pay rent in 3 days", today()),
+            ("pay rent".to_string(), Some(date(7, 5)), None)
+        );
+        assert_eq!(
+            split("dentist on thu", today()),
+            ("dentist".to_string(), Some(date(7, 3)), None)
+        );
+    }
+
+    #[test]
+    fn split_keeps_lines_without_a_date() {
+        for line in [
+            "sit in the sun",
+            "fix the sat nav",
+            "tomorrow",
+            "meet at 14:30",
+        ] {
+            assert_eq!(split(line, today()), (line.to_string(), None, None));
+        }
+    }
+}